@@ -0,0 +1,440 @@
+use std::{
+    f32::consts::TAU,
+    fs::File,
+    io::{self, BufWriter, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+const SAMPLE_RATE: u32 = 22050;
+
+pub const MARCH_NOTE_COUNT: usize = 4;
+
+/// The descending bass notes of the invaders' march, in Hz.
+const MARCH_NOTE_FREQUENCIES: [f32; MARCH_NOTE_COUNT] = [98.0, 87.3, 82.4, 73.4];
+
+const BELL_MIN_INTERVAL: Duration = Duration::from_millis(150);
+
+/// Where the game's sound goes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum AudioOutput {
+    #[default]
+    Off,
+    Bell,
+    /// Record the session to a WAV file.
+    Wav(PathBuf),
+}
+impl AudioOutput {
+    /// Parses `off`, `bell` or `wav:<path>`.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "off" => Some(Self::Off),
+            "bell" => Some(Self::Bell),
+            _ => value
+                .strip_prefix("wav:")
+                .filter(|path| !path.is_empty())
+                .map(|path| Self::Wav(PathBuf::from(path))),
+        }
+    }
+
+    pub fn open_sink(&self) -> io::Result<Box<dyn AudioSink>> {
+        Ok(match self {
+            Self::Off => Box::new(SilentSink {}),
+            Self::Bell => Box::new(BellSink::new()),
+            Self::Wav(path) => Box::new(WavSynthSink::create(path)?),
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SoundCue {
+    Shoot,
+    EnemyKilled,
+    PlayerHit,
+    /// One note of the march that plays as the formation steps. Cycles through `0..MARCH_NOTE_COUNT`.
+    MarchNote(usize),
+    /// Plays on repeat while the boss saucer is on screen.
+    UfoDrone,
+}
+
+/// Somewhere sound cues are sent to be heard, or recorded.
+pub trait AudioSink {
+    fn play(&mut self, cue: SoundCue);
+
+    /// Called once when the game shuts down, so sinks can write out anything they're holding on to.
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Ignores every cue.
+pub struct SilentSink {}
+impl AudioSink for SilentSink {
+    fn play(&mut self, _: SoundCue) {}
+}
+
+/// Rings the terminal bell for the cues that matter most. The bell only has one sound, so the rest are ignored
+/// rather than turning it into a constant buzz.
+pub struct BellSink {
+    last_ring: Option<Instant>,
+}
+impl BellSink {
+    pub fn new() -> Self {
+        Self { last_ring: None }
+    }
+}
+impl Default for BellSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl AudioSink for BellSink {
+    fn play(&mut self, cue: SoundCue) {
+        if !matches!(cue, SoundCue::PlayerHit | SoundCue::EnemyKilled) {
+            return;
+        }
+
+        if self
+            .last_ring
+            .is_some_and(|last_ring| last_ring.elapsed() < BELL_MIN_INTERVAL)
+        {
+            return;
+        }
+
+        let mut stdout = io::stdout();
+        let _ = stdout.write_all(b"\x07").and_then(|_| stdout.flush());
+
+        self.last_ring = Some(Instant::now());
+    }
+}
+
+/// Synthesizes every cue and mixes it into a recording of the session, streamed to a WAV file as it goes. Handy for
+/// working on sound design without any audio hardware.
+pub struct WavSynthSink {
+    file: BufWriter<File>,
+    started_at: Instant,
+    /// The samples that cues still playing can mix into, starting at `written_sample_count`.
+    pending_samples: Vec<f32>,
+    written_sample_count: usize,
+    /// The first write that failed. Nothing more is written after it, and it's reported when the sink finishes.
+    error: Option<io::Error>,
+}
+impl WavSynthSink {
+    /// Creates the file straight away, so a bad path is reported at launch rather than lost at shutdown.
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+
+        // The sizes aren't known until the game shuts down, so they're filled in then.
+        write_wav_header(&mut file, 0)?;
+
+        Ok(Self {
+            file,
+            started_at: Instant::now(),
+            pending_samples: vec![],
+            written_sample_count: 0,
+            error: None,
+        })
+    }
+
+    /// Writes out every sample before `end`, padding with silence past the pending ones.
+    fn write_samples_until(&mut self, end: usize) -> io::Result<()> {
+        let count = end.saturating_sub(self.written_sample_count);
+        let pending_count = count.min(self.pending_samples.len());
+
+        for sample in self.pending_samples.drain(..pending_count) {
+            write_sample(&mut self.file, sample)?;
+        }
+
+        for _ in pending_count..count {
+            write_sample(&mut self.file, 0.0)?;
+        }
+
+        self.written_sample_count += count;
+
+        Ok(())
+    }
+
+    fn write_sizes(&mut self) -> io::Result<()> {
+        let data_size = self.written_sample_count as u32 * 2;
+
+        self.file.seek(SeekFrom::Start(4))?;
+        self.file.write_all(&(36 + data_size).to_le_bytes())?;
+        self.file.seek(SeekFrom::Start(40))?;
+        self.file.write_all(&data_size.to_le_bytes())?;
+
+        self.file.flush()
+    }
+}
+impl AudioSink for WavSynthSink {
+    fn play(&mut self, cue: SoundCue) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let start = (self.started_at.elapsed().as_secs_f64() * SAMPLE_RATE as f64).round() as usize;
+
+        // Cues only ever start later than the ones before, so nothing before this one can change any more.
+        if let Err(error) = self.write_samples_until(start) {
+            self.error = Some(error);
+            return;
+        }
+
+        let offset = start - self.written_sample_count;
+        let cue_samples = synthesize(cue);
+
+        if self.pending_samples.len() < offset + cue_samples.len() {
+            self.pending_samples.resize(offset + cue_samples.len(), 0.0);
+        }
+
+        for (index, sample) in cue_samples.into_iter().enumerate() {
+            self.pending_samples[offset + index] += sample;
+        }
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+
+        self.write_samples_until(self.written_sample_count + self.pending_samples.len())?;
+        self.write_sizes()
+    }
+}
+
+/// Renders a cue as samples from -1 to 1.
+pub fn synthesize(cue: SoundCue) -> Vec<f32> {
+    match cue {
+        SoundCue::Shoot => render(0.12, |t, progress| {
+            // A quick downward zap.
+            square(t, 1200.0 - 800.0 * progress) * 0.25 * (1.0 - progress)
+        }),
+        SoundCue::EnemyKilled => {
+            let mut noise = Noise::new(0x2545f491);
+
+            render(0.2, |_, progress| {
+                noise.next() * 0.35 * (1.0 - progress).powi(2)
+            })
+        }
+        SoundCue::PlayerHit => {
+            let mut noise = Noise::new(0x9e3779b9);
+
+            render(0.5, |t, progress| {
+                (noise.next() * 0.5 + square(t, 110.0 - 50.0 * progress) * 0.5)
+                    * 0.4
+                    * (1.0 - progress)
+            })
+        }
+        SoundCue::MarchNote(note) => {
+            let frequency = MARCH_NOTE_FREQUENCIES[note % MARCH_NOTE_COUNT];
+
+            render(0.08, |t, progress| {
+                square(t, frequency) * 0.3 * (1.0 - progress)
+            })
+        }
+        SoundCue::UfoDrone => render(0.15, |t, _| {
+            // Wobbles between 400 and 600 Hz, like the original's siren.
+            triangle(t, 500.0 + 100.0 * (t * TAU * 8.0).sin()) * 0.15
+        }),
+    }
+}
+
+/// Calls `sample` for every sample of a sound lasting `seconds`, passing the time in seconds and how far through the
+/// sound it is, from 0 to 1.
+fn render(seconds: f32, mut sample: impl FnMut(f32, f32) -> f32) -> Vec<f32> {
+    let sample_count = (seconds * SAMPLE_RATE as f32) as usize;
+
+    (0..sample_count)
+        .map(|index| {
+            sample(
+                index as f32 / SAMPLE_RATE as f32,
+                index as f32 / sample_count as f32,
+            )
+        })
+        .collect()
+}
+
+fn square(t: f32, frequency: f32) -> f32 {
+    if (t * frequency).fract() < 0.5 {
+        1.0
+    } else {
+        -1.0
+    }
+}
+
+fn triangle(t: f32, frequency: f32) -> f32 {
+    4.0 * ((t * frequency).fract() - 0.5).abs() - 1.0
+}
+
+/// Cheap white noise. Deterministic, so a cue sounds the same every time it plays.
+struct Noise {
+    state: u32,
+}
+impl Noise {
+    fn new(seed: u32) -> Self {
+        Self { state: seed }
+    }
+
+    fn next(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+
+        self.state as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
+
+/// Writes the header for mono 16-bit PCM holding `data_size` bytes of samples.
+fn write_wav_header(file: &mut impl Write, data_size: u32) -> io::Result<()> {
+    file.write_all(b"RIFF")?;
+    file.write_all(&(36 + data_size).to_le_bytes())?;
+    file.write_all(b"WAVE")?;
+
+    file.write_all(b"fmt ")?;
+    file.write_all(&16u32.to_le_bytes())?;
+    // PCM, mono.
+    file.write_all(&1u16.to_le_bytes())?;
+    file.write_all(&1u16.to_le_bytes())?;
+    file.write_all(&SAMPLE_RATE.to_le_bytes())?;
+    // Byte rate, block align and bits per sample.
+    file.write_all(&(SAMPLE_RATE * 2).to_le_bytes())?;
+    file.write_all(&2u16.to_le_bytes())?;
+    file.write_all(&16u16.to_le_bytes())?;
+
+    file.write_all(b"data")?;
+    file.write_all(&data_size.to_le_bytes())
+}
+
+/// Samples outside -1 to 1 are clipped.
+fn write_sample(file: &mut impl Write, sample: f32) -> io::Result<()> {
+    file.write_all(&((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).to_le_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use super::*;
+
+    /// A path in the temp directory that no other test uses.
+    fn temp_wav_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("space_invaders_{}_{name}.wav", process::id()))
+    }
+
+    fn read_u32(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    fn encode(samples: &[f32]) -> Vec<u8> {
+        let mut bytes = vec![];
+
+        for sample in samples {
+            write_sample(&mut bytes, *sample).unwrap();
+        }
+
+        bytes
+    }
+
+    #[test]
+    fn writes_a_mono_16_bit_pcm_header() {
+        let mut header = vec![];
+
+        write_wav_header(&mut header, 100).unwrap();
+
+        assert_eq!(header.len(), 44);
+        assert_eq!(&header[0..4], b"RIFF");
+        assert_eq!(read_u32(&header, 4), 136);
+        assert_eq!(&header[8..16], b"WAVEfmt ");
+        assert_eq!(read_u32(&header, 16), 16);
+        assert_eq!(&header[20..24], &[1, 0, 1, 0]);
+        assert_eq!(read_u32(&header, 24), SAMPLE_RATE);
+        assert_eq!(read_u32(&header, 28), SAMPLE_RATE * 2);
+        assert_eq!(&header[32..36], &[2, 0, 16, 0]);
+        assert_eq!(&header[36..40], b"data");
+        assert_eq!(read_u32(&header, 40), 100);
+    }
+
+    #[test]
+    fn writes_samples_as_little_endian_16_bit() {
+        assert_eq!(encode(&[0.0]), [0, 0]);
+        assert_eq!(encode(&[1.0]), i16::MAX.to_le_bytes());
+        assert_eq!(encode(&[-1.0]), (-i16::MAX).to_le_bytes());
+        assert_eq!(encode(&[0.5]), 16383i16.to_le_bytes());
+    }
+
+    #[test]
+    fn clips_samples_outside_the_range() {
+        assert_eq!(encode(&[2.5]), encode(&[1.0]));
+        assert_eq!(encode(&[-7.0]), encode(&[-1.0]));
+    }
+
+    #[test]
+    fn an_empty_recording_is_just_a_header() {
+        let path = temp_wav_path("empty");
+        let mut sink = WavSynthSink::create(&path).unwrap();
+
+        sink.finish().unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(bytes.len(), 44);
+        assert_eq!(read_u32(&bytes, 4), 36);
+        assert_eq!(read_u32(&bytes, 40), 0);
+    }
+
+    #[test]
+    fn pads_with_silence_past_the_pending_samples() {
+        let path = temp_wav_path("padding");
+        let mut sink = WavSynthSink::create(&path).unwrap();
+
+        sink.pending_samples = vec![0.5, -0.5];
+        sink.write_samples_until(4).unwrap();
+        sink.finish().unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(read_u32(&bytes, 4), 36 + 8);
+        assert_eq!(read_u32(&bytes, 40), 8);
+        assert_eq!(bytes[44..], encode(&[0.5, -0.5, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn finishing_writes_out_the_cues_still_playing() {
+        let path = temp_wav_path("cue");
+        let mut sink = WavSynthSink::create(&path).unwrap();
+
+        sink.play(SoundCue::Shoot);
+        sink.finish().unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        let cue = encode(&synthesize(SoundCue::Shoot));
+
+        assert_eq!(read_u32(&bytes, 40) as usize, bytes.len() - 44);
+        assert_eq!(read_u32(&bytes, 4) as usize, bytes.len() - 8);
+        assert!(bytes.ends_with(&cue));
+    }
+
+    #[test]
+    fn overlapping_cues_are_mixed() {
+        let path = temp_wav_path("mix");
+        let mut sink = WavSynthSink::create(&path).unwrap();
+
+        sink.pending_samples = vec![0.25; 3];
+        sink.play(SoundCue::MarchNote(0));
+
+        let _ = fs::remove_file(&path);
+
+        // The note starts wherever the recording had got to, which the three earlier samples may already be behind.
+        for (index, sample) in synthesize(SoundCue::MarchNote(0)).iter().enumerate() {
+            let under = if sink.written_sample_count + index < 3 {
+                0.25
+            } else {
+                0.0
+            };
+
+            assert_eq!(sink.pending_samples[index], sample + under);
+        }
+    }
+}
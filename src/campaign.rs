@@ -0,0 +1,451 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::{Level, LevelError};
+
+/// The file in a level pack that lists its waves.
+pub const CAMPAIGN_FILE_NAME: &str = "campaign.txt";
+
+const BUILT_IN_CAMPAIGN: &str = include_str!("../campaign/campaign.txt");
+const BUILT_IN_CAMPAIGN_LEVELS: [(&str, &str); 5] = [
+    (
+        "first_contact.txt",
+        include_str!("../campaign/first_contact.txt"),
+    ),
+    ("the_wall.txt", include_str!("../campaign/the_wall.txt")),
+    ("crossfire.txt", include_str!("../campaign/crossfire.txt")),
+    ("the_swarm.txt", include_str!("../campaign/the_swarm.txt")),
+    ("last_line.txt", include_str!("../campaign/last_line.txt")),
+];
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct CampaignWave {
+    /// The formation, or for a boss wave, the level it's fought in.
+    pub level: Level,
+    /// Set for a boss wave, to the boss's health.
+    pub boss_hp: Option<u64>,
+    /// Shown before the wave starts, a line at a time.
+    pub interlude: Vec<String>,
+    /// Losing every life from here on starts over at this wave rather than the first.
+    pub is_checkpoint: bool,
+}
+
+/// An ordered run of waves read from a level pack: a directory holding a `campaign.txt` and the level files it
+/// names.
+///
+/// `campaign.txt` is a list of `key=value` lines, read from the top. `name` names the campaign. `level=<file>` adds
+/// a wave laid out from a level file in the pack, and `boss=<health>` adds a boss wave. `interlude=<text>` adds a
+/// line to the story shown before the next wave, and `checkpoint` on a line of its own makes the next wave a
+/// checkpoint. The last wave has to be a boss wave, and any interlude after it is shown once it's beaten.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Campaign {
+    pub name: String,
+    pub waves: Vec<CampaignWave>,
+    /// Shown after the final boss.
+    pub epilogue: Vec<String>,
+}
+impl Campaign {
+    /// The campaign the game comes with.
+    pub fn built_in() -> Self {
+        Self::parse(BUILT_IN_CAMPAIGN, |file_name| {
+            let (_, contents) = BUILT_IN_CAMPAIGN_LEVELS
+                .iter()
+                .find(|(name, _)| *name == file_name)
+                .ok_or_else(|| format!("no built-in level {file_name}"))?;
+
+            Level::parse(contents).map_err(|errors| format!("{}", errors[0]))
+        })
+        .expect("the built-in campaign should be valid")
+    }
+
+    /// Loads the level pack in a directory. The name defaults to the directory's name.
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let path = dir.join(CAMPAIGN_FILE_NAME);
+        let contents = fs::read_to_string(&path)
+            .map_err(|e| format!("Could not read campaign file {}: {e}", path.display()))?;
+
+        let mut campaign = Self::parse(&contents, |file_name| Level::load(&dir.join(file_name)))
+            .map_err(|errors| {
+                errors
+                    .iter()
+                    .map(|error| format!("Invalid campaign file {}:{error}", path.display()))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            })?;
+
+        if campaign.name.is_empty() {
+            if let Some(dir_name) = dir.file_name() {
+                campaign.name = dir_name.to_string_lossy().into_owned();
+            }
+        }
+
+        Ok(campaign)
+    }
+
+    /// Reads a campaign, loading each level it names with `load_level`. Reports every problem with it rather than
+    /// stopping at the first.
+    pub fn parse(
+        contents: &str,
+        load_level: impl Fn(&str) -> Result<Level, String>,
+    ) -> Result<Self, Vec<LevelError>> {
+        let mut errors = vec![];
+        let mut campaign = Self {
+            name: String::new(),
+            waves: vec![],
+            epilogue: vec![],
+        };
+        let mut interlude = vec![];
+        let mut checkpoint_line = None;
+
+        for (line_number, line) in contents
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line))
+        {
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            let column = line.len() - line.trim_start().len() + 1;
+
+            if trimmed == "checkpoint" {
+                checkpoint_line = Some(line_number);
+                continue;
+            }
+
+            let Some((key, value)) = trimmed.split_once('=') else {
+                errors.push(LevelError::new(
+                    line_number,
+                    column,
+                    "expected key=value or checkpoint",
+                ));
+                continue;
+            };
+
+            let (key, value) = (key.trim(), value.trim());
+            let value_column = column + trimmed.find('=').unwrap_or(0) + 1;
+
+            let (level, boss_hp) = match key {
+                "name" => {
+                    campaign.name = String::from(value);
+                    continue;
+                }
+                "interlude" => {
+                    interlude.push(String::from(value));
+                    continue;
+                }
+                "level" => match load_level(value) {
+                    Ok(level) => (level, None),
+                    Err(error) => {
+                        errors.push(LevelError::new(line_number, value_column, error));
+                        continue;
+                    }
+                },
+                "boss" => match value.parse() {
+                    // Boss waves keep the formation's speed and fire rate from the wave before.
+                    Ok(hp) if hp > 0 => (
+                        campaign
+                            .waves
+                            .last()
+                            .map(|wave| wave.level.clone())
+                            .unwrap_or_else(Level::classic),
+                        Some(hp),
+                    ),
+                    _ => {
+                        errors.push(LevelError::new(
+                            line_number,
+                            value_column,
+                            format!("boss expects a health above 0, got '{value}'"),
+                        ));
+                        continue;
+                    }
+                },
+                _ => {
+                    errors.push(LevelError::new(
+                        line_number,
+                        column,
+                        format!("unknown key '{key}'"),
+                    ));
+                    continue;
+                }
+            };
+
+            campaign.waves.push(CampaignWave {
+                level,
+                boss_hp,
+                interlude: std::mem::take(&mut interlude),
+                is_checkpoint: checkpoint_line.take().is_some(),
+            });
+        }
+
+        if let Some(line_number) = checkpoint_line {
+            errors.push(LevelError::new(
+                line_number,
+                1,
+                "a checkpoint has to come before a wave",
+            ));
+        }
+
+        if campaign
+            .waves
+            .last()
+            .is_none_or(|wave| wave.boss_hp.is_none())
+        {
+            errors.push(LevelError::new(
+                contents.lines().count() + 1,
+                1,
+                "a campaign ends with its final boss",
+            ));
+        }
+
+        campaign.epilogue = interlude;
+
+        if errors.is_empty() {
+            Ok(campaign)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// The wave by its number. Waves count from 1.
+    pub fn wave(&self, number: u64) -> Option<&CampaignWave> {
+        self.waves.get((number as usize).checked_sub(1)?)
+    }
+
+    pub fn is_final_wave(&self, number: u64) -> bool {
+        number == self.waves.len() as u64
+    }
+
+    /// The level of every wave, in order, for the formations to be laid out from.
+    pub fn levels(&self) -> Vec<Level> {
+        self.waves.iter().map(|wave| wave.level.clone()).collect()
+    }
+
+    /// The key the campaign's progress is saved under.
+    pub fn slug(&self) -> String {
+        self.name
+            .to_lowercase()
+            .chars()
+            .map(|character| {
+                if character.is_alphanumeric() {
+                    character
+                } else {
+                    '-'
+                }
+            })
+            .collect()
+    }
+}
+
+/// How far the player has got through a campaign, kept between sessions.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct CampaignProgress {
+    /// The last checkpoint reached, as a wave number.
+    pub checkpoint: Option<u64>,
+    pub is_completed: bool,
+}
+impl CampaignProgress {
+    /// Reads the campaign's progress out of the saved progress of every campaign.
+    pub fn from_saved(saved: &HashMap<String, String>, slug: &str) -> Self {
+        Self {
+            checkpoint: saved
+                .get(&format!("{slug}.checkpoint"))
+                .and_then(|value| value.parse().ok()),
+            is_completed: saved
+                .get(&format!("{slug}.completed"))
+                .is_some_and(|value| value == "true"),
+        }
+    }
+
+    /// Writes the campaign's progress into the saved progress of every campaign, leaving the others as they were.
+    pub fn write_to(&self, saved: &mut HashMap<String, String>, slug: &str) {
+        let checkpoint_key = format!("{slug}.checkpoint");
+
+        match self.checkpoint {
+            Some(checkpoint) => saved.insert(checkpoint_key, checkpoint.to_string()),
+            None => saved.remove(&checkpoint_key),
+        };
+
+        saved.insert(format!("{slug}.completed"), self.is_completed.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, path::PathBuf, process};
+
+    use super::*;
+
+    fn load_level(file_name: &str) -> Result<Level, String> {
+        match file_name {
+            "wave.txt" => Ok(Level::classic()),
+            _ => Err(format!("no level {file_name}")),
+        }
+    }
+
+    fn parse_errors(contents: &str) -> Vec<LevelError> {
+        Campaign::parse(contents, load_level).unwrap_err()
+    }
+
+    /// A fresh level pack directory holding the given files.
+    fn temp_pack(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = env::temp_dir().join(format!("space_invaders_{}_{name}", process::id()));
+
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        for (file_name, contents) in files {
+            fs::write(dir.join(file_name), contents).unwrap();
+        }
+
+        dir
+    }
+
+    #[test]
+    fn reads_the_waves_story_and_checkpoints() {
+        let campaign = Campaign::parse(
+            "name=Test\ninterlude=They're here.\nlevel=wave.txt\ncheckpoint\nboss=30\ninterlude=The end.\n",
+            load_level,
+        )
+        .unwrap();
+
+        assert_eq!(campaign.name, "Test");
+        assert_eq!(campaign.waves.len(), 2);
+        assert_eq!(campaign.waves[0].interlude, vec!["They're here."]);
+        assert!(!campaign.waves[0].is_checkpoint);
+        assert_eq!(campaign.waves[1].boss_hp, Some(30));
+        assert!(campaign.waves[1].is_checkpoint);
+        assert_eq!(campaign.epilogue, vec!["The end."]);
+    }
+
+    #[test]
+    fn reports_lines_that_arent_key_values() {
+        assert_eq!(
+            parse_errors("  onwards\nboss=10\n"),
+            vec![LevelError::new(1, 3, "expected key=value or checkpoint")]
+        );
+    }
+
+    #[test]
+    fn reports_unknown_keys() {
+        assert_eq!(
+            parse_errors("music=loud\nboss=10\n"),
+            vec![LevelError::new(1, 1, "unknown key 'music'")]
+        );
+    }
+
+    #[test]
+    fn reports_levels_that_cant_be_loaded_at_their_value() {
+        assert_eq!(
+            parse_errors("level = missing.txt\nboss=10\n"),
+            vec![LevelError::new(1, 8, "no level missing.txt")]
+        );
+    }
+
+    #[test]
+    fn reports_bosses_without_health() {
+        assert_eq!(
+            parse_errors("level=wave.txt\nboss=0\nboss=lots\nboss=10\n"),
+            vec![
+                LevelError::new(2, 6, "boss expects a health above 0, got '0'"),
+                LevelError::new(3, 6, "boss expects a health above 0, got 'lots'"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_a_checkpoint_after_the_last_wave() {
+        assert_eq!(
+            parse_errors("boss=10\ncheckpoint\n"),
+            vec![LevelError::new(
+                2,
+                1,
+                "a checkpoint has to come before a wave"
+            )]
+        );
+    }
+
+    #[test]
+    fn reports_a_campaign_that_doesnt_end_with_a_boss() {
+        assert_eq!(
+            parse_errors("boss=10\nlevel=wave.txt\n"),
+            vec![LevelError::new(3, 1, "a campaign ends with its final boss")]
+        );
+    }
+
+    #[test]
+    fn reports_an_empty_campaign() {
+        assert_eq!(
+            parse_errors("# Nothing yet\n"),
+            vec![LevelError::new(2, 1, "a campaign ends with its final boss")]
+        );
+    }
+
+    #[test]
+    fn reports_every_problem_at_once() {
+        assert_eq!(parse_errors("music=loud\nlevel=missing.txt\n").len(), 3);
+    }
+
+    #[test]
+    fn names_a_pack_after_its_directory() {
+        let dir = temp_pack("unnamed_pack", &[(CAMPAIGN_FILE_NAME, "boss=10\n")]);
+
+        let campaign = Campaign::load(&dir).unwrap();
+
+        assert_eq!(campaign.name, dir.file_name().unwrap().to_string_lossy());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reports_a_pack_without_a_campaign_file() {
+        let dir = temp_pack("empty_pack", &[]);
+
+        let error = Campaign::load(&dir).unwrap_err();
+
+        assert!(error.starts_with(&format!(
+            "Could not read campaign file {}",
+            dir.join(CAMPAIGN_FILE_NAME).display()
+        )));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reports_problems_with_the_path_of_the_campaign_file() {
+        let dir = temp_pack(
+            "broken_pack",
+            &[(CAMPAIGN_FILE_NAME, "level=missing.txt\nboss=10\n")],
+        );
+        let path = dir.join(CAMPAIGN_FILE_NAME);
+
+        let error = Campaign::load(&dir).unwrap_err();
+
+        assert!(error.starts_with(&format!(
+            "Invalid campaign file {}:1:7: Could not read level file {}",
+            path.display(),
+            dir.join("missing.txt").display()
+        )));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn loads_levels_from_the_pack() {
+        let dir = temp_pack(
+            "level_pack",
+            &[
+                (CAMPAIGN_FILE_NAME, "name=Pack\nlevel=wave.txt\nboss=10\n"),
+                ("wave.txt", "map:\nSCO\n"),
+            ],
+        );
+
+        let campaign = Campaign::load(&dir).unwrap();
+
+        assert_eq!(campaign.name, "Pack");
+        assert_eq!(campaign.waves[0].level.name, "wave");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use thomas::Component;
+
+use crate::Locale;
+
+pub struct AchievementDefinition {
+  pub id: &'static str,
+  pub goal: u64,
+}
+impl AchievementDefinition {
+  pub fn name(&self, locale: &Locale) -> String {
+    String::from(locale.text(&format!("achievement.{}.name", self.id)))
+  }
+
+  pub fn description(&self, locale: &Locale) -> String {
+    String::from(locale.text(&format!("achievement.{}.description", self.id)))
+  }
+}
+
+pub const ACHIEVEMENT_FLAWLESS: &str = "flawless";
+pub const ACHIEVEMENT_DOUBLE_DIGITS: &str = "double-digits";
+pub const ACHIEVEMENT_GIANT_SLAYER: &str = "giant-slayer";
+pub const ACHIEVEMENT_LAST_STAND: &str = "last-stand";
+pub const ACHIEVEMENT_COMBO_MASTER: &str = "combo-master";
+pub const ACHIEVEMENT_EXTERMINATOR: &str = "exterminator";
+
+pub const ACHIEVEMENTS: [AchievementDefinition; 6] = [
+  AchievementDefinition {
+    id: ACHIEVEMENT_FLAWLESS,
+    goal: 1,
+  },
+  AchievementDefinition {
+    id: ACHIEVEMENT_DOUBLE_DIGITS,
+    goal: 10,
+  },
+  AchievementDefinition {
+    id: ACHIEVEMENT_GIANT_SLAYER,
+    goal: 1,
+  },
+  AchievementDefinition {
+    id: ACHIEVEMENT_LAST_STAND,
+    goal: 1,
+  },
+  AchievementDefinition {
+    id: ACHIEVEMENT_COMBO_MASTER,
+    goal: 1,
+  },
+  AchievementDefinition {
+    id: ACHIEVEMENT_EXTERMINATOR,
+    goal: 1000,
+  },
+];
+
+/// Progress towards every achievement. An achievement is unlocked once its progress reaches its goal.
+#[derive(Component)]
+pub struct Achievements {
+  pub progress: HashMap<&'static str, u64>,
+  /// How many of the current run's kills have already been counted towards lifetime progress.
+  pub counted_run_kills: u64,
+  pub has_unsaved_progress: bool,
+}
+impl Achievements {
+  pub fn from_saved(saved: &HashMap<String, String>) -> Self {
+    Self {
+      progress: ACHIEVEMENTS
+        .iter()
+        .map(|achievement| {
+          (
+            achievement.id,
+            saved
+              .get(achievement.id)
+              .and_then(|value| value.parse().ok())
+              .unwrap_or(0),
+          )
+        })
+        .collect(),
+      counted_run_kills: 0,
+      has_unsaved_progress: false,
+    }
+  }
+
+  pub fn to_saved(&self) -> Vec<(String, String)> {
+    ACHIEVEMENTS
+      .iter()
+      .map(|achievement| {
+        (
+          String::from(achievement.id),
+          self.progress_of(achievement.id).to_string(),
+        )
+      })
+      .collect()
+  }
+
+  pub fn progress_of(&self, id: &str) -> u64 {
+    *self.progress.get(id).unwrap_or(&0)
+  }
+
+  pub fn is_unlocked(&self, achievement: &AchievementDefinition) -> bool {
+    self.progress_of(achievement.id) >= achievement.goal
+  }
+
+  pub fn unlocked_count(&self) -> usize {
+    ACHIEVEMENTS
+      .iter()
+      .filter(|achievement| self.is_unlocked(achievement))
+      .count()
+  }
+
+  /// Raises the achievement's progress to `value`, capped at its goal. Progress never goes down. Returns the
+  /// achievement if this is what unlocked it.
+  pub fn raise_progress(&mut self, id: &str, value: u64) -> Option<&'static AchievementDefinition> {
+    let achievement = ACHIEVEMENTS.iter().find(|achievement| achievement.id == id)?;
+    let was_unlocked = self.is_unlocked(achievement);
+    let new_progress = u64::min(value, achievement.goal);
+
+    if new_progress > self.progress_of(id) {
+      self.progress.insert(achievement.id, new_progress);
+      self.has_unsaved_progress = true;
+    }
+
+    if !was_unlocked && self.is_unlocked(achievement) {
+      Some(achievement)
+    } else {
+      None
+    }
+  }
+}
+
+/// Marks the text entities that make up the achievements screen.
+#[derive(Component)]
+pub struct AchievementsScreen {}
@@ -0,0 +1,94 @@
+use thomas::{Component, Rgb, Timer};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AnimationFrame {
+  pub display: char,
+  pub color: Option<Rgb>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AnimationTiming {
+  /// Moves to the next frame after this many milliseconds.
+  Interval(u128),
+  /// Moves to the next frame each time the enemy formation takes a step.
+  FormationStep,
+}
+
+/// Swaps the display and colour of the entity's `TerminalRenderer` through a list of frames.
+#[derive(Component)]
+pub struct Animation {
+  pub frames: Vec<AnimationFrame>,
+  pub timing: AnimationTiming,
+  pub is_looping: bool,
+  /// When a non-looping animation finishes, whether to destroy its entity instead of just removing the animation.
+  pub destroy_when_finished: bool,
+  pub current_frame: usize,
+  pub frame_timer: Timer,
+}
+impl Animation {
+  pub fn looping(frames: Vec<AnimationFrame>, timing: AnimationTiming) -> Self {
+    Self {
+      frames,
+      timing,
+      is_looping: true,
+      destroy_when_finished: false,
+      current_frame: 0,
+      frame_timer: Timer::start_new(),
+    }
+  }
+
+  pub fn once(frames: Vec<AnimationFrame>, frame_interval_millis: u128, destroy_when_finished: bool) -> Self {
+    Self {
+      frames,
+      timing: AnimationTiming::Interval(frame_interval_millis),
+      is_looping: false,
+      destroy_when_finished,
+      current_frame: 0,
+      frame_timer: Timer::start_new(),
+    }
+  }
+
+  /// Flashes `frame` off and on, finishing on it.
+  pub fn blink(frame: AnimationFrame, times: usize, frame_interval_millis: u128) -> Self {
+    let hidden_frame = AnimationFrame {
+      display: ' ',
+      color: frame.color,
+    };
+
+    Self::once(
+      (0..times).flat_map(|_| [hidden_frame, frame]).collect(),
+      frame_interval_millis,
+      false,
+    )
+  }
+
+  pub fn frame(&self) -> AnimationFrame {
+    self.frames[self.current_frame]
+  }
+
+  pub fn advance(&mut self) {
+    if self.current_frame + 1 < self.frames.len() {
+      self.current_frame += 1;
+    } else if self.is_looping {
+      self.current_frame = 0;
+    }
+
+    self.frame_timer.restart();
+  }
+
+  pub fn restart(&mut self) {
+    self.current_frame = 0;
+    self.frame_timer.restart();
+  }
+
+  pub fn is_finished(&self) -> bool {
+    !self.is_looping
+      && self.current_frame + 1 >= self.frames.len()
+      && match self.timing {
+        AnimationTiming::Interval(frame_interval_millis) => {
+          self.frame_timer.elapsed_millis() >= frame_interval_millis
+        }
+        AnimationTiming::FormationStep => false,
+      }
+  }
+}
@@ -0,0 +1,30 @@
+use thomas::{Component, Timer};
+
+use crate::{AudioSink, SoundCue, MARCH_NOTE_COUNT};
+
+#[derive(Component)]
+pub struct Audio {
+  pub sink: Box<dyn AudioSink>,
+  /// Which of the four march notes plays on the formation's next step.
+  pub next_march_note: usize,
+  pub drone_timer: Timer,
+}
+impl Audio {
+  pub fn new(sink: Box<dyn AudioSink>) -> Self {
+    Self {
+      sink,
+      next_march_note: 0,
+      drone_timer: Timer::start_new(),
+    }
+  }
+
+  pub fn play(&mut self, cue: SoundCue) {
+    self.sink.play(cue);
+  }
+
+  pub fn play_march_note(&mut self) {
+    self.sink.play(SoundCue::MarchNote(self.next_march_note));
+
+    self.next_march_note = (self.next_march_note + 1) % MARCH_NOTE_COUNT;
+  }
+}
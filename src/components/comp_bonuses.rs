@@ -0,0 +1,210 @@
+use thomas::Component;
+
+use crate::{
+  RunStats, EXTRA_LIFE_MAX_AWARDED, EXTRA_LIFE_SCORE_INTERVAL, WAVE_MAX_ACCURACY_BONUS,
+  WAVE_NO_DAMAGE_BONUS, WAVE_TIME_BONUS_PAR_MILLIS, WAVE_TIME_BONUS_PER_SECOND,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct BonusRules {
+  /// The score at which the first extra life is awarded.
+  pub extra_life_first_threshold: u64,
+  /// How many more points are needed for each extra life after the first.
+  pub extra_life_interval: u64,
+  /// The most extra lives that can be awarded in a single run.
+  pub max_extra_lives: u8,
+  /// Clearing a wave faster than this earns a time bonus.
+  pub time_bonus_par_millis: u128,
+  pub time_bonus_per_second: u64,
+  pub no_damage_bonus: u64,
+  /// The accuracy bonus for a wave cleared without a single miss. Lower accuracy earns proportionally less.
+  pub max_accuracy_bonus: u64,
+}
+impl Default for BonusRules {
+  fn default() -> Self {
+    Self {
+      extra_life_first_threshold: EXTRA_LIFE_SCORE_INTERVAL,
+      extra_life_interval: EXTRA_LIFE_SCORE_INTERVAL,
+      max_extra_lives: EXTRA_LIFE_MAX_AWARDED,
+      time_bonus_par_millis: WAVE_TIME_BONUS_PAR_MILLIS,
+      time_bonus_per_second: WAVE_TIME_BONUS_PER_SECOND,
+      no_damage_bonus: WAVE_NO_DAMAGE_BONUS,
+      max_accuracy_bonus: WAVE_MAX_ACCURACY_BONUS,
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WaveBonus {
+  pub time: u64,
+  pub no_damage: u64,
+  pub accuracy: u64,
+}
+impl WaveBonus {
+  pub fn total(&self) -> u64 {
+    self.time + self.no_damage + self.accuracy
+  }
+}
+
+#[derive(Component)]
+pub struct Bonuses {
+  pub rules: BonusRules,
+  pub extra_lives_awarded: u8,
+}
+impl Bonuses {
+  /// The score needed for the next extra life, or `None` if the run has had all the extra lives it can get.
+  pub fn next_extra_life_threshold(&self) -> Option<u64> {
+    if self.extra_lives_awarded >= self.rules.max_extra_lives {
+      return None;
+    }
+
+    Some(
+      self.rules.extra_life_first_threshold
+        + self.rules.extra_life_interval * self.extra_lives_awarded as u64,
+    )
+  }
+
+  pub fn wave_bonus(&self, wave_stats: &RunStats) -> WaveBonus {
+    let wave_time_millis = *wave_stats.wave_times_millis.last().unwrap_or(&u128::MAX);
+
+    WaveBonus {
+      time: (self.rules.time_bonus_par_millis.saturating_sub(wave_time_millis) / 1000) as u64
+        * self.rules.time_bonus_per_second,
+      no_damage: if wave_stats.lives_lost == 0 {
+        self.rules.no_damage_bonus
+      } else {
+        0
+      },
+      accuracy: (wave_stats.accuracy() * self.rules.max_accuracy_bonus as f64).round() as u64,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn rules() -> BonusRules {
+    BonusRules {
+      extra_life_first_threshold: 10000,
+      extra_life_interval: 20000,
+      max_extra_lives: 2,
+      time_bonus_par_millis: 30000,
+      time_bonus_per_second: 50,
+      no_damage_bonus: 1000,
+      max_accuracy_bonus: 2000,
+    }
+  }
+
+  fn bonuses(extra_lives_awarded: u8) -> Bonuses {
+    Bonuses {
+      rules: rules(),
+      extra_lives_awarded,
+    }
+  }
+
+  fn wave_stats(time_millis: u128, shots_fired: u64, hits: u64, lives_lost: u64) -> RunStats {
+    RunStats {
+      shots_fired,
+      hits,
+      lives_lost,
+      wave_times_millis: vec![time_millis],
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn the_first_extra_life_comes_at_the_first_threshold() {
+    assert_eq!(bonuses(0).next_extra_life_threshold(), Some(10000));
+  }
+
+  #[test]
+  fn each_later_extra_life_is_an_interval_further_on() {
+    assert_eq!(bonuses(1).next_extra_life_threshold(), Some(30000));
+  }
+
+  #[test]
+  fn there_are_no_more_thresholds_once_every_extra_life_is_awarded() {
+    assert_eq!(bonuses(2).next_extra_life_threshold(), None);
+    assert_eq!(bonuses(3).next_extra_life_threshold(), None);
+  }
+
+  #[test]
+  fn the_default_thresholds_are_evenly_spaced() {
+    let bonuses = Bonuses {
+      rules: BonusRules::default(),
+      extra_lives_awarded: 1,
+    };
+
+    assert_eq!(
+      bonuses.next_extra_life_threshold(),
+      Some(EXTRA_LIFE_SCORE_INTERVAL * 2)
+    );
+  }
+
+  #[test]
+  fn the_time_bonus_counts_whole_seconds_under_par() {
+    let bonus = bonuses(0).wave_bonus(&wave_stats(19500, 0, 0, 0));
+
+    assert_eq!(bonus.time, 10 * 50);
+  }
+
+  #[test]
+  fn a_wave_over_par_earns_no_time_bonus() {
+    let bonus = bonuses(0).wave_bonus(&wave_stats(45000, 0, 0, 0));
+
+    assert_eq!(bonus.time, 0);
+  }
+
+  #[test]
+  fn a_wave_without_a_time_earns_no_time_bonus() {
+    let bonus = bonuses(0).wave_bonus(&RunStats::default());
+
+    assert_eq!(bonus.time, 0);
+  }
+
+  #[test]
+  fn losing_a_life_loses_the_no_damage_bonus() {
+    assert_eq!(
+      bonuses(0).wave_bonus(&wave_stats(45000, 0, 0, 0)).no_damage,
+      1000
+    );
+    assert_eq!(
+      bonuses(0).wave_bonus(&wave_stats(45000, 0, 0, 1)).no_damage,
+      0
+    );
+  }
+
+  #[test]
+  fn the_accuracy_bonus_is_proportional_to_accuracy() {
+    assert_eq!(
+      bonuses(0)
+        .wave_bonus(&wave_stats(45000, 10, 10, 0))
+        .accuracy,
+      2000
+    );
+    assert_eq!(
+      bonuses(0).wave_bonus(&wave_stats(45000, 3, 1, 0)).accuracy,
+      667
+    );
+    assert_eq!(
+      bonuses(0).wave_bonus(&wave_stats(45000, 0, 0, 0)).accuracy,
+      0
+    );
+  }
+
+  #[test]
+  fn the_total_adds_up_every_bonus() {
+    let bonus = bonuses(0).wave_bonus(&wave_stats(20000, 4, 2, 0));
+
+    assert_eq!(
+      bonus,
+      WaveBonus {
+        time: 500,
+        no_damage: 1000,
+        accuracy: 1000,
+      }
+    );
+    assert_eq!(bonus.total(), 2500);
+  }
+}
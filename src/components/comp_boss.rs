@@ -0,0 +1,62 @@
+use thomas::{Component, IntCoords2d, Timer};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BossPhase {
+  Volley,
+  Spread,
+  Frenzy,
+}
+impl BossPhase {
+  pub fn number(&self) -> u8 {
+    match self {
+      BossPhase::Volley => 1,
+      BossPhase::Spread => 2,
+      BossPhase::Frenzy => 3,
+    }
+  }
+}
+
+#[derive(Component)]
+pub struct Boss {
+  pub hp: u64,
+  pub max_hp: u64,
+  pub attack_timer: Timer,
+  pub move_timer: Timer,
+  pub direction: IntCoords2d,
+}
+impl Boss {
+  /// A boss at full health, heading right.
+  pub fn new(max_hp: u64) -> Self {
+    Self {
+      hp: max_hp,
+      max_hp,
+      attack_timer: Timer::start_new(),
+      move_timer: Timer::start_new(),
+      direction: IntCoords2d::right(),
+    }
+  }
+
+  pub fn phase(&self) -> BossPhase {
+    if self.hp * 3 > self.max_hp * 2 {
+      BossPhase::Volley
+    } else if self.hp * 3 > self.max_hp {
+      BossPhase::Spread
+    } else {
+      BossPhase::Frenzy
+    }
+  }
+
+  pub fn take_damage(&mut self, damage: u64) {
+    self.hp = self.hp.saturating_sub(damage);
+  }
+
+  pub fn is_defeated(&self) -> bool {
+    self.hp == 0
+  }
+}
+
+/// Marks one of the cells of the boss's sprite.
+#[derive(Component)]
+pub struct BossPart {
+  pub is_weak_spot: bool,
+}
@@ -0,0 +1,7 @@
+use thomas::Component;
+
+/// One block of a bunker. Each hit chips it, until it's shot away.
+#[derive(Component)]
+pub struct Bunker {
+  pub hp: u8,
+}
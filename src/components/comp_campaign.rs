@@ -0,0 +1,51 @@
+use thomas::Component;
+
+use crate::{Campaign, CampaignProgress, Level};
+
+/// The campaign, how far through it the player has got and where the run in progress is up to.
+#[derive(Component)]
+pub struct CampaignRun {
+  pub campaign: Campaign,
+  pub progress: CampaignProgress,
+  /// Set to start the campaign on the next frame, at this wave.
+  pub start_wave: Option<u64>,
+  /// The wave whose interlude was shown last, so it's only shown once. One past the final wave for the epilogue.
+  pub interlude_wave: u64,
+  /// Set once the player has read the interlude and the next wave can start.
+  pub is_interlude_over: bool,
+  /// The levels the game was using before the campaign swapped its own in.
+  pub stashed_levels: Option<Vec<Level>>,
+}
+impl CampaignRun {
+  pub fn new(campaign: Campaign, progress: CampaignProgress) -> Self {
+    Self {
+      campaign,
+      progress,
+      start_wave: None,
+      interlude_wave: 0,
+      is_interlude_over: false,
+      stashed_levels: None,
+    }
+  }
+
+  /// Starts the campaign from the first wave, or from the last checkpoint reached.
+  pub fn start(&mut self, from_checkpoint: bool) {
+    self.start_wave = Some(if from_checkpoint {
+      self.progress.checkpoint.unwrap_or(1)
+    } else {
+      1
+    });
+  }
+
+  /// The story shown before a wave, or after the final one.
+  pub fn interlude(&self, wave: u64) -> &[String] {
+    match self.campaign.wave(wave) {
+      Some(campaign_wave) => &campaign_wave.interlude,
+      None => &self.campaign.epilogue,
+    }
+  }
+}
+
+/// Marks the text entities that make up an interlude on screen.
+#[derive(Component)]
+pub struct InterludeLine {}
@@ -3,6 +3,9 @@ use thomas::{Component, Timer, IntCoords2d};
 #[derive(Component)]
 pub struct Combat {
   pub shoot_timer: Timer,
+  /// How long after the timer starts the next shot can be fired. Shorter than usual for the first shot after a
+  /// saved game is resumed, so the cooldown picks up where it was left.
+  pub shoot_wait_millis: u128,
 }
 
 #[derive(Component)]
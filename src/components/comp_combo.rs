@@ -0,0 +1,98 @@
+use thomas::Component;
+
+use crate::{PausableTimer, COMBO_HITS_PER_MULTIPLIER_STEP, COMBO_MAX_MULTIPLIER};
+
+#[derive(Component)]
+pub struct Combo {
+  pub chain: u64,
+  pub window_timer: PausableTimer,
+}
+impl Combo {
+  pub fn multiplier(&self) -> u64 {
+    u64::min(
+      1 + self.chain / COMBO_HITS_PER_MULTIPLIER_STEP,
+      COMBO_MAX_MULTIPLIER,
+    )
+  }
+
+  pub fn register_hit(&mut self) {
+    self.chain += 1;
+    self.window_timer.restart();
+  }
+
+  pub fn break_chain(&mut self) {
+    self.chain = 0;
+    self.window_timer.stop();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::COMBO_WINDOW_MILLIS;
+
+  fn combo(chain: u64) -> Combo {
+    Combo {
+      chain,
+      window_timer: PausableTimer::new(),
+    }
+  }
+
+  #[test]
+  fn multiplier_steps_up_every_few_hits() {
+    assert_eq!(combo(0).multiplier(), 1);
+    assert_eq!(combo(COMBO_HITS_PER_MULTIPLIER_STEP - 1).multiplier(), 1);
+    assert_eq!(combo(COMBO_HITS_PER_MULTIPLIER_STEP).multiplier(), 2);
+    assert_eq!(combo(COMBO_HITS_PER_MULTIPLIER_STEP * 3).multiplier(), 4);
+  }
+
+  #[test]
+  fn multiplier_stops_at_the_max() {
+    let chain_at_max = COMBO_HITS_PER_MULTIPLIER_STEP * (COMBO_MAX_MULTIPLIER - 1);
+
+    assert_eq!(combo(chain_at_max).multiplier(), COMBO_MAX_MULTIPLIER);
+    assert_eq!(combo(chain_at_max * 10).multiplier(), COMBO_MAX_MULTIPLIER);
+  }
+
+  #[test]
+  fn a_hit_extends_the_chain_and_opens_the_window() {
+    let mut combo = combo(0);
+
+    combo.register_hit();
+    combo.register_hit();
+
+    assert_eq!(combo.chain, 2);
+    assert!(combo.window_timer.is_running());
+  }
+
+  #[test]
+  fn breaking_the_chain_resets_it_and_closes_the_window() {
+    let mut combo = combo(0);
+
+    for _ in 0..COMBO_HITS_PER_MULTIPLIER_STEP {
+      combo.register_hit();
+    }
+
+    combo.break_chain();
+
+    assert_eq!(combo.chain, 0);
+    assert_eq!(combo.multiplier(), 1);
+    assert!(!combo.window_timer.is_running());
+    assert_eq!(combo.window_timer.elapsed_millis(), 0);
+  }
+
+  #[test]
+  fn a_paused_window_stays_open() {
+    let mut combo = combo(0);
+
+    combo.register_hit();
+    combo.window_timer.pause();
+
+    assert_eq!(combo.chain, 1);
+    assert!(combo.window_timer.elapsed_millis() < COMBO_WINDOW_MILLIS);
+
+    combo.window_timer.resume();
+
+    assert!(combo.window_timer.is_running());
+  }
+}
@@ -0,0 +1,42 @@
+use thomas::Component;
+
+use crate::{DailyChallenge, DailyResults, HighScore, Level};
+
+/// Today's challenge, the results of the days played so far and where the run in progress is up to.
+#[derive(Component)]
+pub struct DailyRun {
+  pub challenge: DailyChallenge,
+  pub results: DailyResults,
+  /// Who the results of runs played this session go under.
+  pub player_name: String,
+  /// Set to start the challenge on the next frame.
+  pub is_starting: bool,
+  /// Whether the run in progress counts. Only the player's first go each day does, and any after it are practice.
+  pub is_scored: bool,
+  /// The levels the game was using before the challenge swapped its own in.
+  pub stashed_levels: Option<Vec<Level>>,
+}
+impl DailyRun {
+  pub fn new(challenge: DailyChallenge, results: DailyResults, player_name: String) -> Self {
+    Self {
+      challenge,
+      results,
+      player_name,
+      is_starting: false,
+      is_scored: false,
+      stashed_levels: None,
+    }
+  }
+
+  /// The player's result for today, once they've played today's challenge.
+  pub fn todays_result(&self) -> Option<&HighScore> {
+    self.results.get(&self.challenge.date(), &self.player_name)
+  }
+
+  /// Records the player's result for today.
+  pub fn record_todays_result(&mut self, result: HighScore) {
+    self
+      .results
+      .insert(self.challenge.date(), self.player_name.clone(), result);
+  }
+}
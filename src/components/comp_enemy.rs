@@ -1,11 +1,138 @@
-use thomas::{Component, Timer};
+use thomas::{Component, IntCoords2d, Timer};
+
+use crate::{
+  PausableTimer, BOSS_POINT_VALUE, BOSS_SPRITE_ART, CRAB_ALT_SPRITE_ART, CRAB_SPRITE_ART,
+  ENEMY_POINT_VALUE, OCTOPUS_ALT_SPRITE_ART, OCTOPUS_SPRITE_ART, SQUID_ALT_SPRITE_ART,
+  SQUID_SPRITE_ART,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum EnemyKind {
+  Squid,
+  Crab,
+  Octopus,
+  Boss,
+}
+impl EnemyKind {
+  pub const ALL: [EnemyKind; 4] = [
+    EnemyKind::Squid,
+    EnemyKind::Crab,
+    EnemyKind::Octopus,
+    EnemyKind::Boss,
+  ];
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      EnemyKind::Squid => "Squid",
+      EnemyKind::Crab => "Crab",
+      EnemyKind::Octopus => "Octopus",
+      EnemyKind::Boss => "Boss",
+    }
+  }
+
+  pub fn parse(name: &str) -> Option<Self> {
+    Self::ALL
+      .into_iter()
+      .find(|kind| kind.name().eq_ignore_ascii_case(name))
+  }
+
+  pub fn point_value(&self) -> u64 {
+    match self {
+      EnemyKind::Boss => BOSS_POINT_VALUE,
+      _ => ENEMY_POINT_VALUE,
+    }
+  }
+
+  pub fn sprite_art(&self) -> &'static [&'static str] {
+    match self {
+      EnemyKind::Squid => &SQUID_SPRITE_ART,
+      EnemyKind::Crab => &CRAB_SPRITE_ART,
+      EnemyKind::Octopus => &OCTOPUS_SPRITE_ART,
+      EnemyKind::Boss => &BOSS_SPRITE_ART,
+    }
+  }
+
+  /// The frame the sprite flips to on every other step of the formation.
+  pub fn alt_sprite_art(&self) -> &'static [&'static str] {
+    match self {
+      EnemyKind::Squid => &SQUID_ALT_SPRITE_ART,
+      EnemyKind::Crab => &CRAB_ALT_SPRITE_ART,
+      EnemyKind::Octopus => &OCTOPUS_ALT_SPRITE_ART,
+      _ => self.sprite_art(),
+    }
+  }
+}
 
 #[derive(Component)]
 pub struct Enemy {
   pub shoot_timer: Timer,
+  pub kind: EnemyKind,
+  /// Set while the invader's broken formation to dive at the player.
+  pub dive: Option<Dive>,
+}
+impl Enemy {
+  /// Its place in the formation, whether it's in it or away diving.
+  pub fn formation_coords(&self, coords: IntCoords2d) -> IntCoords2d {
+    self.dive.as_ref().map(|dive| dive.slot).unwrap_or(coords)
+  }
+
+  pub fn is_diving(&self) -> bool {
+    self.dive.is_some()
+  }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DiveStage {
+  /// Swooping down at the player.
+  Attack,
+  /// Heading back to its slot.
+  Return,
+}
+
+/// An invader's dive. The path curves out to one side on the way down and to the other on the way back, so each
+/// dive makes a loop.
+pub struct Dive {
+  /// Where the invader would be in the formation. It keeps moving with the formation while the invader's away.
+  pub slot: IntCoords2d,
+  /// Where the current stage started.
+  pub from: IntCoords2d,
+  /// How far across the attack carries it, towards where the player was when it broke off.
+  pub sweep: i64,
+  /// Which way the path bows out first, -1 for left or 1 for right.
+  pub swing: i64,
+  /// Whether the attack carries on out of the bottom of the screen, to come back in from the top, rather than
+  /// pulling up short of the player.
+  pub is_exiting: bool,
+  pub stage: DiveStage,
+  /// Paused along with the game, so the dive picks up where it left off.
+  pub stage_timer: PausableTimer,
 }
 
 #[derive(Component)]
 pub struct EnemyMovement {
   pub move_timer: Timer,
-}
\ No newline at end of file
+  /// The way the formation last stepped.
+  pub direction: IntCoords2d,
+}
+
+/// Survival's stream of rows, each pushing the formation further down.
+#[derive(Component)]
+pub struct FormationStream {
+  pub descend_timer: Timer,
+  pub rows_spawned: u64,
+  /// How many of the seconds survived have been scored so far.
+  pub seconds_scored: u64,
+}
+
+/// Sends invaders off to dive at the player every so often.
+#[derive(Component)]
+pub struct DiveLauncher {
+  pub launch_timer: Timer,
+}
+
+/// Set once the board is clear and the next wave is due. The systems that tell a campaign's story, finish a run
+/// and spawn the wave take it in turn, and whichever acts clears it.
+#[derive(Component)]
+pub struct WaveSpawner {
+  pub is_next_wave_due: bool,
+}
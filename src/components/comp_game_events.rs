@@ -0,0 +1,72 @@
+use thomas::{Component, IntCoords2d};
+
+use crate::{BulletType, EnemyKind};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DestroyedBy {
+  PlayerBullet,
+  /// Crashed into the player while diving.
+  Collision,
+}
+
+/// Something that happened in the game that other systems may want to react to.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameEvent {
+  EnemyDestroyed {
+    kind: EnemyKind,
+    coords: IntCoords2d,
+    by: DestroyedBy,
+  },
+  /// An enemy took a hit and survived it.
+  EnemyHit {
+    kind: EnemyKind,
+    coords: IntCoords2d,
+  },
+  /// A bullet chipped a bunker block, or shot it away.
+  BunkerHit { coords: IntCoords2d },
+  /// The player was hit and is about to lose a life.
+  PlayerHit { coords: IntCoords2d },
+  ShotFired {
+    coords: IntCoords2d,
+    bullet_type: BulletType,
+  },
+  /// A run's first wave is about to start.
+  RunStarted { seed: u64 },
+  WaveStarted {
+    level: u64,
+    is_boss_wave: bool,
+  },
+  /// The formation took a step sideways.
+  FormationStepped,
+  WaveCleared {
+    level: u64,
+    time_millis: u128,
+    accuracy: f64,
+  },
+  GameOver {
+    score: u64,
+    level: u64,
+    time_millis: u128,
+  },
+  /// The campaign's final boss was beaten.
+  CampaignCompleted { score: u64, time_millis: u128 },
+  /// The time attack's last wave was cleared.
+  TimeAttackCompleted { score: u64, time_millis: u128 },
+}
+
+/// The events of the current frame. The core systems emit them during `EVENT_UPDATE` and subscribers react to them
+/// during `EVENT_AFTER_UPDATE`, so no subscriber misses one and the emitters don't need to know who's listening.
+/// Cleared at the start of every frame.
+#[derive(Component, Default)]
+pub struct GameEvents {
+  pub events: Vec<GameEvent>,
+}
+impl GameEvents {
+  pub fn emit(&mut self, event: GameEvent) {
+    self.events.push(event);
+  }
+
+  pub fn iter(&self) -> impl Iterator<Item = &GameEvent> {
+    self.events.iter()
+  }
+}
@@ -0,0 +1,88 @@
+use thomas::{Component, Timer};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GamePhase {
+  /// Between runs, with the menus open.
+  Menu,
+  Playing,
+  WaveSummary,
+  GameOver,
+  Achievements,
+  /// Building a level in the editor.
+  Editor,
+  /// A campaign's story, shown between its waves.
+  Interlude,
+  /// The campaign's final boss is beaten, or the time attack's last wave is cleared.
+  RunComplete,
+}
+
+/// The kind of run being played.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum GameMode {
+  Classic,
+  /// Working through the campaign's waves in order. Losing every life starts over from the last checkpoint.
+  Campaign,
+  /// Rows of invaders keep coming down from the top, faster and faster, until they land or the last life is lost.
+  Survival,
+  /// Racing the clock through a fixed run of waves, against the splits of the fastest run so far.
+  TimeAttack,
+  /// The day's challenge, which is the same run for everyone who plays it that day.
+  Daily,
+  /// Trying out the level in the editor. Nothing from the run is saved, and it ends back in the editor.
+  PlayTest,
+}
+
+impl GameMode {
+  /// The modes a run can be saved in and continued later. A campaign picks up from its last checkpoint instead,
+  /// and a time attack or daily challenge is a single attempt.
+  pub const RESUMABLE: [GameMode; 2] = [GameMode::Classic, GameMode::Survival];
+
+  pub fn is_resumable(&self) -> bool {
+    Self::RESUMABLE.contains(self)
+  }
+
+  /// The name the mode is saved and looked up under.
+  pub fn id(&self) -> &'static str {
+    match self {
+      GameMode::Classic => "classic",
+      GameMode::Campaign => "campaign",
+      GameMode::Survival => "survival",
+      GameMode::TimeAttack => "time_attack",
+      GameMode::Daily => "daily",
+      GameMode::PlayTest => "play_test",
+    }
+  }
+}
+
+#[derive(Component)]
+pub struct GameState {
+  pub phase: GamePhase,
+  pub mode: GameMode,
+  pub phase_timer: Timer,
+}
+impl GameState {
+  pub fn is_playing(&self) -> bool {
+    self.phase == GamePhase::Playing
+  }
+
+  pub fn enter(&mut self, phase: GamePhase) {
+    self.phase = phase;
+    self.phase_timer.restart();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn only_endless_modes_are_resumable() {
+    assert!(GameMode::Classic.is_resumable());
+    assert!(GameMode::Survival.is_resumable());
+
+    assert!(!GameMode::Campaign.is_resumable());
+    assert!(!GameMode::TimeAttack.is_resumable());
+    assert!(!GameMode::Daily.is_resumable());
+    assert!(!GameMode::PlayTest.is_resumable());
+  }
+}
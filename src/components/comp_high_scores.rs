@@ -0,0 +1,134 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use thomas::Component;
+
+use crate::{GameMode, MAX_HIGH_SCORES};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct HighScore {
+  pub score: u64,
+  /// The wave the run ended on.
+  pub level: u64,
+  pub time_millis: u128,
+}
+impl HighScore {
+  /// Reads `<score>,<level>,<time>`. Entries saved before the time was kept have none, and count as 0.
+  pub fn parse(value: &str) -> Option<Self> {
+    let mut fields = value.split(',');
+
+    Some(Self {
+      score: fields.next()?.parse().ok()?,
+      level: fields.next()?.parse().ok()?,
+      time_millis: match fields.next() {
+        Some(time) => time.parse().ok()?,
+        None => 0,
+      },
+    })
+  }
+
+  pub fn to_saved(&self) -> String {
+    format!("{},{},{}", self.score, self.level, self.time_millis)
+  }
+}
+
+/// The best runs ever played, in a table for each mode that has one, best first.
+#[derive(Component)]
+pub struct HighScores {
+  pub tables: HashMap<GameMode, Vec<HighScore>>,
+}
+impl HighScores {
+  /// The modes with a table, in the order they're listed. Campaigns can carry on from a checkpoint, daily
+  /// challenges keep their own results by day and play-tests don't count, so none of them has one.
+  pub const MODES: [GameMode; 3] = [GameMode::Classic, GameMode::Survival, GameMode::TimeAttack];
+
+  /// Saved as `<mode>.<rank>=<score>,<level>,<time>`. Entries from before there was a table per mode are saved as
+  /// `<rank>=<score>,<level>` and go in the classic table. Entries that can't be read are dropped.
+  pub fn from_saved(saved: &HashMap<String, String>) -> Self {
+    let mut tables: HashMap<GameMode, Vec<HighScore>> = HashMap::new();
+
+    for (key, value) in saved {
+      let mode = match key.split_once('.') {
+        Some((mode_id, _)) => Self::MODES.into_iter().find(|mode| mode.id() == mode_id),
+        None => Some(GameMode::Classic),
+      };
+
+      if let (Some(mode), Some(entry)) = (mode, HighScore::parse(value)) {
+        tables.entry(mode).or_default().push(entry);
+      }
+    }
+
+    for (mode, entries) in tables.iter_mut() {
+      if Self::is_timed(*mode) {
+        entries.sort_by_key(|entry| entry.time_millis);
+      } else {
+        entries.sort_by_key(|entry| Reverse(entry.score));
+      }
+
+      entries.truncate(MAX_HIGH_SCORES);
+    }
+
+    Self { tables }
+  }
+
+  pub fn to_saved(&self) -> Vec<(String, String)> {
+    Self::MODES
+      .iter()
+      .flat_map(|mode| {
+        self
+          .entries(*mode)
+          .iter()
+          .enumerate()
+          .map(|(index, entry)| (format!("{}.{}", mode.id(), index + 1), entry.to_saved()))
+      })
+      .collect()
+  }
+
+  /// Whether the mode's table is ranked by the fastest time rather than the highest score.
+  pub fn is_timed(mode: GameMode) -> bool {
+    mode == GameMode::TimeAttack
+  }
+
+  pub fn entries(&self, mode: GameMode) -> &[HighScore] {
+    self
+      .tables
+      .get(&mode)
+      .map(Vec::as_slice)
+      .unwrap_or_default()
+  }
+
+  /// The best classic score.
+  pub fn best(&self) -> Option<u64> {
+    self
+      .entries(GameMode::Classic)
+      .first()
+      .map(|entry| entry.score)
+  }
+
+  /// Adds the run to the mode's table if it makes it. Returns whether it did.
+  pub fn record(&mut self, mode: GameMode, entry: HighScore) -> bool {
+    if !Self::MODES.contains(&mode) || (!Self::is_timed(mode) && entry.score == 0) {
+      return false;
+    }
+
+    let entries = self.tables.entry(mode).or_default();
+    let rank = entries
+      .iter()
+      .position(|existing| {
+        if Self::is_timed(mode) {
+          entry.time_millis < existing.time_millis
+        } else {
+          entry.score > existing.score
+        }
+      })
+      .unwrap_or(entries.len());
+
+    if rank >= MAX_HIGH_SCORES {
+      return false;
+    }
+
+    entries.insert(rank, entry);
+    entries.truncate(MAX_HIGH_SCORES);
+
+    true
+  }
+}
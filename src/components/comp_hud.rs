@@ -0,0 +1,142 @@
+use std::collections::{HashMap, VecDeque};
+
+use thomas::{Component, Timer};
+
+use crate::{Locale, ThemeRole};
+
+/// A piece of game state the HUD can show.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum HudBinding {
+  Lives,
+  Level,
+  Score,
+  HighScore,
+  Combo,
+  BossHealth,
+  /// The time attack clock.
+  Timer,
+  /// The time attack's latest split, against the best run's.
+  Split,
+}
+
+/// A HUD text that's kept up to date with the state it's bound to.
+#[derive(Component)]
+pub struct HudWidget {
+  pub binding: HudBinding,
+  /// The key of the message that's shown, with `{value}` standing in for the bound value. The widget is blank
+  /// while the binding has no value, like the combo between chains.
+  pub message_key: &'static str,
+}
+impl HudWidget {
+  pub fn render(&self, locale: &Locale, hud: &Hud) -> String {
+    hud
+      .get(self.binding)
+      .map(|value| locale.format(self.message_key, &[("value", &value)]))
+      .unwrap_or_default()
+  }
+}
+
+/// What the HUD shows. Systems write the value of each binding they own, and the widgets read them back.
+#[derive(Component, Default)]
+pub struct Hud {
+  values: HashMap<HudBinding, String>,
+  roles: HashMap<HudBinding, ThemeRole>,
+}
+impl Hud {
+  /// Sets the value of a binding, or clears it with `None`.
+  pub fn set(&mut self, binding: HudBinding, value: Option<String>) {
+    match value {
+      Some(value) => self.values.insert(binding, value),
+      None => self.values.remove(&binding),
+    };
+  }
+
+  pub fn get(&self, binding: HudBinding) -> Option<&str> {
+    self.values.get(&binding).map(String::as_str)
+  }
+
+  /// Styles the binding's widget in a role other than its layout's, like a split that's ahead of the best run.
+  pub fn set_role(&mut self, binding: HudBinding, role: ThemeRole) {
+    self.roles.insert(binding, role);
+  }
+
+  pub fn role(&self, binding: HudBinding) -> Option<ThemeRole> {
+    self.roles.get(&binding).copied()
+  }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BannerMessage {
+  pub value: String,
+  pub role: ThemeRole,
+  pub duration_millis: u128,
+}
+
+/// Big centred messages waiting to be shown, like "WAVE 3". They're shown one at a time, in the order they're
+/// queued.
+#[derive(Component, Default)]
+pub struct Banners {
+  pub queued: VecDeque<BannerMessage>,
+}
+impl Banners {
+  pub fn show(&mut self, value: String, role: ThemeRole, duration_millis: u128) {
+    self.queued.push_back(BannerMessage {
+      value,
+      role,
+      duration_millis,
+    });
+  }
+}
+
+/// The banner on screen.
+#[derive(Component)]
+pub struct Banner {
+  pub timer: Timer,
+  pub duration_millis: u128,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn score_widget() -> HudWidget {
+    HudWidget {
+      binding: HudBinding::Score,
+      message_key: "hud.score",
+    }
+  }
+
+  #[test]
+  fn a_widget_shows_its_bindings_value() {
+    let locale = Locale::find_built_in("en").unwrap();
+    let mut hud = Hud::default();
+
+    hud.set(HudBinding::Score, Some(String::from("120")));
+    hud.set(HudBinding::Level, Some(String::from("3")));
+
+    assert_eq!(score_widget().render(&locale, &hud), "Score: 120");
+  }
+
+  #[test]
+  fn a_widget_is_blank_while_its_binding_has_no_value() {
+    let locale = Locale::find_built_in("en").unwrap();
+    let mut hud = Hud::default();
+
+    assert_eq!(score_widget().render(&locale, &hud), "");
+
+    hud.set(HudBinding::Score, Some(String::from("120")));
+    hud.set(HudBinding::Score, None);
+
+    assert_eq!(score_widget().render(&locale, &hud), "");
+  }
+
+  #[test]
+  fn only_restyled_bindings_have_a_role() {
+    let mut hud = Hud::default();
+
+    hud.set_role(HudBinding::Split, ThemeRole::Positive);
+
+    assert_eq!(hud.role(HudBinding::Split), Some(ThemeRole::Positive));
+    assert_eq!(hud.role(HudBinding::Score), None);
+  }
+}
@@ -0,0 +1,185 @@
+use std::path::PathBuf;
+
+use thomas::Component;
+
+use crate::{
+  Level, LevelCell, MarchRule, MAX_LEVEL_BUNKER_ROWS, MAX_LEVEL_COLUMNS, MAX_LEVEL_INVADER_ROWS,
+};
+
+/// Why a cell couldn't be placed where the cursor is.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PlacementError {
+  /// A bunker was put in one of the invader rows.
+  InvadersOnly,
+  /// An invader was put in one of the bunker rows.
+  BunkersOnly,
+}
+
+/// What the editor last had to report.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum EditorStatus {
+  /// Saved to the path.
+  Saved(String),
+  /// Couldn't save, for the reason given.
+  SaveFailed(String),
+  CantPlace(PlacementError),
+  /// A play-test or save was asked for before there was anything to shoot at.
+  NoInvaders,
+}
+
+/// The level being built in the editor. The map is edited on a grid of the biggest size a level can be, with the
+/// invader rows on top and the bunker rows below, and trimmed down when it's turned back into a level.
+#[derive(Component)]
+pub struct LevelEditor {
+  /// Where the level is saved.
+  pub path: PathBuf,
+  /// The name, speed, fire rate and march rule. Its map isn't used while editing.
+  pub level: Level,
+  pub cells: Vec<Vec<LevelCell>>,
+  /// The column and row of the cell being edited.
+  pub cursor: (usize, usize),
+  /// Set to open the editor on the next frame.
+  pub is_opening: bool,
+  /// The levels the game was using before a play-test swapped the edited one in.
+  pub stashed_levels: Option<Vec<Level>>,
+  pub status: Option<EditorStatus>,
+}
+impl LevelEditor {
+  pub const ROWS: usize = MAX_LEVEL_INVADER_ROWS + MAX_LEVEL_BUNKER_ROWS;
+
+  /// Opens the level, or an empty one named after the file when there isn't one yet.
+  pub fn new(path: PathBuf, level: Option<Level>) -> Self {
+    let name = path
+      .file_stem()
+      .map(|file_stem| file_stem.to_string_lossy().into_owned())
+      .unwrap_or_default();
+
+    let mut editor = Self {
+      path,
+      level: Level {
+        name,
+        move_wait_millis: None,
+        shoot_chance: None,
+        march_rule: MarchRule::Random,
+        invader_rows: vec![],
+        bunker_rows: vec![],
+      },
+      cells: vec![vec![LevelCell::Empty; MAX_LEVEL_COLUMNS]; Self::ROWS],
+      cursor: (MAX_LEVEL_COLUMNS / 2, 0),
+      is_opening: false,
+      stashed_levels: None,
+      status: None,
+    };
+
+    if let Some(level) = level {
+      editor.lay_out(&level);
+      editor.level = level;
+    }
+
+    editor
+  }
+
+  /// Puts the level's map on the grid, centred the way the game centres the formation. Bunker rows sit at the
+  /// bottom, just as they do above the player.
+  pub fn lay_out(&mut self, level: &Level) {
+    let left = (MAX_LEVEL_COLUMNS - level.columns()) / 2;
+    let bunker_top = Self::ROWS - level.bunker_rows.len();
+
+    self.cells = vec![vec![LevelCell::Empty; MAX_LEVEL_COLUMNS]; Self::ROWS];
+
+    let rows = level.invader_rows.iter().enumerate().chain(
+      level
+        .bunker_rows
+        .iter()
+        .enumerate()
+        .map(|(row, cells)| (bunker_top + row, cells)),
+    );
+
+    for (row, cells) in rows {
+      for (col, cell) in cells.iter().enumerate() {
+        self.cells[row][left + col] = *cell;
+      }
+    }
+  }
+
+  /// The level as it stands, with the empty columns either side of the map trimmed away.
+  pub fn to_level(&self) -> Level {
+    let is_empty = |row: &[LevelCell]| row.iter().all(|cell| *cell == LevelCell::Empty);
+    let is_column_empty = |col: usize| self.cells.iter().all(|row| row[col] == LevelCell::Empty);
+
+    let first_col = (0..MAX_LEVEL_COLUMNS)
+      .find(|col| !is_column_empty(*col))
+      .unwrap_or(0);
+    let last_col = (0..MAX_LEVEL_COLUMNS)
+      .rfind(|col| !is_column_empty(*col))
+      .unwrap_or(0);
+
+    let trim = |rows: &[Vec<LevelCell>]| -> Vec<Vec<LevelCell>> {
+      rows
+        .iter()
+        .map(|row| row[first_col..=last_col].to_vec())
+        .collect()
+    };
+
+    let mut invader_rows = trim(&self.cells[..MAX_LEVEL_INVADER_ROWS]);
+
+    while invader_rows.last().is_some_and(|row| is_empty(row)) {
+      invader_rows.pop();
+    }
+
+    let bunker_rows = trim(&self.cells[MAX_LEVEL_INVADER_ROWS..])
+      .into_iter()
+      .skip_while(|row| is_empty(row))
+      .collect();
+
+    Level {
+      invader_rows,
+      bunker_rows,
+      ..self.level.clone()
+    }
+  }
+
+  pub fn has_invaders(&self) -> bool {
+    self.cells[..MAX_LEVEL_INVADER_ROWS]
+      .iter()
+      .flatten()
+      .any(|cell| matches!(cell, LevelCell::Enemy(_)))
+  }
+
+  pub fn move_cursor(&mut self, cols: i64, rows: i64) {
+    self.cursor = (
+      (self.cursor.0 as i64 + cols).clamp(0, MAX_LEVEL_COLUMNS as i64 - 1) as usize,
+      (self.cursor.1 as i64 + rows).clamp(0, Self::ROWS as i64 - 1) as usize,
+    );
+  }
+
+  pub fn is_bunker_row(row: usize) -> bool {
+    row >= MAX_LEVEL_INVADER_ROWS
+  }
+
+  /// Puts the cell under the cursor. Invaders can only go in the invader rows and bunkers in the bunker rows.
+  pub fn place(&mut self, cell: LevelCell) -> Result<(), PlacementError> {
+    let (col, row) = self.cursor;
+
+    match cell {
+      LevelCell::Enemy(_) if Self::is_bunker_row(row) => Err(PlacementError::BunkersOnly),
+      LevelCell::Bunker if !Self::is_bunker_row(row) => Err(PlacementError::InvadersOnly),
+      _ => {
+        self.cells[row][col] = cell;
+
+        Ok(())
+      }
+    }
+  }
+}
+
+/// Marks the entity that draws one cell of the editor's grid.
+#[derive(Component)]
+pub struct EditorCell {
+  pub col: usize,
+  pub row: usize,
+}
+
+/// Marks the text entities that make up the editor's header, status and help.
+#[derive(Component)]
+pub struct EditorLine {}
@@ -0,0 +1,15 @@
+use thomas::Component;
+
+use crate::Level;
+
+/// The levels a run's formations are laid out from, played in order and starting over after the last.
+#[derive(Component)]
+pub struct Levels {
+  pub levels: Vec<Level>,
+}
+impl Levels {
+  /// The level for a wave. Waves count from 1, and boss waves still use up a level.
+  pub fn for_wave(&self, wave: u64) -> &Level {
+    &self.levels[(wave.saturating_sub(1) as usize) % self.levels.len()]
+  }
+}
@@ -0,0 +1,26 @@
+use thomas::Component;
+
+use crate::Locale;
+
+/// The languages the player can switch between, and the one in use.
+#[derive(Component)]
+pub struct Localization {
+  pub locales: Vec<Locale>,
+  pub current: usize,
+}
+impl Localization {
+  /// Starts in the built-in locale with the code, or English if there isn't one.
+  pub fn new(code: &str) -> Self {
+    let locales = Locale::built_ins();
+    let current = locales
+      .iter()
+      .position(|locale| locale.code == code)
+      .unwrap_or(0);
+
+    Self { locales, current }
+  }
+
+  pub fn current(&self) -> &Locale {
+    &self.locales[self.current]
+  }
+}
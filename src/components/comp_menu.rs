@@ -0,0 +1,190 @@
+use thomas::Component;
+
+use crate::{GameMode, Locale};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MenuPage {
+  Main,
+  Modes,
+  /// Today's challenge, ready to play.
+  Daily,
+  Options,
+  /// Picks which mode's table to look at.
+  HighScores,
+  /// The table of best runs for a mode.
+  Leaderboard(GameMode),
+  /// The latest day's daily challenge results, a player to a line.
+  DailyResults,
+  /// Every achievement, with how close the player is to unlocking it.
+  Achievements,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MenuAction {
+  Play,
+  /// Picks up the run that was saved when the game was last quit.
+  Continue,
+  /// Opens the level editor.
+  Edit,
+  /// Starts a survival run.
+  Survival,
+  /// Starts a time attack run.
+  TimeAttack,
+  /// Starts today's challenge.
+  Daily,
+  /// Starts the campaign, from the first wave or the last checkpoint reached.
+  Campaign { from_checkpoint: bool },
+  Open(MenuPage),
+  Back,
+  Quit,
+}
+
+/// A setting a menu item changes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MenuSetting {
+  Difficulty,
+  Controls,
+  Theme,
+  Language,
+  ShakeIntensity,
+  FlashIntensity,
+  Sprites,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum MenuItemKind {
+  /// Does something when chosen.
+  Action(MenuAction),
+  /// A number from 0 to `max`, nudged with left and right.
+  Slider {
+    setting: MenuSetting,
+    value: u8,
+    max: u8,
+  },
+  /// Flipped by choosing it, or with left and right.
+  Toggle { setting: MenuSetting, value: bool },
+  /// One of a list of named values, stepped through with left and right.
+  Choice {
+    setting: MenuSetting,
+    value: String,
+  },
+  /// Shown but can't be selected, like an entry in the high score table.
+  Label,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MenuItem {
+  pub label: String,
+  pub kind: MenuItemKind,
+}
+impl MenuItem {
+  pub fn action(label: &str, action: MenuAction) -> Self {
+    Self {
+      label: String::from(label),
+      kind: MenuItemKind::Action(action),
+    }
+  }
+
+  pub fn label(label: String) -> Self {
+    Self {
+      label,
+      kind: MenuItemKind::Label,
+    }
+  }
+
+  pub fn is_selectable(&self) -> bool {
+    self.kind != MenuItemKind::Label
+  }
+
+  /// The item as it's shown, with its current value if it has one.
+  pub fn display(&self, locale: &Locale) -> String {
+    match &self.kind {
+      MenuItemKind::Action(_) | MenuItemKind::Label => self.label.clone(),
+      MenuItemKind::Slider { value, max, .. } => format!(
+        "{}  [{}{}]",
+        self.label,
+        "#".repeat(*value as usize),
+        "-".repeat((max - value) as usize)
+      ),
+      MenuItemKind::Toggle { value, .. } => {
+        let value = locale.text(if *value { "menu.on" } else { "menu.off" });
+
+        format!("{}  {}", self.label, value)
+      }
+      MenuItemKind::Choice { value, .. } => format!("{}  < {} >", self.label, value),
+    }
+  }
+}
+
+/// What a menu page shows. Built fresh from the game's state whenever it's needed, so it never goes stale.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MenuScreen {
+  pub title: String,
+  pub items: Vec<MenuItem>,
+}
+impl MenuScreen {
+  /// The nearest selectable item from `from` in the direction of `step`, wrapping around. Stays put if there isn't
+  /// one.
+  pub fn step_selection(&self, from: usize, step: i64) -> usize {
+    let item_count = self.items.len() as i64;
+
+    (1..=item_count)
+      .map(|distance| (from as i64 + step * distance).rem_euclid(item_count) as usize)
+      .find(|index| self.items[*index].is_selectable())
+      .unwrap_or(from)
+  }
+
+  pub fn first_selectable(&self) -> usize {
+    self
+      .items
+      .iter()
+      .position(|item| item.is_selectable())
+      .unwrap_or(0)
+  }
+}
+
+/// Where the player is in the menus.
+#[derive(Component)]
+pub struct Menu {
+  pub page: MenuPage,
+  pub selected: usize,
+  /// The pages to go back through, and what was selected on each.
+  pub history: Vec<(MenuPage, usize)>,
+}
+impl Menu {
+  pub fn new() -> Self {
+    Self {
+      page: MenuPage::Main,
+      selected: 0,
+      history: vec![],
+    }
+  }
+
+  pub fn open(&mut self, page: MenuPage, selected: usize) {
+    self.history.push((self.page, self.selected));
+    self.page = page;
+    self.selected = selected;
+  }
+
+  /// Returns to the previous page. Does nothing on the main menu.
+  pub fn back(&mut self) {
+    if let Some((page, selected)) = self.history.pop() {
+      self.page = page;
+      self.selected = selected;
+    }
+  }
+
+  /// Goes back to the top of the main menu, ready for the next time it's shown.
+  pub fn reset(&mut self) {
+    *self = Self::new();
+  }
+}
+impl Default for Menu {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Marks the text entities that make up the menu on screen.
+#[derive(Component)]
+pub struct MenuLine {}
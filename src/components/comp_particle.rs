@@ -0,0 +1,63 @@
+use thomas::{Component, Coords2d, IntCoords2d, Rgb, Timer};
+
+/// What a burst of particles looks like. Each particle moves outward at up to `speed` cells per second and steps
+/// through `glyphs` over its lifetime while its colour fades from `start_color` to `end_color`.
+#[derive(Clone, Copy, Debug)]
+pub struct ParticleBurst {
+  pub count: usize,
+  pub glyphs: &'static [char],
+  pub start_color: Rgb,
+  pub end_color: Rgb,
+  pub speed: f64,
+  pub lifetime_millis: u128,
+}
+impl ParticleBurst {
+  pub const ENEMY_DEATH: ParticleBurst = ParticleBurst {
+    count: 8,
+    glyphs: &['#', '*', '+', '.'],
+    start_color: Rgb(255, 220, 64),
+    end_color: Rgb(96, 24, 24),
+    speed: 12.0,
+    lifetime_millis: 500,
+  };
+  pub const PLAYER_HIT: ParticleBurst = ParticleBurst {
+    count: 12,
+    glyphs: &['%', '*', ':', '.'],
+    start_color: Rgb(255, 255, 255),
+    end_color: Rgb(0, 96, 0),
+    speed: 10.0,
+    lifetime_millis: 700,
+  };
+  pub const BUNKER_CHIP: ParticleBurst = ParticleBurst {
+    count: 3,
+    glyphs: &[',', '.'],
+    start_color: Rgb(64, 200, 64),
+    end_color: Rgb(16, 48, 16),
+    speed: 5.0,
+    lifetime_millis: 300,
+  };
+  pub const BULLET_IMPACT: ParticleBurst = ParticleBurst {
+    count: 4,
+    glyphs: &['+', '.'],
+    start_color: Rgb(255, 255, 255),
+    end_color: Rgb(64, 64, 64),
+    speed: 8.0,
+    lifetime_millis: 200,
+  };
+}
+
+/// A burst waiting to be turned into particles. Bursts are spawned this way so the particle cap can be enforced in
+/// one place.
+#[derive(Component)]
+pub struct PendingParticleBurst {
+  pub coords: IntCoords2d,
+  pub burst: ParticleBurst,
+}
+
+#[derive(Component)]
+pub struct Particle {
+  pub origin: IntCoords2d,
+  /// In cells per second.
+  pub velocity: Coords2d,
+  pub age_timer: Timer,
+}
@@ -0,0 +1,48 @@
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use thomas::Component;
+
+/// The source of randomness for anything that affects gameplay. Purely cosmetic randomness (like the stars) doesn't
+/// need to go through here.
+#[derive(Component)]
+pub struct GameRng {
+  pub seed: u64,
+  /// The seed every run gets, when it was fixed at launch.
+  pub fixed_seed: Option<u64>,
+  pub rng: ChaCha8Rng,
+}
+impl GameRng {
+  pub fn new(fixed_seed: Option<u64>) -> Self {
+    let seed = fixed_seed.unwrap_or_else(|| thread_rng().gen());
+
+    Self {
+      seed,
+      fixed_seed,
+      rng: ChaCha8Rng::seed_from_u64(seed),
+    }
+  }
+
+  /// Reseeds for a new run. A fixed seed replays the same run every time.
+  pub fn start_new_run(&mut self) {
+    self.start_run_from(self.fixed_seed.unwrap_or_else(|| thread_rng().gen()));
+  }
+
+  /// Reseeds for a run that brings its own seed, like the daily challenge. The run after it goes back to the usual
+  /// seed.
+  pub fn start_run_from(&mut self, seed: u64) {
+    self.seed = seed;
+    self.rng = ChaCha8Rng::seed_from_u64(seed);
+  }
+
+  /// How many random numbers have been drawn from the seed so far.
+  pub fn position(&self) -> u128 {
+    self.rng.get_word_pos()
+  }
+
+  /// Picks a saved run's randomness back up from where it was left.
+  pub fn resume_run(&mut self, seed: u64, position: u128) {
+    self.seed = seed;
+    self.rng = ChaCha8Rng::seed_from_u64(seed);
+    self.rng.set_word_pos(position);
+  }
+}
@@ -0,0 +1,13 @@
+use thomas::Component;
+
+use crate::{SavedGame, SavedGameError};
+
+/// The run that was in progress when the game was last quit, if there was one.
+#[derive(Component)]
+pub struct ResumableGame {
+  pub saved: Option<SavedGame>,
+  /// Why the saved game couldn't be read, if it couldn't.
+  pub error: Option<SavedGameError>,
+  /// Set when the player chooses to continue, so the run is restored at the end of the frame.
+  pub is_resuming: bool,
+}
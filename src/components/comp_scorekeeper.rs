@@ -5,4 +5,13 @@ pub struct Scorekeeper {
   pub score: u64,
   pub high_score: u64,
   pub level: u64,
-}
\ No newline at end of file
+}
+impl Scorekeeper {
+  pub fn add_points(&mut self, points: u64) {
+    self.score += points;
+
+    if self.score > self.high_score {
+      self.high_score = self.score;
+    }
+  }
+}
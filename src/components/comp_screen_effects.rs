@@ -0,0 +1,95 @@
+use thomas::{Component, Rgb, Timer};
+
+use crate::{
+  DEFAULT_FLASH_INTENSITY, DEFAULT_SHAKE_INTENSITY, FLASH_DURATION_MILLIS,
+  MAX_SCREEN_EFFECT_INTENSITY, SHAKE_DURATION_MILLIS,
+};
+
+/// How strong screen shake and flashes are, from 0 (off) to `MAX_SCREEN_EFFECT_INTENSITY`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScreenEffectSettings {
+  pub shake_intensity: u8,
+  pub flash_intensity: u8,
+}
+impl ScreenEffectSettings {
+  pub fn off() -> Self {
+    Self {
+      shake_intensity: 0,
+      flash_intensity: 0,
+    }
+  }
+}
+impl Default for ScreenEffectSettings {
+  fn default() -> Self {
+    Self {
+      shake_intensity: DEFAULT_SHAKE_INTENSITY,
+      flash_intensity: DEFAULT_FLASH_INTENSITY,
+    }
+  }
+}
+
+#[derive(Component)]
+pub struct ScreenEffects {
+  pub settings: ScreenEffectSettings,
+  /// How far the camera can be thrown from its resting position by the shake in progress.
+  pub shake_amplitude: i64,
+  pub shake_timer: Timer,
+  /// A flash that's been asked for but not drawn yet.
+  pub pending_flash_color: Option<Rgb>,
+  pub flash_timer: Timer,
+}
+impl ScreenEffects {
+  pub fn new(settings: ScreenEffectSettings) -> Self {
+    Self {
+      settings,
+      shake_amplitude: 0,
+      shake_timer: Timer::new(),
+      pending_flash_color: None,
+      flash_timer: Timer::new(),
+    }
+  }
+
+  /// Shakes the screen. `strength` is how big the event was: 1 for a small knock, higher for bigger ones.
+  pub fn shake(&mut self, strength: u8) {
+    if self.settings.shake_intensity == 0 {
+      return;
+    }
+
+    let amplitude = (strength * self.settings.shake_intensity).div_ceil(2) as i64;
+
+    if !self.is_shaking() || amplitude > self.shake_amplitude {
+      self.shake_amplitude = amplitude;
+    }
+
+    self.shake_timer.restart();
+  }
+
+  /// Flashes the whole screen. Lower intensities blend the colour towards black.
+  pub fn flash(&mut self, color: Rgb) {
+    if self.settings.flash_intensity == 0 {
+      return;
+    }
+
+    let strength = self.settings.flash_intensity as u16;
+    let max_strength = MAX_SCREEN_EFFECT_INTENSITY as u16;
+
+    self.pending_flash_color = Some(Rgb(
+      (color.r() as u16 * strength / max_strength) as u8,
+      (color.g() as u16 * strength / max_strength) as u8,
+      (color.b() as u16 * strength / max_strength) as u8,
+    ));
+    self.flash_timer.restart();
+  }
+
+  pub fn is_shaking(&self) -> bool {
+    self.shake_timer.is_running() && self.shake_timer.elapsed_millis() < SHAKE_DURATION_MILLIS
+  }
+
+  pub fn is_flashing(&self) -> bool {
+    self.flash_timer.is_running() && self.flash_timer.elapsed_millis() < FLASH_DURATION_MILLIS
+  }
+}
+
+/// One cell of the full-screen flash.
+#[derive(Component)]
+pub struct ScreenFlash {}
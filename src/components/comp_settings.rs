@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+
+use thomas::{Component, IntCoords2d, Keycode};
+
+use crate::{MenuSetting, ScreenEffectSettings, Theme, MAX_SCREEN_EFFECT_INTENSITY};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Difficulty {
+  Easy,
+  Normal,
+  Hard,
+}
+impl Difficulty {
+  pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      Difficulty::Easy => "Easy",
+      Difficulty::Normal => "Normal",
+      Difficulty::Hard => "Hard",
+    }
+  }
+
+  pub fn parse(name: &str) -> Option<Self> {
+    Self::ALL
+      .into_iter()
+      .find(|difficulty| difficulty.name().eq_ignore_ascii_case(name))
+  }
+
+  /// The percent chance an invader shoots each time it's ready to.
+  pub fn enemy_shoot_chance(&self) -> u8 {
+    match self {
+      Difficulty::Easy => 6,
+      Difficulty::Normal => 10,
+      Difficulty::Hard => 15,
+    }
+  }
+
+  /// How long the formation waits between steps.
+  pub fn enemy_move_wait_millis(&self) -> u128 {
+    match self {
+      Difficulty::Easy => 1000,
+      Difficulty::Normal => 800,
+      Difficulty::Hard => 600,
+    }
+  }
+
+  /// How long between invaders breaking formation to dive at the player.
+  pub fn enemy_dive_wait_millis(&self) -> u128 {
+    match self {
+      Difficulty::Easy => 9000,
+      Difficulty::Normal => 6000,
+      Difficulty::Hard => 4000,
+    }
+  }
+}
+
+/// Which keys move the player.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ControlScheme {
+  Letters,
+  Arrows,
+  Both,
+}
+impl ControlScheme {
+  pub const ALL: [ControlScheme; 3] = [
+    ControlScheme::Letters,
+    ControlScheme::Arrows,
+    ControlScheme::Both,
+  ];
+
+  pub fn id(&self) -> &'static str {
+    match self {
+      ControlScheme::Letters => "letters",
+      ControlScheme::Arrows => "arrows",
+      ControlScheme::Both => "both",
+    }
+  }
+
+  pub fn parse(id: &str) -> Option<Self> {
+    Self::ALL.into_iter().find(|scheme| scheme.id() == id)
+  }
+
+  pub fn movement_keys(&self) -> Vec<(Keycode, IntCoords2d)> {
+    let letters = [
+      (Keycode::A, IntCoords2d::left()),
+      (Keycode::D, IntCoords2d::right()),
+    ];
+    let arrows = [
+      (Keycode::Left, IntCoords2d::left()),
+      (Keycode::Right, IntCoords2d::right()),
+    ];
+
+    match self {
+      ControlScheme::Letters => letters.to_vec(),
+      ControlScheme::Arrows => arrows.to_vec(),
+      ControlScheme::Both => letters.into_iter().chain(arrows).collect(),
+    }
+  }
+}
+
+/// The options the player can change from the options screen, as they apply to this session. Command line options
+/// take precedence over the saved ones here, so these are never saved themselves.
+#[derive(Component, Clone, PartialEq, Eq, Debug)]
+pub struct Settings {
+  pub difficulty: Difficulty,
+  pub controls: ControlScheme,
+  /// The slug of the theme in use. Classic when absent or not a built-in.
+  pub theme: Option<String>,
+  /// The code of the language in use. Taken from the system locale when absent.
+  pub language: Option<String>,
+  pub screen_effects: ScreenEffectSettings,
+  /// Only takes effect the next time the game is launched, since the player and invaders are already built.
+  pub use_sprites: bool,
+}
+impl Default for Settings {
+  fn default() -> Self {
+    Self {
+      difficulty: Difficulty::Normal,
+      controls: ControlScheme::Both,
+      theme: None,
+      language: None,
+      screen_effects: ScreenEffectSettings::default(),
+      use_sprites: false,
+    }
+  }
+}
+impl Settings {
+  /// Anything missing or unreadable keeps its default.
+  pub fn from_saved(saved: &HashMap<String, String>) -> Self {
+    let defaults = Self::default();
+    let parse_intensity = |key: &str, default: u8| {
+      saved
+        .get(key)
+        .and_then(|value| value.parse().ok())
+        .filter(|intensity| *intensity <= MAX_SCREEN_EFFECT_INTENSITY)
+        .unwrap_or(default)
+    };
+
+    Self {
+      difficulty: saved
+        .get("difficulty")
+        .and_then(|value| Difficulty::parse(value))
+        .unwrap_or(defaults.difficulty),
+      controls: saved
+        .get("controls")
+        .and_then(|value| ControlScheme::parse(value))
+        .unwrap_or(defaults.controls),
+      theme: saved.get("theme").cloned(),
+      language: saved.get("language").cloned(),
+      screen_effects: ScreenEffectSettings {
+        shake_intensity: parse_intensity("shake", defaults.screen_effects.shake_intensity),
+        flash_intensity: parse_intensity("flash", defaults.screen_effects.flash_intensity),
+      },
+      use_sprites: saved
+        .get("sprites")
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(defaults.use_sprites),
+    }
+  }
+
+  pub fn to_saved(&self) -> Vec<(String, String)> {
+    let mut saved = vec![
+      (String::from("difficulty"), self.difficulty.name().to_lowercase()),
+      (String::from("controls"), String::from(self.controls.id())),
+      (String::from("shake"), self.screen_effects.shake_intensity.to_string()),
+      (String::from("flash"), self.screen_effects.flash_intensity.to_string()),
+      (String::from("sprites"), self.use_sprites.to_string()),
+    ];
+
+    if let Some(theme) = &self.theme {
+      saved.push((String::from("theme"), theme.clone()));
+    }
+
+    if let Some(language) = &self.language {
+      saved.push((String::from("language"), language.clone()));
+    }
+
+    saved
+  }
+}
+
+/// The settings as they were loaded at launch, with only the player's own changes made since. These are what get
+/// saved, so command line options given for one launch don't carry over to the next.
+#[derive(Component)]
+pub struct SavedSettings {
+  pub settings: Settings,
+}
+impl SavedSettings {
+  /// Takes the value of one setting the player changed from the settings in use.
+  pub fn record(&mut self, setting: MenuSetting, settings: &Settings) {
+    match setting {
+      MenuSetting::Difficulty => self.settings.difficulty = settings.difficulty,
+      MenuSetting::Controls => self.settings.controls = settings.controls,
+      MenuSetting::Theme => {
+        // A theme loaded from a file can't be found again by its name, so the saved one is left as it was.
+        if settings
+          .theme
+          .as_deref()
+          .and_then(Theme::find_built_in)
+          .is_some()
+        {
+          self.settings.theme = settings.theme.clone();
+        }
+      }
+      MenuSetting::Language => self.settings.language = settings.language.clone(),
+      MenuSetting::ShakeIntensity => {
+        self.settings.screen_effects.shake_intensity = settings.screen_effects.shake_intensity;
+      }
+      MenuSetting::FlashIntensity => {
+        self.settings.screen_effects.flash_intensity = settings.screen_effects.flash_intensity;
+      }
+      MenuSetting::Sprites => self.settings.use_sprites = settings.use_sprites,
+    }
+  }
+}
@@ -0,0 +1,66 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use thomas::{Component, IntCoords2d};
+
+static NEXT_SPRITE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Draws an entity as a grid of glyphs instead of a single cell. Each visible glyph is a separate `SpriteCell`
+/// entity that follows the sprite's transform, which sits at the middle of the art's top row.
+#[derive(Component)]
+pub struct Sprite {
+  pub id: u64,
+  pub art: &'static [&'static str],
+}
+impl Sprite {
+  pub fn new(art: &'static [&'static str]) -> Self {
+    Self {
+      id: NEXT_SPRITE_ID.fetch_add(1, Ordering::Relaxed),
+      art,
+    }
+  }
+
+  pub fn width(&self) -> i64 {
+    self
+      .art
+      .iter()
+      .map(|line| line.chars().count())
+      .max()
+      .unwrap_or(0) as i64
+  }
+
+  pub fn height(&self) -> i64 {
+    self.art.len() as i64
+  }
+
+  /// The offset from the sprite's transform of every glyph that isn't a space.
+  pub fn cells(&self) -> Vec<(IntCoords2d, char)> {
+    let left = -(self.width() / 2);
+
+    self
+      .art
+      .iter()
+      .enumerate()
+      .flat_map(|(row, line)| {
+        line
+          .chars()
+          .enumerate()
+          .filter(|(_, glyph)| *glyph != ' ')
+          .map(move |(col, glyph)| (IntCoords2d::new(left + col as i64, row as i64), glyph))
+      })
+      .collect()
+  }
+
+  /// The glyph at `offset` in another frame of the same size as this sprite's art.
+  pub fn glyph_in_frame(&self, frame: &[&str], offset: IntCoords2d) -> char {
+    frame
+      .get(offset.y() as usize)
+      .and_then(|line| line.chars().nth((offset.x() + self.width() / 2) as usize))
+      .unwrap_or(' ')
+  }
+}
+
+#[derive(Component)]
+pub struct SpriteCell {
+  pub sprite_id: u64,
+  pub offset: IntCoords2d,
+}
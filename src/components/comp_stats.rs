@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use thomas::Component;
+
+use crate::{EnemyKind, PausableTimer};
+
+#[derive(Clone, Debug, Default)]
+pub struct RunStats {
+  pub shots_fired: u64,
+  pub hits: u64,
+  pub enemies_killed: HashMap<EnemyKind, u64>,
+  pub lives_lost: u64,
+  pub wave_times_millis: Vec<u128>,
+  pub bullets_dodged: u64,
+}
+impl RunStats {
+  /// The fraction of shots fired that hit an enemy, from 0 to 1.
+  pub fn accuracy(&self) -> f64 {
+    if self.shots_fired == 0 {
+      0.0
+    } else {
+      self.hits as f64 / self.shots_fired as f64
+    }
+  }
+
+  pub fn kills_of(&self, kind: EnemyKind) -> u64 {
+    *self.enemies_killed.get(&kind).unwrap_or(&0)
+  }
+
+  pub fn total_kills(&self) -> u64 {
+    self.enemies_killed.values().sum()
+  }
+}
+
+/// Tracks stats for the whole run and for the wave currently in progress.
+#[derive(Component)]
+pub struct Stats {
+  pub run: RunStats,
+  pub wave: RunStats,
+  pub wave_timer: PausableTimer,
+}
+impl Stats {
+  pub fn record_shot(&mut self) {
+    self.run.shots_fired += 1;
+    self.wave.shots_fired += 1;
+  }
+
+  pub fn record_hit(&mut self) {
+    self.run.hits += 1;
+    self.wave.hits += 1;
+  }
+
+  pub fn record_kill(&mut self, kind: EnemyKind) {
+    for stats in [&mut self.run, &mut self.wave] {
+      *stats.enemies_killed.entry(kind).or_insert(0) += 1;
+    }
+  }
+
+  pub fn record_life_lost(&mut self) {
+    self.run.lives_lost += 1;
+    self.wave.lives_lost += 1;
+  }
+
+  pub fn record_dodge(&mut self) {
+    self.run.bullets_dodged += 1;
+    self.wave.bullets_dodged += 1;
+  }
+
+  pub fn start_wave(&mut self) {
+    self.wave = RunStats::default();
+    self.wave_timer.restart();
+  }
+
+  /// Records the time taken for the wave in progress and returns that wave's stats.
+  pub fn finish_wave(&mut self) -> RunStats {
+    let wave_time = self.wave_timer.elapsed_millis();
+
+    self.run.wave_times_millis.push(wave_time);
+    self.wave.wave_times_millis.push(wave_time);
+
+    self.wave_timer.stop();
+
+    self.wave.clone()
+  }
+
+  /// Total time played this run, including the wave in progress.
+  pub fn run_time_millis(&self) -> u128 {
+    self.run.wave_times_millis.iter().sum::<u128>() + self.wave_timer.elapsed_millis()
+  }
+
+  pub fn reset(&mut self) {
+    self.run = RunStats::default();
+    self.wave = RunStats::default();
+    self.wave_timer.stop();
+  }
+}
+
+/// Marks the text entities that make up an on-screen stats summary.
+#[derive(Component)]
+pub struct SummaryCard {}
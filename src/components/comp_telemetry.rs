@@ -0,0 +1,16 @@
+use thomas::Component;
+
+use crate::{TelemetryEvent, TelemetryWriter};
+
+#[derive(Component)]
+pub struct Telemetry {
+  pub writer: Option<TelemetryWriter>,
+}
+impl Telemetry {
+  /// Records the event if telemetry is enabled. Does nothing otherwise.
+  pub fn record(&self, event: TelemetryEvent) {
+    if let Some(writer) = &self.writer {
+      writer.record(event);
+    }
+  }
+}
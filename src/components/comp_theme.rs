@@ -0,0 +1,77 @@
+use thomas::Component;
+
+use crate::{TerminalSupport, Theme, ThemeRole};
+
+/// The themes the player can switch between, and the one in use.
+#[derive(Component)]
+pub struct Themes {
+  /// The built-in themes, followed by the theme chosen at launch if it isn't one of them. Every theme is already
+  /// adapted to what the terminal can show.
+  pub themes: Vec<Theme>,
+  pub current: usize,
+}
+impl Themes {
+  pub fn new(selected: Option<Theme>, terminal_support: &TerminalSupport) -> Self {
+    let mut themes = Theme::built_ins();
+
+    let current = match selected {
+      Some(selected) => themes
+        .iter()
+        .position(|theme| *theme == selected)
+        .unwrap_or_else(|| {
+          themes.push(selected);
+
+          themes.len() - 1
+        }),
+      None => 0,
+    };
+
+    Self {
+      themes: themes
+        .into_iter()
+        .map(|theme| theme.adapted_to(terminal_support))
+        .collect(),
+      current,
+    }
+  }
+
+  pub fn current(&self) -> &Theme {
+    &self.themes[self.current]
+  }
+
+  pub fn cycle(&mut self) {
+    self.current = (self.current + 1) % self.themes.len();
+  }
+}
+
+/// Marks an entity whose colours and glyphs have been swapped for ones the terminal can show.
+#[derive(Component)]
+pub struct TerminalAdapted {}
+
+/// Marks an entity that's restyled whenever the theme changes.
+#[derive(Component)]
+pub struct Themed {
+  pub role: ThemeRole,
+  /// Whether the theme picks the glyph too. Cells of a sprite keep the glyph from the sprite's art.
+  pub is_glyph_themed: bool,
+  /// Index into `Themes::themes` of the theme the entity is currently drawn in. `None` until it's first restyled,
+  /// while it's still drawn in the unadapted Classic theme the prefabs use.
+  pub applied_theme: Option<usize>,
+}
+impl Themed {
+  pub fn new(role: ThemeRole) -> Self {
+    Self {
+      role,
+      is_glyph_themed: true,
+      applied_theme: None,
+    }
+  }
+
+  pub fn color_only(role: ThemeRole) -> Self {
+    Self {
+      role,
+      is_glyph_themed: false,
+      applied_theme: None,
+    }
+  }
+}
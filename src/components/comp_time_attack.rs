@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+
+use thomas::Component;
+
+use crate::{format_seconds, TIME_ATTACK_WAVES};
+
+/// The splits of the time attack run in progress, and of the fastest run it's racing against. A split is the time
+/// on the clock when a wave is cleared, counted from the start of the first wave.
+#[derive(Component)]
+pub struct TimeAttack {
+  pub splits: Vec<u128>,
+  /// Empty until a run has been finished.
+  pub best_splits: Vec<u128>,
+}
+impl TimeAttack {
+  /// Saved as `<wave>=<split>`. Best splits that don't cover every wave are dropped.
+  pub fn from_saved(saved: &HashMap<String, String>) -> Self {
+    let best_splits: Option<Vec<u128>> = (1..=TIME_ATTACK_WAVES)
+      .map(|wave| saved.get(&wave.to_string())?.parse().ok())
+      .collect();
+
+    Self {
+      splits: vec![],
+      best_splits: best_splits.unwrap_or_default(),
+    }
+  }
+
+  pub fn to_saved(&self) -> Vec<(String, String)> {
+    self
+      .best_splits
+      .iter()
+      .enumerate()
+      .map(|(index, split)| ((index + 1).to_string(), split.to_string()))
+      .collect()
+  }
+
+  pub fn record_split(&mut self, wave_time_millis: u128) {
+    let previous = self.splits.last().copied().unwrap_or(0);
+
+    self.splits.push(previous + wave_time_millis);
+  }
+
+  /// How far behind the best run the latest split is, in milliseconds. Negative when it's ahead, and `None` when
+  /// there's no best run to compare against.
+  pub fn latest_delta(&self) -> Option<i128> {
+    let split = *self.splits.last()?;
+    let best_split = *self.best_splits.get(self.splits.len() - 1)?;
+
+    Some(split as i128 - best_split as i128)
+  }
+
+  /// The wave the latest split was taken at and its time, with how far it is from the best run's.
+  pub fn describe_latest_split(&self) -> Option<String> {
+    let split = format!(
+      "{} {}",
+      self.splits.len(),
+      format_seconds(*self.splits.last()?)
+    );
+
+    Some(match self.latest_delta() {
+      Some(delta) => format!("{split} ({})", format_delta(delta)),
+      None => split,
+    })
+  }
+
+  /// Whether the run is finished, and faster than any before it.
+  pub fn is_personal_best(&self) -> bool {
+    self.splits.len() as u64 == TIME_ATTACK_WAVES
+      && self
+        .best_splits
+        .last()
+        .is_none_or(|best_time| self.splits.last().is_some_and(|time| time < best_time))
+  }
+}
+
+/// How far behind (+) or ahead (-) of the best run a split is, in seconds.
+pub fn format_delta(delta_millis: i128) -> String {
+  let sign = if delta_millis > 0 { '+' } else { '-' };
+
+  format!("{sign}{}", format_seconds(delta_millis.unsigned_abs()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn time_attack(splits: &[u128], best_splits: &[u128]) -> TimeAttack {
+    TimeAttack {
+      splits: splits.to_vec(),
+      best_splits: best_splits.to_vec(),
+    }
+  }
+
+  fn full_run(wave_time_millis: u128) -> Vec<u128> {
+    (1..=TIME_ATTACK_WAVES as u128)
+      .map(|wave| wave * wave_time_millis)
+      .collect()
+  }
+
+  #[test]
+  fn splits_add_up_the_wave_times() {
+    let mut time_attack = time_attack(&[], &[]);
+
+    time_attack.record_split(12_000);
+    time_attack.record_split(8_500);
+    time_attack.record_split(10_250);
+
+    assert_eq!(time_attack.splits, vec![12_000, 20_500, 30_750]);
+  }
+
+  #[test]
+  fn delta_is_against_the_best_split_at_the_same_wave() {
+    let best_splits = full_run(10_000);
+
+    assert_eq!(
+      time_attack(&[9_000, 21_500], &best_splits).latest_delta(),
+      Some(1_500)
+    );
+    assert_eq!(
+      time_attack(&[9_000], &best_splits).latest_delta(),
+      Some(-1_000)
+    );
+    assert_eq!(time_attack(&[10_000], &best_splits).latest_delta(), Some(0));
+  }
+
+  #[test]
+  fn there_is_no_delta_without_a_split_or_a_best_run() {
+    assert_eq!(time_attack(&[], &full_run(10_000)).latest_delta(), None);
+    assert_eq!(time_attack(&[9_000], &[]).latest_delta(), None);
+  }
+
+  #[test]
+  fn deltas_are_signed_and_shown_to_the_tenth_of_a_second() {
+    assert_eq!(format_delta(1_500), "+1.5");
+    assert_eq!(format_delta(-320), "-0.3");
+    assert_eq!(format_delta(61_099), "+61.0");
+  }
+
+  #[test]
+  fn describes_the_latest_split_with_its_delta() {
+    assert_eq!(
+      time_attack(&[9_000, 21_500], &full_run(10_000)).describe_latest_split(),
+      Some(String::from("2 21.5 (+1.5)"))
+    );
+    assert_eq!(
+      time_attack(&[9_000], &[]).describe_latest_split(),
+      Some(String::from("1 9.0"))
+    );
+    assert_eq!(time_attack(&[], &[]).describe_latest_split(), None);
+  }
+
+  #[test]
+  fn only_a_faster_finished_run_is_a_personal_best() {
+    let best_splits = full_run(10_000);
+
+    assert!(time_attack(&full_run(10_000), &[]).is_personal_best());
+    assert!(time_attack(&full_run(9_000), &best_splits).is_personal_best());
+    assert!(!time_attack(&full_run(10_000), &best_splits).is_personal_best());
+    assert!(!time_attack(&full_run(11_000), &best_splits).is_personal_best());
+    assert!(!time_attack(&[1_000], &best_splits).is_personal_best());
+  }
+
+  #[test]
+  fn drops_saved_best_splits_that_dont_cover_every_wave() {
+    let complete = time_attack(&[], &full_run(10_000));
+    let saved: HashMap<String, String> = complete.to_saved().into_iter().collect();
+
+    assert_eq!(
+      TimeAttack::from_saved(&saved).best_splits,
+      complete.best_splits
+    );
+
+    let mut partial = saved.clone();
+    partial.remove(&TIME_ATTACK_WAVES.to_string());
+
+    assert!(TimeAttack::from_saved(&partial).best_splits.is_empty());
+  }
+}
@@ -0,0 +1,7 @@
+use thomas::{Component, Timer};
+
+/// Short-lived text that announces something, like an unlocked achievement.
+#[derive(Component)]
+pub struct Toast {
+  pub timer: Timer,
+}
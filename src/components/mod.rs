@@ -11,4 +11,88 @@ mod comp_scorekeeper;
 pub use comp_scorekeeper::*;
 
 mod comp_star;
-pub use comp_star::*;
\ No newline at end of file
+pub use comp_star::*;
+
+mod comp_combo;
+pub use comp_combo::*;
+
+mod comp_game_state;
+pub use comp_game_state::*;
+
+mod comp_stats;
+pub use comp_stats::*;
+
+mod comp_rng;
+pub use comp_rng::*;
+
+mod comp_telemetry;
+pub use comp_telemetry::*;
+
+mod comp_achievements;
+pub use comp_achievements::*;
+
+mod comp_toast;
+pub use comp_toast::*;
+
+mod comp_hud;
+pub use comp_hud::*;
+
+mod comp_localization;
+pub use comp_localization::*;
+
+mod comp_settings;
+pub use comp_settings::*;
+
+mod comp_high_scores;
+pub use comp_high_scores::*;
+
+mod comp_time_attack;
+pub use comp_time_attack::*;
+
+mod comp_daily;
+pub use comp_daily::*;
+
+mod comp_menu;
+pub use comp_menu::*;
+
+mod comp_bonuses;
+pub use comp_bonuses::*;
+
+mod comp_boss;
+pub use comp_boss::*;
+
+mod comp_sprite;
+pub use comp_sprite::*;
+
+mod comp_animation;
+pub use comp_animation::*;
+
+mod comp_particle;
+pub use comp_particle::*;
+
+mod comp_screen_effects;
+pub use comp_screen_effects::*;
+
+mod comp_theme;
+pub use comp_theme::*;
+
+mod comp_game_events;
+pub use comp_game_events::*;
+
+mod comp_audio;
+pub use comp_audio::*;
+
+mod comp_saved_game;
+pub use comp_saved_game::*;
+
+mod comp_bunker;
+pub use comp_bunker::*;
+
+mod comp_levels;
+pub use comp_levels::*;
+
+mod comp_level_editor;
+pub use comp_level_editor::*;
+
+mod comp_campaign;
+pub use comp_campaign::*;
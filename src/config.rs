@@ -0,0 +1,259 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    is_valid_player_name, AudioOutput, Campaign, ColorDepth, Level, Locale, Theme,
+    MAX_PLAYER_NAME_LENGTH, MAX_SCREEN_EFFECT_INTENSITY,
+};
+
+pub const USAGE: &str = "Usage: space_invaders [--seed <number>] [--telemetry <path>] [--sprites] \
+    [--shake <0-3>] [--flash <0-3>] [--no-screen-effects] [--theme <name|path>] \
+    [--colors <truecolor|256|16>] [--ascii] [--audio <off|bell|wav:<path>>] [--lang <code>] \
+    [--level <path>] [--edit <path>] [--campaign <dir>] [--name <name>]";
+
+/// Options chosen when launching the game. Any that are set take precedence over the saved settings.
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    /// Seed for gameplay randomness. When absent, every run gets a fresh random seed.
+    pub seed: Option<u64>,
+    /// Where to append telemetry events. Telemetry is off unless this is set.
+    pub telemetry_path: Option<PathBuf>,
+    /// Draw the player and invaders with multi-cell sprites instead of single characters.
+    pub use_sprites: bool,
+    pub shake_intensity: Option<u8>,
+    pub flash_intensity: Option<u8>,
+    /// The theme to start with. Classic when absent.
+    pub theme: Option<Theme>,
+    /// The size of the palette colours are rounded to. Detected from the environment when absent.
+    pub color_depth: Option<ColorDepth>,
+    /// Only draw ASCII glyphs, even if the locale says the terminal handles Unicode.
+    pub force_ascii: bool,
+    /// Where sound cues go. Silent by default.
+    pub audio: AudioOutput,
+    /// The language of the game's text. Detected from the system locale when absent.
+    pub locale: Option<Locale>,
+    /// The level file the formations are laid out from. Classic when absent.
+    pub level: Option<Level>,
+    /// Start in the level editor, working on this file. It's created when it's first saved.
+    pub edit_path: Option<PathBuf>,
+    /// The level pack the campaign is played from. The built-in campaign when absent.
+    pub campaign: Option<Campaign>,
+    /// Who daily challenge results go under. Taken from the system's user name when absent.
+    pub player_name: Option<String>,
+}
+impl Config {
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut config = Config::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => {
+                    let value = next_value(&mut args, &arg)?;
+
+                    config.seed = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("Seed must be a whole number, got '{value}'."))?,
+                    );
+                }
+                "--telemetry" => {
+                    config.telemetry_path = Some(PathBuf::from(next_value(&mut args, &arg)?));
+                }
+                "--sprites" => {
+                    config.use_sprites = true;
+                }
+                "--shake" => {
+                    config.shake_intensity =
+                        Some(parse_intensity(&next_value(&mut args, &arg)?, &arg)?);
+                }
+                "--flash" => {
+                    config.flash_intensity =
+                        Some(parse_intensity(&next_value(&mut args, &arg)?, &arg)?);
+                }
+                "--no-screen-effects" => {
+                    config.shake_intensity = Some(0);
+                    config.flash_intensity = Some(0);
+                }
+                "--theme" => {
+                    let value = next_value(&mut args, &arg)?;
+
+                    config.theme = Some(match Theme::find_built_in(&value) {
+                        Some(theme) => theme,
+                        None => Theme::load(Path::new(&value))?,
+                    });
+                }
+                "--colors" => {
+                    let value = next_value(&mut args, &arg)?;
+
+                    config.color_depth = Some(ColorDepth::parse(&value).ok_or_else(|| {
+                        format!("{arg} expects truecolor, 256 or 16, got '{value}'.")
+                    })?);
+                }
+                "--ascii" => {
+                    config.force_ascii = true;
+                }
+                "--audio" => {
+                    let value = next_value(&mut args, &arg)?;
+
+                    config.audio = AudioOutput::parse(&value).ok_or_else(|| {
+                        format!("{arg} expects off, bell or wav:<path>, got '{value}'.")
+                    })?;
+                }
+                "--lang" => {
+                    let value = next_value(&mut args, &arg)?;
+
+                    config.locale = Some(Locale::find_built_in(&value).ok_or_else(|| {
+                        format!(
+                            "{arg} expects a language the game has, like en or es, got '{value}'."
+                        )
+                    })?);
+                }
+                "--level" => {
+                    config.level = Some(Level::load(Path::new(&next_value(&mut args, &arg)?))?);
+                }
+                "--edit" => {
+                    config.edit_path = Some(PathBuf::from(next_value(&mut args, &arg)?));
+                }
+                "--campaign" => {
+                    config.campaign =
+                        Some(Campaign::load(Path::new(&next_value(&mut args, &arg)?))?);
+                }
+                "--name" => {
+                    let value = next_value(&mut args, &arg)?;
+
+                    if !is_valid_player_name(&value) {
+                        return Err(format!(
+                            "{arg} expects up to {MAX_PLAYER_NAME_LENGTH} characters without an '=', got '{value}'."
+                        ));
+                    }
+
+                    config.player_name = Some(value);
+                }
+                _ => return Err(format!("Unknown argument '{arg}'.")),
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+fn next_value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("{flag} expects a value."))
+}
+
+fn parse_intensity(value: &str, flag: &str) -> Result<u8, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|intensity| *intensity <= MAX_SCREEN_EFFECT_INTENSITY)
+        .ok_or_else(|| {
+            format!(
+                "{flag} expects a number from 0 to {MAX_SCREEN_EFFECT_INTENSITY}, got '{value}'."
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_args(args: &[&str]) -> Result<Config, String> {
+        Config::from_args(args.iter().map(|arg| String::from(*arg)))
+    }
+
+    #[test]
+    fn no_args_gives_the_defaults() {
+        let config = from_args(&[]).unwrap();
+
+        assert_eq!(config.seed, None);
+        assert!(!config.use_sprites);
+        assert_eq!(config.shake_intensity, None);
+        assert_eq!(config.flash_intensity, None);
+        assert!(config.theme.is_none());
+        assert_eq!(config.audio, AudioOutput::Off);
+    }
+
+    #[test]
+    fn reads_flags_and_values() {
+        let config = from_args(&[
+            "--seed",
+            "42",
+            "--sprites",
+            "--shake",
+            "3",
+            "--flash",
+            "1",
+            "--colors",
+            "256",
+            "--ascii",
+            "--audio",
+            "wav:session.wav",
+            "--lang",
+            "es",
+        ])
+        .unwrap();
+
+        assert_eq!(config.seed, Some(42));
+        assert!(config.use_sprites);
+        assert_eq!(config.shake_intensity, Some(3));
+        assert_eq!(config.flash_intensity, Some(1));
+        assert_eq!(config.color_depth, Some(ColorDepth::Ansi256));
+        assert!(config.force_ascii);
+        assert_eq!(config.audio, AudioOutput::Wav(PathBuf::from("session.wav")));
+        assert_eq!(config.locale.unwrap().code, "es");
+    }
+
+    #[test]
+    fn no_screen_effects_turns_both_off() {
+        let config = from_args(&["--no-screen-effects"]).unwrap();
+
+        assert_eq!(config.shake_intensity, Some(0));
+        assert_eq!(config.flash_intensity, Some(0));
+    }
+
+    #[test]
+    fn finds_built_in_themes_by_name() {
+        let theme = Theme::built_ins().pop().unwrap();
+        let config = from_args(&["--theme", &theme.slug()]).unwrap();
+
+        assert_eq!(config.theme, Some(theme));
+    }
+
+    #[test]
+    fn rejects_bad_values() {
+        assert!(from_args(&["--seed", "soon"]).is_err());
+        assert!(from_args(&["--shake", "4"]).is_err());
+        assert!(from_args(&["--flash", "-1"]).is_err());
+        assert!(from_args(&["--colors", "mono"]).is_err());
+        assert!(from_args(&["--audio", "speakers"]).is_err());
+        assert!(from_args(&["--lang", "xx"]).is_err());
+        assert!(from_args(&["--theme", "no-such-theme"]).is_err());
+    }
+
+    #[test]
+    fn rejects_flags_missing_a_value() {
+        assert_eq!(
+            from_args(&["--seed"]).unwrap_err(),
+            "--seed expects a value."
+        );
+    }
+
+    #[test]
+    fn checks_player_names() {
+        assert_eq!(
+            from_args(&["--name", "ana"]).unwrap().player_name,
+            Some(String::from("ana"))
+        );
+        assert!(from_args(&["--name", "a=b"]).is_err());
+        assert!(from_args(&["--name", ""]).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_args() {
+        assert_eq!(
+            from_args(&["--fast"]).unwrap_err(),
+            "Unknown argument '--fast'."
+        );
+    }
+}
@@ -6,18 +6,89 @@ pub const SCREEN_HEIGHT: u64 = 20;
 pub const UI_Y_START_POSITION: u64 = 17;
 
 pub const PLAYER_DISPLAY_CHAR: char = 'A';
+pub const PLAYER_SPRITE_ART: [&str; 2] = ["/^\\", "<=>"];
 pub const PLAYER_COLLISION_LAYER: Layer = Layer(1);
 pub const PLAYER_BULLET_DISPLAY_CHAR: char = '"';
 pub const PLAYER_BULLET_COLLISION_LAYER: Layer = Layer(3);
 pub const PLAYER_STARTING_LIVES: u8 = 3;
+pub const PLAYER_MAX_LIVES: u8 = 9;
+pub const PLAYER_SHOOT_WAIT_MILLIS: u128 = 100;
 pub const PLAYER_COLOR: Rgb = Rgb(0, 255, 0);
 
 pub const BACKGROUND_LAYER: i32 = -10;
 pub const BULLET_LAYER: i32 = -1;
 
 pub const ENEMY_DISPLAY_CHAR: char = 'X';
+pub const ENEMY_ALT_DISPLAY_CHAR: char = 'x';
+pub const SQUID_SPRITE_ART: [&str; 2] = ["{@}", "/\"\\"];
+pub const SQUID_ALT_SPRITE_ART: [&str; 2] = ["{@}", "\\\"/"];
+pub const CRAB_SPRITE_ART: [&str; 2] = ["\\o/", "/ \\"];
+pub const CRAB_ALT_SPRITE_ART: [&str; 2] = ["/o\\", "| |"];
+pub const OCTOPUS_SPRITE_ART: [&str; 2] = ["(X)", "/W\\"];
+pub const OCTOPUS_ALT_SPRITE_ART: [&str; 2] = ["(X)", "\\M/"];
 pub const ENEMY_COLLISION_LAYER: Layer = Layer(2);
 pub const ENEMY_BULLET_DISPLAY_CHAR: char = '•';
 pub const ENEMY_BULLET_COLLISION_LAYER: Layer = Layer(4);
 pub const ENEMY_POINT_VALUE: u64 = 100;
 pub const ENEMY_COLOR: Rgb = Rgb(196, 63, 96);
+
+pub const EXPLOSION_FRAME_INTERVAL_MILLIS: u128 = 80;
+pub const PLAYER_HIT_BLINK_TIMES: usize = 5;
+pub const PLAYER_HIT_BLINK_INTERVAL_MILLIS: u128 = 100;
+
+/// A bunker block's glyph at each hp, from one hit away from being shot away up to untouched.
+pub const BUNKER_DISPLAY_CHARS: [char; 3] = ['-', '=', '#'];
+pub const BUNKER_COLLISION_LAYER: Layer = Layer(6);
+pub const BUNKER_MAX_HP: u8 = 3;
+pub const BUNKER_COLOR: Rgb = Rgb(64, 200, 64);
+
+pub const BOSS_WAVE_INTERVAL: u64 = 5;
+pub const BOSS_SPRITE_ART: [&str; 3] = ["  /MMM\\  ", "<#=o#o=#>", " \\/ V \\/ "];
+pub const BOSS_WEAK_SPOT_DISPLAY_CHAR: char = 'o';
+pub const BOSS_COLLISION_LAYER: Layer = Layer(5);
+pub const BOSS_BASE_HP: u64 = 40;
+pub const BOSS_HP_PER_APPEARANCE: u64 = 20;
+pub const BOSS_WEAK_SPOT_DAMAGE: u64 = 3;
+pub const BOSS_POINT_VALUE: u64 = 5000;
+pub const BOSS_COLOR: Rgb = Rgb(255, 128, 0);
+pub const BOSS_WEAK_SPOT_COLOR: Rgb = Rgb(255, 255, 0);
+
+pub const COMBO_WINDOW_MILLIS: u128 = 1500;
+pub const COMBO_HITS_PER_MULTIPLIER_STEP: u64 = 4;
+pub const COMBO_MAX_MULTIPLIER: u64 = 8;
+
+pub const EXTRA_LIFE_SCORE_INTERVAL: u64 = 10000;
+pub const EXTRA_LIFE_MAX_AWARDED: u8 = 3;
+
+pub const WAVE_TIME_BONUS_PAR_MILLIS: u128 = 60000;
+pub const WAVE_TIME_BONUS_PER_SECOND: u64 = 50;
+pub const WAVE_NO_DAMAGE_BONUS: u64 = 1000;
+pub const WAVE_MAX_ACCURACY_BONUS: u64 = 2000;
+
+pub const MAX_PARTICLES: usize = 150;
+
+pub const MAX_SCREEN_EFFECT_INTENSITY: u8 = 3;
+pub const DEFAULT_SHAKE_INTENSITY: u8 = 2;
+pub const DEFAULT_FLASH_INTENSITY: u8 = 2;
+pub const SHAKE_DURATION_MILLIS: u128 = 250;
+pub const FLASH_DURATION_MILLIS: u128 = 100;
+
+/// Banners sit above the middle of the screen, clear of the summary cards.
+pub const BANNER_ROW_OFFSET: i64 = -6;
+pub const BANNER_DURATION_MILLIS: u128 = 1500;
+pub const GAME_OVER_BANNER_DURATION_MILLIS: u128 = 3000;
+
+pub const MAX_HIGH_SCORES: usize = 10;
+pub const SETTINGS_FILE_NAME: &str = "settings.txt";
+pub const SAVED_GAME_FILE_NAME: &str = "saved_game.txt";
+/// Where the editor saves when it isn't given a level file.
+pub const EDITOR_LEVEL_FILE_NAME: &str = "custom_level.txt";
+/// How far the player has got through each campaign.
+pub const CAMPAIGN_PROGRESS_FILE_NAME: &str = "campaign_progress.txt";
+/// The result of each day's daily challenge.
+pub const DAILY_RESULTS_FILE_NAME: &str = "daily_results.txt";
+/// The splits of the fastest time attack run.
+pub const TIME_ATTACK_SPLITS_FILE_NAME: &str = "time_attack_splits.txt";
+
+/// The waves a time attack run has to clear. The last of them is a boss wave.
+pub const TIME_ATTACK_WAVES: u64 = BOSS_WAVE_INTERVAL;
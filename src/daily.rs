@@ -0,0 +1,418 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{EnemyKind, HighScore, Level, LevelCell, MarchRule};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Who a daily result belongs to when no name can be found for the player.
+pub const DEFAULT_PLAYER_NAME: &str = "Player";
+pub const MAX_PLAYER_NAME_LENGTH: usize = 16;
+
+/// The formations a daily challenge cycles through.
+const DAILY_LEVELS: u64 = 3;
+const DAILY_MODIFIERS: usize = 2;
+const DAILY_INVADER_ROWS: usize = 4;
+const DAILY_MIN_COLUMNS: usize = 8;
+const DAILY_MAX_COLUMNS: usize = 11;
+/// The chance each spot in the formation is left empty.
+const DAILY_GAP_CHANCE: f64 = 0.1;
+/// The daily formations are timed and armed the same whatever the difficulty, so everyone plays the same run.
+const DAILY_MOVE_WAIT_MILLIS: u128 = 800;
+const DAILY_MOVE_SPEEDUP_PER_LEVEL: u128 = 100;
+const DAILY_SHOOT_CHANCE: u8 = 10;
+const DAILY_SHOOT_CHANCE_PER_LEVEL: u8 = 2;
+
+/// A twist on the daily challenge's run.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DailyModifier {
+    /// The formations step faster.
+    Swift,
+    /// The invaders shoot twice as often.
+    TriggerHappy,
+    NoBunkers,
+    /// The formations march the whole way across before turning.
+    Sweep,
+    /// The run starts with a single life.
+    LastLife,
+}
+impl DailyModifier {
+    pub const ALL: [DailyModifier; 5] = [
+        DailyModifier::Swift,
+        DailyModifier::TriggerHappy,
+        DailyModifier::NoBunkers,
+        DailyModifier::Sweep,
+        DailyModifier::LastLife,
+    ];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            DailyModifier::Swift => "swift",
+            DailyModifier::TriggerHappy => "trigger_happy",
+            DailyModifier::NoBunkers => "no_bunkers",
+            DailyModifier::Sweep => "sweep",
+            DailyModifier::LastLife => "last_life",
+        }
+    }
+
+    fn apply(&self, level: &mut Level) {
+        match self {
+            DailyModifier::Swift => {
+                level.move_wait_millis = level.move_wait_millis.map(|millis| millis * 3 / 5);
+            }
+            DailyModifier::TriggerHappy => {
+                level.shoot_chance = level.shoot_chance.map(|chance| (chance * 2).min(100));
+            }
+            DailyModifier::NoBunkers => level.bunker_rows.clear(),
+            DailyModifier::Sweep => level.march_rule = MarchRule::Sweep,
+            DailyModifier::LastLife => {}
+        }
+    }
+}
+
+/// The challenge for one day. Everything about it follows from the date, so everyone who plays on the same day
+/// plays the same run.
+#[derive(Clone, PartialEq, Debug)]
+pub struct DailyChallenge {
+    /// Days since 1970-01-01.
+    pub day: u64,
+    /// The seed for the run's randomness.
+    pub seed: u64,
+    pub modifiers: Vec<DailyModifier>,
+    pub levels: Vec<Level>,
+}
+impl DailyChallenge {
+    pub fn today() -> Self {
+        Self::for_day(today())
+    }
+
+    pub fn for_day(day: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(day);
+        let seed = rng.gen();
+        let modifiers: Vec<DailyModifier> = DailyModifier::ALL
+            .choose_multiple(&mut rng, DAILY_MODIFIERS)
+            .copied()
+            .collect();
+
+        let levels = (0..DAILY_LEVELS)
+            .map(|index| {
+                let mut level = make_daily_level(index, &mut rng);
+
+                for modifier in &modifiers {
+                    modifier.apply(&mut level);
+                }
+
+                level
+            })
+            .collect();
+
+        Self {
+            day,
+            seed,
+            modifiers,
+            levels,
+        }
+    }
+
+    pub fn has(&self, modifier: DailyModifier) -> bool {
+        self.modifiers.contains(&modifier)
+    }
+
+    /// The date, as `YYYY-MM-DD`.
+    pub fn date(&self) -> String {
+        format_day(self.day)
+    }
+}
+
+/// A formation of randomly picked invaders, a little faster and fiercer for each level into the challenge.
+fn make_daily_level(index: u64, rng: &mut ChaCha8Rng) -> Level {
+    let columns = rng.gen_range(DAILY_MIN_COLUMNS..=DAILY_MAX_COLUMNS);
+
+    let invader_rows = (0..DAILY_INVADER_ROWS)
+        .map(|_| {
+            let kind = *[EnemyKind::Squid, EnemyKind::Crab, EnemyKind::Octopus]
+                .choose(rng)
+                .expect("there should be kinds of invader");
+
+            (0..columns)
+                .map(|_| {
+                    if rng.gen_bool(DAILY_GAP_CHANCE) {
+                        LevelCell::Empty
+                    } else {
+                        LevelCell::Enemy(kind)
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    // Bunkers two blocks wide with two-block gaps, like the classic layout.
+    let bunker_row: Vec<LevelCell> = (0..columns)
+        .map(|col| {
+            if col % 4 < 2 {
+                LevelCell::Bunker
+            } else {
+                LevelCell::Empty
+            }
+        })
+        .collect();
+
+    Level {
+        name: format!("Daily {}", index + 1),
+        move_wait_millis: Some(
+            DAILY_MOVE_WAIT_MILLIS - DAILY_MOVE_SPEEDUP_PER_LEVEL * index as u128,
+        ),
+        shoot_chance: Some(DAILY_SHOOT_CHANCE + DAILY_SHOOT_CHANCE_PER_LEVEL * index as u8),
+        march_rule: MarchRule::Random,
+        invader_rows,
+        bunker_rows: vec![bunker_row.clone(), bunker_row],
+    }
+}
+
+/// The days since 1970-01-01, by the UTC clock so everyone moves on to the next challenge at the same moment.
+pub fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() / SECONDS_PER_DAY)
+        .unwrap_or(0)
+}
+
+/// A day since 1970-01-01 as `YYYY-MM-DD`, in the proleptic Gregorian calendar.
+pub fn format_day(day: u64) -> String {
+    // Counts from 0000-03-01, so leap days fall at the end of each year.
+    let days = day as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day_of_month = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = era * 400 + year_of_era + i64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day_of_month:02}")
+}
+
+/// The scored run of each player on each day, by date and then by name. A player is in a day's results from the
+/// moment their run starts, so quitting part-way through doesn't earn another go.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct DailyResults {
+    pub days: BTreeMap<String, BTreeMap<String, HighScore>>,
+}
+impl DailyResults {
+    /// Saved as `<date>.<name>=<score>,<level>,<time>`. Results from before players were named are saved as
+    /// `<date>=<score>,<level>,<time>` and go under `DEFAULT_PLAYER_NAME`. Results that can't be read are dropped.
+    pub fn from_saved(saved: &HashMap<String, String>) -> Self {
+        let mut days: BTreeMap<String, BTreeMap<String, HighScore>> = BTreeMap::new();
+
+        for (key, value) in saved {
+            let (date, name) = key.split_once('.').unwrap_or((key, DEFAULT_PLAYER_NAME));
+
+            if let Some(result) = HighScore::parse(value) {
+                days.entry(String::from(date))
+                    .or_default()
+                    .insert(String::from(name), result);
+            }
+        }
+
+        Self { days }
+    }
+
+    pub fn to_saved(&self) -> Vec<(String, String)> {
+        self.days
+            .iter()
+            .flat_map(|(date, results)| {
+                results
+                    .iter()
+                    .map(move |(name, result)| (format!("{date}.{name}"), result.to_saved()))
+            })
+            .collect()
+    }
+
+    pub fn get(&self, date: &str, name: &str) -> Option<&HighScore> {
+        self.days.get(date).and_then(|results| results.get(name))
+    }
+
+    pub fn insert(&mut self, date: String, name: String, result: HighScore) {
+        self.days.entry(date).or_default().insert(name, result);
+    }
+
+    /// The most recent day anyone has a result for.
+    pub fn latest_date(&self) -> Option<&String> {
+        self.days.keys().next_back()
+    }
+
+    /// The day's results with the names they belong to, best first. Ties go to whoever got further, then whoever
+    /// was quicker.
+    pub fn ranked(&self, date: &str) -> Vec<(&String, &HighScore)> {
+        let mut ranked: Vec<(&String, &HighScore)> = self
+            .days
+            .get(date)
+            .map(|results| results.iter().collect())
+            .unwrap_or_default();
+
+        ranked.sort_by(|(_, a), (_, b)| {
+            b.score
+                .cmp(&a.score)
+                .then(b.level.cmp(&a.level))
+                .then(a.time_millis.cmp(&b.time_millis))
+        });
+
+        ranked
+    }
+}
+
+/// Whether the name can be kept with the daily results: something to show, short enough for the results to fit
+/// on screen and nothing that would get in the way of reading them back.
+pub fn is_valid_player_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.trim() == name
+        && name.chars().count() <= MAX_PLAYER_NAME_LENGTH
+        && !name.chars().any(|c| c == '=' || c.is_control())
+}
+
+/// The name the system knows the player by, for sharing a computer without having to give a name every launch.
+pub fn detect_player_name() -> String {
+    ["USER", "USERNAME"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| is_valid_player_name(value))
+        .unwrap_or_else(|| String::from(DEFAULT_PLAYER_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(score: u64, level: u64, time_millis: u128) -> HighScore {
+        HighScore {
+            score,
+            level,
+            time_millis,
+        }
+    }
+
+    /// Results from `(date, name, score, level, time)` entries.
+    fn daily_results(entries: &[(&str, &str, u64, u64, u128)]) -> DailyResults {
+        let mut results = DailyResults::default();
+
+        for (date, name, score, level, time_millis) in entries {
+            results.insert(
+                String::from(*date),
+                String::from(*name),
+                result(*score, *level, *time_millis),
+            );
+        }
+
+        results
+    }
+
+    #[test]
+    fn the_same_day_gives_the_same_challenge() {
+        assert_eq!(
+            DailyChallenge::for_day(20745),
+            DailyChallenge::for_day(20745)
+        );
+    }
+
+    #[test]
+    fn each_day_gives_a_different_challenge() {
+        let today = DailyChallenge::for_day(20745);
+        let tomorrow = DailyChallenge::for_day(20746);
+
+        assert_ne!(today.seed, tomorrow.seed);
+        assert_ne!(today.levels, tomorrow.levels);
+    }
+
+    #[test]
+    fn challenges_have_distinct_modifiers_and_valid_levels() {
+        for day in 20000..20100 {
+            let challenge = DailyChallenge::for_day(day);
+
+            assert_eq!(challenge.modifiers.len(), DAILY_MODIFIERS);
+            assert_ne!(challenge.modifiers[0], challenge.modifiers[1]);
+            assert_eq!(challenge.levels.len() as u64, DAILY_LEVELS);
+
+            for level in &challenge.levels {
+                assert_eq!(Level::parse(&level.to_text()).as_ref(), Ok(level));
+            }
+        }
+    }
+
+    #[test]
+    fn formats_days_as_dates() {
+        assert_eq!(format_day(0), "1970-01-01");
+        assert_eq!(format_day(10956), "1999-12-31");
+        assert_eq!(format_day(11016), "2000-02-29");
+        assert_eq!(format_day(19782), "2024-02-29");
+        assert_eq!(format_day(20745), "2026-10-19");
+        assert_eq!(format_day(47541), "2100-03-01");
+    }
+
+    #[test]
+    fn ranks_results_within_the_day() {
+        let results = daily_results(&[
+            ("2026-10-19", "ana", 900, 3, 60_000),
+            ("2026-10-19", "bo", 1500, 4, 90_000),
+            ("2026-10-19", "cy", 900, 3, 45_000),
+            ("2026-10-18", "dee", 5000, 6, 120_000),
+        ]);
+
+        let names: Vec<&String> = results
+            .ranked("2026-10-19")
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+
+        assert_eq!(names, ["bo", "cy", "ana"]);
+        assert!(results.ranked("2026-10-17").is_empty());
+        assert_eq!(results.latest_date().unwrap(), "2026-10-19");
+    }
+
+    #[test]
+    fn reads_back_what_it_saves() {
+        let results = daily_results(&[
+            ("2026-10-19", "ana", 900, 3, 60_000),
+            ("2026-10-19", "j.r", 1500, 4, 90_000),
+            ("2026-10-18", "ana", 400, 2, 30_000),
+        ]);
+
+        let saved = results.to_saved().into_iter().collect();
+
+        assert_eq!(DailyResults::from_saved(&saved), results);
+    }
+
+    #[test]
+    fn reads_results_from_before_players_were_named() {
+        let saved = HashMap::from([(String::from("2026-10-19"), String::from("900,3,60000"))]);
+
+        assert_eq!(
+            DailyResults::from_saved(&saved).get("2026-10-19", DEFAULT_PLAYER_NAME),
+            Some(&result(900, 3, 60_000))
+        );
+    }
+
+    #[test]
+    fn checks_player_names() {
+        assert!(is_valid_player_name("ana"));
+        assert!(is_valid_player_name("Ana María"));
+        assert!(!is_valid_player_name(""));
+        assert!(!is_valid_player_name(" ana"));
+        assert!(!is_valid_player_name("a=b"));
+        assert!(!is_valid_player_name("a\tb"));
+        assert!(!is_valid_player_name(
+            &"a".repeat(MAX_PLAYER_NAME_LENGTH + 1)
+        ));
+    }
+}
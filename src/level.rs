@@ -0,0 +1,544 @@
+use std::{fmt, fs, io, path::Path};
+
+use crate::EnemyKind;
+
+/// The widest formation a level can lay out, so the sprite formation still has room to march.
+pub const MAX_LEVEL_COLUMNS: usize = 11;
+/// Invader rows, counting any empty rows between them.
+pub const MAX_LEVEL_INVADER_ROWS: usize = 4;
+pub const MAX_LEVEL_BUNKER_ROWS: usize = 3;
+pub const MIN_LEVEL_MOVE_WAIT_MILLIS: u128 = 50;
+pub const MAX_LEVEL_MOVE_WAIT_MILLIS: u128 = 5000;
+
+const CLASSIC_LEVEL: &str = include_str!("../levels/classic.txt");
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LevelCell {
+    Empty,
+    Enemy(EnemyKind),
+    Bunker,
+}
+impl LevelCell {
+    pub fn symbol(&self) -> char {
+        match self {
+            LevelCell::Enemy(EnemyKind::Squid) => 'S',
+            LevelCell::Enemy(EnemyKind::Crab) => 'C',
+            LevelCell::Enemy(EnemyKind::Octopus) => 'O',
+            LevelCell::Bunker => '#',
+            _ => '.',
+        }
+    }
+
+    fn parse(character: char) -> Option<Self> {
+        match character {
+            '.' | ' ' => Some(LevelCell::Empty),
+            'S' => Some(LevelCell::Enemy(EnemyKind::Squid)),
+            'C' => Some(LevelCell::Enemy(EnemyKind::Crab)),
+            'O' => Some(LevelCell::Enemy(EnemyKind::Octopus)),
+            '#' => Some(LevelCell::Bunker),
+            _ => None,
+        }
+    }
+}
+
+/// How the formation picks which way to step.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MarchRule {
+    /// A random direction every step, turning back at the edges.
+    Random,
+    /// Keeps going one way until it reaches an edge, like the arcade formation.
+    Sweep,
+}
+impl MarchRule {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MarchRule::Random => "random",
+            MarchRule::Sweep => "sweep",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "random" => Some(MarchRule::Random),
+            "sweep" => Some(MarchRule::Sweep),
+            _ => None,
+        }
+    }
+}
+
+/// Where a level file went wrong. Lines and columns count from 1.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct LevelError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+impl LevelError {
+    pub fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+}
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// A formation read from a level file: a header of `key=value` lines, then a `map:` line followed by the map.
+///
+/// In the map, `S`, `C` and `O` are squids, crabs and octopuses, `#` is a bunker block and `.` or a space is
+/// empty. Invader rows come first and are laid out from the top of the screen. Bunker rows come after them and
+/// are laid out upwards from just above the player, however many empty rows separate the two.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Level {
+    pub name: String,
+    /// How long the formation waits between steps. The difficulty decides when absent.
+    pub move_wait_millis: Option<u128>,
+    /// The percentage chance each invader shoots when it's ready to. The difficulty decides when absent.
+    pub shoot_chance: Option<u8>,
+    pub march_rule: MarchRule,
+    pub invader_rows: Vec<Vec<LevelCell>>,
+    pub bunker_rows: Vec<Vec<LevelCell>>,
+}
+impl Level {
+    /// The formation the game has always had, with a row of bunkers.
+    pub fn classic() -> Self {
+        Self::parse(CLASSIC_LEVEL).expect("the built-in level should be valid")
+    }
+
+    /// Loads a level from a file. The name defaults to the file name.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read level file {}: {e}", path.display()))?;
+
+        let mut level = Self::parse(&contents).map_err(|errors| {
+            errors
+                .iter()
+                .map(|error| format!("Invalid level file {}:{error}", path.display()))
+                .collect::<Vec<String>>()
+                .join("\n")
+        })?;
+
+        if level.name.is_empty() {
+            if let Some(file_stem) = path.file_stem() {
+                level.name = file_stem.to_string_lossy().into_owned();
+            }
+        }
+
+        Ok(level)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, self.to_text())
+    }
+
+    /// The level written out in the level file format, with a blank line between the invaders and the bunkers
+    /// so the map reads the way it's laid out.
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![format!("name={}", self.name)];
+
+        if let Some(move_wait_millis) = self.move_wait_millis {
+            lines.push(format!("speed={move_wait_millis}"));
+        }
+
+        if let Some(shoot_chance) = self.shoot_chance {
+            lines.push(format!("fire_rate={shoot_chance}"));
+        }
+
+        lines.push(format!("march={}", self.march_rule.name()));
+        lines.push(String::from("map:"));
+
+        let row_text = |row: &Vec<LevelCell>| row.iter().map(LevelCell::symbol).collect::<String>();
+
+        lines.extend(self.invader_rows.iter().map(row_text));
+
+        if !self.bunker_rows.is_empty() {
+            lines.push(String::new());
+            lines.extend(self.bunker_rows.iter().map(row_text));
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    /// Reads a level, or reports every problem with it rather than stopping at the first.
+    pub fn parse(contents: &str) -> Result<Self, Vec<LevelError>> {
+        let mut errors = vec![];
+        let mut level = Self {
+            name: String::new(),
+            move_wait_millis: None,
+            shoot_chance: None,
+            march_rule: MarchRule::Random,
+            invader_rows: vec![],
+            bunker_rows: vec![],
+        };
+
+        let mut lines = contents
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line));
+        let mut map_line = None;
+
+        for (line_number, line) in lines.by_ref() {
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            if trimmed == "map:" {
+                map_line = Some(line_number);
+                break;
+            }
+
+            // Columns count characters rather than bytes, so they line up with what an editor shows.
+            let column = line.chars().take_while(|c| c.is_whitespace()).count() + 1;
+
+            let Some((key, value)) = trimmed.split_once('=') else {
+                errors.push(LevelError::new(
+                    line_number,
+                    column,
+                    "expected key=value or map:",
+                ));
+                continue;
+            };
+
+            let (key, value) = (key.trim(), value.trim());
+            let value_column = column + trimmed.chars().take_while(|c| *c != '=').count() + 1;
+            let value_error = |expected: &str| {
+                LevelError::new(
+                    line_number,
+                    value_column,
+                    format!("{key} expects {expected}, got '{value}'"),
+                )
+            };
+
+            match key {
+                "name" => level.name = String::from(value),
+                "speed" => match value.parse() {
+                    Ok(millis)
+                        if (MIN_LEVEL_MOVE_WAIT_MILLIS..=MAX_LEVEL_MOVE_WAIT_MILLIS)
+                            .contains(&millis) =>
+                    {
+                        level.move_wait_millis = Some(millis)
+                    }
+                    _ => errors.push(value_error(&format!(
+                        "milliseconds from {MIN_LEVEL_MOVE_WAIT_MILLIS} to {MAX_LEVEL_MOVE_WAIT_MILLIS}"
+                    ))),
+                },
+                "fire_rate" => match value.parse() {
+                    Ok(chance) if chance <= 100 => level.shoot_chance = Some(chance),
+                    _ => errors.push(value_error("a percentage from 0 to 100")),
+                },
+                "march" => match MarchRule::parse(value) {
+                    Some(march_rule) => level.march_rule = march_rule,
+                    None => errors.push(value_error("random or sweep")),
+                },
+                _ => errors.push(LevelError::new(
+                    line_number,
+                    column,
+                    format!("unknown key '{key}'"),
+                )),
+            }
+        }
+
+        let Some(map_line) = map_line else {
+            errors.push(LevelError::new(
+                contents.lines().count() + 1,
+                1,
+                "missing map: line",
+            ));
+            return Err(errors);
+        };
+
+        let mut rows: Vec<(usize, Vec<LevelCell>)> = vec![];
+
+        for (line_number, line) in lines {
+            let mut row = vec![];
+
+            for (index, character) in line.trim_end().chars().enumerate() {
+                match LevelCell::parse(character) {
+                    Some(cell) => row.push(cell),
+                    None => {
+                        errors.push(LevelError::new(
+                            line_number,
+                            index + 1,
+                            format!("unknown cell '{character}'"),
+                        ));
+                        row.push(LevelCell::Empty);
+                    }
+                }
+            }
+
+            if row.len() > MAX_LEVEL_COLUMNS {
+                errors.push(LevelError::new(
+                    line_number,
+                    MAX_LEVEL_COLUMNS + 1,
+                    format!("rows can be at most {MAX_LEVEL_COLUMNS} cells wide"),
+                ));
+            }
+
+            rows.push((line_number, row));
+        }
+
+        let is_empty = |row: &[LevelCell]| row.iter().all(|cell| *cell == LevelCell::Empty);
+        let has_bunker = |row: &[LevelCell]| row.contains(&LevelCell::Bunker);
+
+        while rows.last().is_some_and(|(_, row)| is_empty(row)) {
+            rows.pop();
+        }
+
+        let first_bunker_row = rows
+            .iter()
+            .position(|(_, row)| has_bunker(row))
+            .unwrap_or(rows.len());
+
+        for (line_number, row) in &rows[..first_bunker_row] {
+            if level.invader_rows.len() == MAX_LEVEL_INVADER_ROWS && !is_empty(row) {
+                errors.push(LevelError::new(
+                    *line_number,
+                    1,
+                    format!("a level can have at most {MAX_LEVEL_INVADER_ROWS} invader rows"),
+                ));
+                break;
+            }
+
+            level.invader_rows.push(row.clone());
+        }
+
+        while level.invader_rows.last().is_some_and(|row| is_empty(row)) {
+            level.invader_rows.pop();
+        }
+
+        if level.invader_rows.is_empty() {
+            errors.push(LevelError::new(map_line, 1, "the map has no invaders"));
+        }
+
+        for (line_number, row) in &rows[first_bunker_row..] {
+            if let Some(index) = row
+                .iter()
+                .position(|cell| matches!(cell, LevelCell::Enemy(_)))
+            {
+                errors.push(LevelError::new(
+                    *line_number,
+                    index + 1,
+                    "invaders must come before the bunkers, in rows of their own",
+                ));
+                continue;
+            }
+
+            if level.bunker_rows.len() == MAX_LEVEL_BUNKER_ROWS {
+                errors.push(LevelError::new(
+                    *line_number,
+                    1,
+                    format!("a level can have at most {MAX_LEVEL_BUNKER_ROWS} bunker rows"),
+                ));
+                break;
+            }
+
+            level.bunker_rows.push(row.clone());
+        }
+
+        if errors.is_empty() {
+            Ok(level)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// The number of columns the formation is laid out in, which the bunkers share.
+    pub fn columns(&self) -> usize {
+        self.invader_rows
+            .iter()
+            .chain(self.bunker_rows.iter())
+            .map(|row| row.len())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_errors(contents: &str) -> Vec<LevelError> {
+        Level::parse(contents).unwrap_err()
+    }
+
+    #[test]
+    fn reads_the_header_and_map() {
+        let level =
+            Level::parse("name=Test\nspeed=400\nfire_rate=20\nmarch=sweep\nmap:\nSCO\n...\n#.#\n")
+                .unwrap();
+
+        assert_eq!(level.name, "Test");
+        assert_eq!(level.move_wait_millis, Some(400));
+        assert_eq!(level.shoot_chance, Some(20));
+        assert_eq!(level.march_rule, MarchRule::Sweep);
+        assert_eq!(level.invader_rows.len(), 1);
+        assert_eq!(level.bunker_rows.len(), 1);
+        assert_eq!(level.columns(), 3);
+    }
+
+    #[test]
+    fn reads_back_what_it_writes() {
+        let level = Level::classic();
+
+        assert_eq!(Level::parse(&level.to_text()).unwrap(), level);
+    }
+
+    #[test]
+    fn reports_lines_that_arent_key_values() {
+        assert_eq!(
+            parse_errors("  fast\nmap:\nS\n"),
+            vec![LevelError::new(1, 3, "expected key=value or map:")]
+        );
+    }
+
+    #[test]
+    fn reports_unknown_keys() {
+        assert_eq!(
+            parse_errors("colour=red\nmap:\nS\n"),
+            vec![LevelError::new(1, 1, "unknown key 'colour'")]
+        );
+    }
+
+    #[test]
+    fn reports_speeds_out_of_range() {
+        assert_eq!(
+            parse_errors("speed=10\nmap:\nS\n"),
+            vec![LevelError::new(
+                1,
+                7,
+                "speed expects milliseconds from 50 to 5000, got '10'"
+            )]
+        );
+    }
+
+    #[test]
+    fn reports_fire_rates_out_of_range() {
+        assert_eq!(
+            parse_errors("fire_rate=101\nmap:\nS\n"),
+            vec![LevelError::new(
+                1,
+                11,
+                "fire_rate expects a percentage from 0 to 100, got '101'"
+            )]
+        );
+    }
+
+    #[test]
+    fn reports_unknown_march_rules() {
+        assert_eq!(
+            parse_errors("march=zigzag\nmap:\nS\n"),
+            vec![LevelError::new(
+                1,
+                7,
+                "march expects random or sweep, got 'zigzag'"
+            )]
+        );
+    }
+
+    #[test]
+    fn reports_a_missing_map() {
+        assert_eq!(
+            parse_errors("name=Test\n"),
+            vec![LevelError::new(2, 1, "missing map: line")]
+        );
+    }
+
+    #[test]
+    fn reports_unknown_cells() {
+        assert_eq!(
+            parse_errors("map:\nS?C\n"),
+            vec![LevelError::new(2, 2, "unknown cell '?'")]
+        );
+    }
+
+    #[test]
+    fn reports_rows_that_are_too_wide() {
+        assert_eq!(
+            parse_errors("map:\nSSSSSSSSSSSS\n"),
+            vec![LevelError::new(2, 12, "rows can be at most 11 cells wide")]
+        );
+    }
+
+    #[test]
+    fn reports_too_many_invader_rows() {
+        assert_eq!(
+            parse_errors("map:\nS\nS\nS\nS\nS\n"),
+            vec![LevelError::new(
+                6,
+                1,
+                "a level can have at most 4 invader rows"
+            )]
+        );
+    }
+
+    #[test]
+    fn reports_a_map_without_invaders() {
+        assert_eq!(
+            parse_errors("map:\n#\n"),
+            vec![LevelError::new(1, 1, "the map has no invaders")]
+        );
+    }
+
+    #[test]
+    fn reports_invaders_among_the_bunkers() {
+        assert_eq!(
+            parse_errors("map:\nS\n#\n.C\n"),
+            vec![LevelError::new(
+                4,
+                2,
+                "invaders must come before the bunkers, in rows of their own"
+            )]
+        );
+    }
+
+    #[test]
+    fn reports_too_many_bunker_rows() {
+        assert_eq!(
+            parse_errors("map:\nS\n#\n#\n#\n#\n"),
+            vec![LevelError::new(
+                6,
+                1,
+                "a level can have at most 3 bunker rows"
+            )]
+        );
+    }
+
+    #[test]
+    fn reports_every_error_at_once() {
+        assert_eq!(parse_errors("speed=fast\nsize=big\nmap:\n#\n").len(), 3);
+    }
+
+    #[test]
+    fn counts_columns_in_characters() {
+        assert_eq!(
+            parse_errors("\u{3000}name=Ünïcödé\n\u{3000}spéed=1\nmap:\nS\n"),
+            vec![LevelError::new(2, 2, "unknown key 'spéed'")]
+        );
+        assert_eq!(
+            parse_errors("\u{3000}march=zigzag\nmap:\nS\n"),
+            vec![LevelError::new(
+                1,
+                8,
+                "march expects random or sweep, got 'zigzag'"
+            )]
+        );
+        assert_eq!(
+            parse_errors("map:\nSé\n"),
+            vec![LevelError::new(2, 2, "unknown cell 'é'")]
+        );
+    }
+}
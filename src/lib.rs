@@ -2,6 +2,42 @@ mod constants;
 
 pub use constants::*;
 
+mod config;
+pub use config::*;
+
+mod telemetry;
+pub use telemetry::*;
+
+mod storage;
+pub use storage::*;
+
+mod timer;
+pub use timer::*;
+
+mod saved_game;
+pub use saved_game::*;
+
+mod audio;
+pub use audio::*;
+
+mod level;
+pub use level::*;
+
+mod campaign;
+pub use campaign::*;
+
+mod daily;
+pub use daily::*;
+
+mod theme;
+pub use theme::*;
+
+mod locale;
+pub use locale::*;
+
+mod terminal_support;
+pub use terminal_support::*;
+
 mod systems;
 pub use systems::*;
 
@@ -11,9 +47,89 @@ pub use components::*;
 mod prefabs;
 pub use prefabs::*;
 
-use thomas::{Dimensions2d, Game, GameOptions, Renderer, Rgb, TerminalRendererOptions};
+use std::{cell::RefCell, rc::Rc};
+
+use thomas::{Dimensions2d, Game, GameOptions, Renderer, TerminalRendererOptions};
+
+pub fn run(config: Config) -> Result<(), String> {
+    let telemetry_writer = if let Some(path) = &config.telemetry_path {
+        Some(TelemetryWriter::open(path).map_err(|e| {
+            format!("Could not open telemetry file {}: {e}", path.display())
+        })?)
+    } else {
+        None
+    };
+
+    let audio_sink = config
+        .audio
+        .open_sink()
+        .map_err(|e| format!("Could not open audio output: {e}"))?;
+    let audio_finish_error = Rc::new(RefCell::new(None));
+
+    let saved_settings =
+        Settings::from_saved(&load_key_values(SETTINGS_FILE_NAME).unwrap_or_default());
+    // Command line options only apply to this session, so they're laid over a copy of the saved settings.
+    let mut settings = saved_settings.clone();
+    let theme = config
+        .theme
+        .clone()
+        .or_else(|| saved_settings.theme.as_deref().and_then(Theme::find_built_in));
+
+    settings.theme = Some(theme.as_ref().unwrap_or(&Theme::classic()).slug());
+
+    if let Some(shake_intensity) = config.shake_intensity {
+        settings.screen_effects.shake_intensity = shake_intensity;
+    }
+
+    if let Some(flash_intensity) = config.flash_intensity {
+        settings.screen_effects.flash_intensity = flash_intensity;
+    }
+
+    let use_sprites = config.use_sprites || settings.use_sprites;
+    let screen_effects = settings.screen_effects;
+    let locale_code = config
+        .locale
+        .as_ref()
+        .map(|locale| locale.code.clone())
+        .or_else(|| settings.language.clone())
+        .or_else(|| Locale::detect().map(|locale| locale.code))
+        .unwrap_or_else(|| String::from("en"));
+    let levels = vec![config.level.clone().unwrap_or_else(Level::classic)];
+    let campaign = config.campaign.clone().unwrap_or_else(Campaign::built_in);
+    let player_name = config
+        .player_name
+        .clone()
+        .unwrap_or_else(detect_player_name);
+    let should_open_editor = config.edit_path.is_some();
+    // A level file given to the editor has to be readable, but it doesn't have to exist yet. The editor's own file
+    // is just started over if it can't be read.
+    let (editor_path, editor_level) = match &config.edit_path {
+        Some(path) if path.exists() => (path.clone(), Some(Level::load(path)?)),
+        Some(path) => (path.clone(), None),
+        None => {
+            let path = data_dir().join(EDITOR_LEVEL_FILE_NAME);
+            let level = Level::load(&path).ok();
+
+            (path, level)
+        }
+    };
+    let mut terminal_support = TerminalSupport::detect();
+
+    if let Some(color_depth) = config.color_depth {
+        terminal_support.color_depth = color_depth;
+    }
+
+    if config.force_ascii {
+        terminal_support.is_unicode = false;
+    }
+
+    let background_color = terminal_support.adapt_color(
+        theme
+            .as_ref()
+            .map(|theme| theme.background)
+            .unwrap_or(Theme::classic().background),
+    );
 
-pub fn run() {
     Game::new(GameOptions {
         max_frame_rate: 60,
         press_escape_to_quit: false,
@@ -21,15 +137,68 @@ pub fn run() {
     // .add_systems_from_generator(EngineAnalysisSystemsGenerator::new(EngineAnalysisOptions {
     //     include_tracking_ui: true,
     // }))
+    .add_systems_from_generator(SessionSystemsGenerator {
+        config,
+        telemetry_writer: Rc::new(RefCell::new(telemetry_writer)),
+    })
+    .add_systems_from_generator(LocalizationSystemsGenerator { locale_code })
+    .add_systems_from_generator(MenuSystemsGenerator {
+        settings,
+        saved_settings,
+    })
+    .add_systems_from_generator(HighScoresSystemsGenerator {})
+    .add_systems_from_generator(SavedGameSystemsGenerator { use_sprites })
+    .add_systems_from_generator(EditorSystemsGenerator {
+        should_open: should_open_editor,
+        path: editor_path,
+        level: editor_level,
+    })
+    .add_systems_from_generator(CampaignSystemsGenerator { campaign })
+    .add_systems_from_generator(TimeAttackSystemsGenerator {})
+    .add_systems_from_generator(DailySystemsGenerator { player_name })
     .add_systems_from_generator(HudSystemsGenerator {})
-    .add_systems_from_generator(PlayerSystemsGenerator {})
+    .add_systems_from_generator(PlayerSystemsGenerator {
+        use_sprites,
+    })
     .add_systems_from_generator(BulletSystemsGenerator {})
-    .add_systems_from_generator(EnemySystemsGenerator {})
+    .add_systems_from_generator(EnemySystemsGenerator {
+        use_sprites,
+        levels,
+    })
+    .add_systems_from_generator(BossSystemsGenerator {})
+    .add_systems_from_generator(ScoringSystemsGenerator {})
+    .add_systems_from_generator(ComboSystemsGenerator {})
+    .add_systems_from_generator(StatsSystemsGenerator {})
+    .add_systems_from_generator(AchievementsSystemsGenerator {})
+    .add_systems_from_generator(BonusSystemsGenerator {
+        rules: BonusRules::default(),
+    })
+    .add_systems_from_generator(EffectsSystemsGenerator {})
+    .add_systems_from_generator(AudioSystemsGenerator {
+        sink: Rc::new(RefCell::new(Some(audio_sink))),
+        finish_error: Rc::clone(&audio_finish_error),
+    })
+    .add_systems_from_generator(SpriteSystemsGenerator {})
+    .add_systems_from_generator(AnimationSystemsGenerator {})
+    .add_systems_from_generator(ParticleSystemsGenerator {})
+    .add_systems_from_generator(ScreenEffectsSystemsGenerator {
+        settings: screen_effects,
+    })
+    .add_systems_from_generator(ThemeSystemsGenerator {
+        theme,
+        terminal_support,
+    })
     .add_systems_from_generator(StarsSystemsGenerator {})
     .start(Renderer::Terminal(TerminalRendererOptions {
         include_default_camera: true,
         screen_resolution: Dimensions2d::new(SCREEN_HEIGHT, SCREEN_WIDTH),
         default_foreground_color: None,
-        default_background_color: Some(Rgb::black()),
+        default_background_color: Some(background_color),
     }));
+
+    if let Some(error) = audio_finish_error.take() {
+        return Err(format!("Could not write audio: {error}"));
+    }
+
+    Ok(())
 }
@@ -0,0 +1,217 @@
+use std::{collections::HashMap, env, fmt::Display};
+
+use crate::parse_key_values;
+
+/// The catalogues that ship with the game, by language code. English comes first, and is what every other locale
+/// falls back to.
+const BUILT_IN_CATALOGUES: [(&str, &str); 2] = [
+    ("en", include_str!("../locales/en.txt")),
+    ("es", include_str!("../locales/es.txt")),
+];
+
+/// Put after every double-width character so each one takes up as many cells as it does columns. Thomas draws
+/// every character of a text in its own cell, so without it the next character would be drawn over the right half
+/// of a wide one, and right-aligned and centred text would be measured short.
+const WIDE_CHAR_FILLER: char = '\u{200B}';
+
+/// The user-facing text of the game in one language.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Locale {
+    /// The language code, like `en`.
+    pub code: String,
+    messages: HashMap<String, String>,
+}
+impl Locale {
+    pub fn built_ins() -> Vec<Self> {
+        let english = parse_key_values(BUILT_IN_CATALOGUES[0].1);
+
+        BUILT_IN_CATALOGUES
+            .iter()
+            .map(|(code, catalogue)| {
+                let mut messages = english.clone();
+                messages.extend(parse_key_values(catalogue));
+
+                Self {
+                    code: String::from(*code),
+                    messages,
+                }
+            })
+            .collect()
+    }
+
+    /// Finds a built-in locale by language code. Region and encoding are ignored, so `es_MX.UTF-8` finds Spanish.
+    pub fn find_built_in(code: &str) -> Option<Self> {
+        let language = code
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or("")
+            .to_lowercase();
+
+        Self::built_ins()
+            .into_iter()
+            .find(|locale| locale.code == language)
+    }
+
+    /// The locale the system asks for through `LC_ALL`, `LC_MESSAGES` or `LANG`, if the game has it.
+    pub fn detect() -> Option<Self> {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Self::find_built_in(&value))
+    }
+
+    /// The name of the language, in that language.
+    pub fn name(&self) -> &str {
+        self.text("locale.name")
+    }
+
+    /// The message with the key. A missing message shows its key, so it's easy to spot.
+    pub fn text<'a>(&'a self, key: &'a str) -> &'a str {
+        self.messages.get(key).map(String::as_str).unwrap_or(key)
+    }
+
+    /// The message with the key, with each `{name}` placeholder replaced by its value.
+    pub fn format(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        args.iter()
+            .fold(String::from(self.text(key)), |message, (name, value)| {
+                message.replace(&format!("{{{name}}}"), &value.to_string())
+            })
+    }
+}
+
+/// How many terminal columns the text takes up.
+pub fn display_width(value: &str) -> usize {
+    value.chars().map(char_width).sum()
+}
+
+/// Pads the text with spaces to fill `width` columns, keeping it centred.
+pub fn center_in_width(value: &str, width: usize) -> String {
+    let padding = width.saturating_sub(display_width(value));
+
+    format!(
+        "{}{value}{}",
+        " ".repeat(padding / 2),
+        " ".repeat(padding - padding / 2)
+    )
+}
+
+/// Pads the end of the text with spaces to fill `width` columns.
+pub fn pad_to_width(value: &str, width: usize) -> String {
+    format!(
+        "{value}{}",
+        " ".repeat(width.saturating_sub(display_width(value)))
+    )
+}
+
+/// Lays the text out for Thomas, which gives every character one cell. Safe to call on text that's already been
+/// laid out.
+pub fn fit_to_cells(value: &str) -> String {
+    let mut fitted = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        fitted.push(c);
+
+        if char_width(c) == 2 && chars.peek() != Some(&WIDE_CHAR_FILLER) {
+            fitted.push(WIDE_CHAR_FILLER);
+        }
+    }
+
+    fitted
+}
+
+/// Covers the East Asian wide and fullwidth blocks and the emoji most terminals draw double-width.
+fn char_width(c: char) -> usize {
+    if c == WIDE_CHAR_FILLER {
+        return 0;
+    }
+
+    match c as u32 {
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F
+        | 0x1F900..=0x1F9FF
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cjk_and_emoji_take_two_columns() {
+        assert_eq!(char_width('a'), 1);
+        assert_eq!(char_width('é'), 1);
+        assert_eq!(char_width('宇'), 2);
+        assert_eq!(char_width('한'), 2);
+        assert_eq!(char_width('Ａ'), 2);
+        assert_eq!(char_width('👾'), 2);
+        assert_eq!(char_width('🎮'), 2);
+        assert_eq!(char_width(WIDE_CHAR_FILLER), 0);
+    }
+
+    #[test]
+    fn display_width_counts_columns_rather_than_chars() {
+        assert_eq!(display_width(""), 0);
+        assert_eq!(display_width("Score"), 5);
+        assert_eq!(display_width("宇宙"), 4);
+        assert_eq!(display_width("👾 x3"), 5);
+        assert_eq!(display_width(&fit_to_cells("宇宙 👾")), 7);
+    }
+
+    #[test]
+    fn fit_to_cells_follows_wide_chars_with_a_filler() {
+        assert_eq!(fit_to_cells("ab"), "ab");
+        assert_eq!(fit_to_cells("宇a"), "宇\u{200B}a");
+        assert_eq!(fit_to_cells("👾👾"), "👾\u{200B}👾\u{200B}");
+    }
+
+    #[test]
+    fn fit_to_cells_gives_one_char_per_column() {
+        for text in ["インベーダー", "우주 침략자", "👾 🎮 x2"] {
+            let fitted = fit_to_cells(text);
+
+            assert_eq!(fitted.chars().count(), display_width(text));
+        }
+    }
+
+    #[test]
+    fn fit_to_cells_leaves_fitted_text_alone() {
+        let fitted = fit_to_cells("宇宙 👾");
+
+        assert_eq!(fit_to_cells(&fitted), fitted);
+    }
+
+    #[test]
+    fn center_in_width_pads_by_columns() {
+        assert_eq!(center_in_width("ab", 6), "  ab  ");
+        assert_eq!(center_in_width("宇宙", 8), "  宇宙  ");
+        assert_eq!(center_in_width("👾", 5), " 👾  ");
+    }
+
+    #[test]
+    fn center_in_width_counts_fillers_as_nothing() {
+        let centered = center_in_width(&fit_to_cells("宇宙"), 8);
+
+        assert_eq!(centered, "  宇\u{200B}宙\u{200B}  ");
+        assert_eq!(display_width(&centered), 8);
+    }
+
+    #[test]
+    fn center_in_width_leaves_text_wider_than_the_width_whole() {
+        assert_eq!(center_in_width("宇宙人", 4), "宇宙人");
+        assert_eq!(center_in_width("👾👾👾", 5), "👾👾👾");
+    }
+}
@@ -1,3 +1,16 @@
+use space_invaders::{Config, USAGE};
+
 fn main() {
-    space_invaders::run();
+    let config = match Config::from_args(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(message) => {
+            eprintln!("{message}\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(message) = space_invaders::run(config) {
+        eprintln!("{message}");
+        std::process::exit(1);
+    }
 }
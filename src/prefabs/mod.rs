@@ -1,14 +1,24 @@
+use std::iter;
+
 use rand::{thread_rng, Rng};
 use thomas::{
-    Component, IntCoords2d, Layer, TerminalCollider, TerminalRenderer, TerminalTransform, Timer, Rgb,
+    Alignment, Component, Coords2d, IntCoords2d, Layer, Lerp, Rgb, TerminalCollider,
+    TerminalRenderer, TerminalTransform, Text, Timer, UiAnchor,
 };
 
 use crate::{
-    Bullet, Enemy, Star, ENEMY_BULLET_COLLISION_LAYER, ENEMY_BULLET_DISPLAY_CHAR,
-    ENEMY_COLLISION_LAYER, ENEMY_DISPLAY_CHAR, PLAYER_BULLET_COLLISION_LAYER,
-    PLAYER_BULLET_DISPLAY_CHAR, SCREEN_WIDTH, ENEMY_COLOR, PLAYER_COLOR,
+    Animation, AnimationFrame, AnimationTiming, Banner, BannerMessage, Boss, BossPart, Bullet,
+    Bunker, Combat, Enemy, EnemyKind, Locale, Particle, ParticleBurst, PendingParticleBurst,
+    Player, PlayerMovement, RunStats, Sprite, SpriteCell, Star, SummaryCard, Theme, ThemeRole,
+    Themed, Toast, WaveBonus, BANNER_ROW_OFFSET, BOSS_COLLISION_LAYER, BOSS_WEAK_SPOT_DISPLAY_CHAR,
+    BUNKER_COLLISION_LAYER, BUNKER_COLOR, BUNKER_DISPLAY_CHARS, ENEMY_ALT_DISPLAY_CHAR,
+    ENEMY_BULLET_COLLISION_LAYER, ENEMY_BULLET_DISPLAY_CHAR, ENEMY_COLLISION_LAYER, ENEMY_COLOR,
+    ENEMY_DISPLAY_CHAR, EXPLOSION_FRAME_INTERVAL_MILLIS, PLAYER_BULLET_COLLISION_LAYER,
+    PLAYER_BULLET_DISPLAY_CHAR, PLAYER_COLLISION_LAYER, PLAYER_COLOR, PLAYER_DISPLAY_CHAR,
+    PLAYER_SHOOT_WAIT_MILLIS, PLAYER_SPRITE_ART, SCREEN_WIDTH,
 };
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum BulletType {
     Player,
     Enemy,
@@ -44,15 +54,103 @@ pub fn make_bullet(
                 BulletType::Enemy => ENEMY_BULLET_COLLISION_LAYER,
             },
         }),
+        Box::new(Themed::new(match bullet_type {
+            BulletType::Player => ThemeRole::PlayerBullet,
+            BulletType::Enemy => ThemeRole::EnemyBullet,
+        })),
     ]
 }
 
-pub fn make_enemy(coords: IntCoords2d) -> Vec<Box<dyn Component>> {
-    vec![
+pub fn make_player(
+    coords: IntCoords2d,
+    lives: u8,
+    is_composite: bool,
+) -> Vec<Vec<Box<dyn Component>>> {
+    let mut player: Vec<Box<dyn Component>> = vec![
+        Box::new(Player { lives }),
+        Box::new(PlayerMovement {
+            move_timer: Timer::start_new(),
+        }),
+        Box::new(Combat {
+            shoot_timer: Timer::start_new(),
+            shoot_wait_millis: PLAYER_SHOOT_WAIT_MILLIS,
+        }),
+    ];
+
+    if is_composite {
+        let sprite = Sprite::new(&PLAYER_SPRITE_ART);
+        let coords = coords + IntCoords2d::new(0, 1 - sprite.height());
+        let cells = make_sprite_cells(
+            &sprite,
+            coords,
+            |_| ThemeRole::Player,
+            PLAYER_COLLISION_LAYER,
+        );
+
+        player.push(Box::new(sprite));
+        player.push(Box::new(TerminalTransform { coords }));
+
+        return iter::once(player).chain(cells).collect();
+    }
+
+    player.append(&mut vec![
+        Box::new(TerminalTransform { coords }),
+        Box::new(TerminalRenderer {
+            display: PLAYER_DISPLAY_CHAR,
+            layer: Layer::base(),
+            foreground_color: Some(PLAYER_COLOR),
+            background_color: None,
+        }),
+        Box::new(TerminalCollider {
+            is_active: true,
+            layer: PLAYER_COLLISION_LAYER,
+        }),
+        Box::new(Themed::new(ThemeRole::Player)),
+    ]);
+
+    vec![player]
+}
+
+pub fn make_enemy(
+    coords: IntCoords2d,
+    kind: EnemyKind,
+    is_composite: bool,
+) -> Vec<Vec<Box<dyn Component>>> {
+    let mut enemy: Vec<Box<dyn Component>> = vec![
         Box::new(Enemy {
             shoot_timer: Timer::start_new(),
+            kind,
+            dive: None,
         }),
         Box::new(TerminalTransform { coords }),
+    ];
+
+    if is_composite {
+        let sprite = Sprite::new(kind.sprite_art());
+        let mut cells = make_sprite_cells(
+            &sprite,
+            coords,
+            |_| ThemeRole::Enemy,
+            ENEMY_COLLISION_LAYER,
+        );
+
+        for ((offset, display), cell) in sprite.cells().into_iter().zip(cells.iter_mut()) {
+            cell.push(Box::new(make_formation_animation(
+                display,
+                sprite.glyph_in_frame(kind.alt_sprite_art(), offset),
+            )));
+        }
+
+        enemy.push(Box::new(sprite));
+
+        return iter::once(enemy).chain(cells).collect();
+    }
+
+    enemy.append(&mut vec![
+        Box::new(make_formation_animation(
+            ENEMY_DISPLAY_CHAR,
+            ENEMY_ALT_DISPLAY_CHAR,
+        )),
         Box::new(TerminalRenderer {
             display: ENEMY_DISPLAY_CHAR,
             layer: Layer::base(),
@@ -63,9 +161,183 @@ pub fn make_enemy(coords: IntCoords2d) -> Vec<Box<dyn Component>> {
             is_active: true,
             layer: ENEMY_COLLISION_LAYER,
         }),
+        Box::new(Themed::new(ThemeRole::Enemy)),
+    ]);
+
+    vec![enemy]
+}
+
+fn make_formation_animation(display: char, alt_display: char) -> Animation {
+    Animation::looping(
+        [display, alt_display]
+            .into_iter()
+            .map(|display| AnimationFrame {
+                display,
+                color: Some(ENEMY_COLOR),
+            })
+            .collect(),
+        AnimationTiming::FormationStep,
+    )
+}
+
+/// Makes one entity per glyph of the sprite, positioned as if the sprite's transform were at `coords`.
+pub fn make_sprite_cells(
+    sprite: &Sprite,
+    coords: IntCoords2d,
+    get_role: impl Fn(char) -> ThemeRole,
+    collision_layer: Layer,
+) -> Vec<Vec<Box<dyn Component>>> {
+    let classic_theme = Theme::classic();
+
+    sprite
+        .cells()
+        .into_iter()
+        .map(|(offset, display)| -> Vec<Box<dyn Component>> {
+            vec![
+                Box::new(SpriteCell {
+                    sprite_id: sprite.id,
+                    offset,
+                }),
+                Box::new(TerminalTransform {
+                    coords: coords + offset,
+                }),
+                Box::new(TerminalRenderer {
+                    display,
+                    layer: Layer::base(),
+                    foreground_color: Some(classic_theme.color(get_role(display))),
+                    background_color: None,
+                }),
+                Box::new(TerminalCollider {
+                    is_active: true,
+                    layer: collision_layer,
+                }),
+                Box::new(Themed::color_only(get_role(display))),
+            ]
+        })
+        .collect()
+}
+
+pub fn make_bunker(coords: IntCoords2d, hp: u8) -> Vec<Box<dyn Component>> {
+    vec![
+        Box::new(Bunker { hp }),
+        Box::new(TerminalTransform { coords }),
+        Box::new(TerminalRenderer {
+            display: BUNKER_DISPLAY_CHARS[hp as usize - 1],
+            layer: Layer::base(),
+            foreground_color: Some(BUNKER_COLOR),
+            background_color: None,
+        }),
+        Box::new(TerminalCollider {
+            is_active: true,
+            layer: BUNKER_COLLISION_LAYER,
+        }),
+        // The glyph shows how worn the block is, so only the colour is themed.
+        Box::new(Themed::color_only(ThemeRole::Bunker)),
+    ]
+}
+
+pub fn make_explosion(coords: IntCoords2d) -> Vec<Box<dyn Component>> {
+    vec![
+        Box::new(Animation::once(
+            [
+                ('*', Rgb::yellow()),
+                ('+', Rgb(255, 128, 0)),
+                ('.', Rgb(128, 128, 128)),
+            ]
+            .into_iter()
+            .map(|(display, color)| AnimationFrame {
+                display,
+                color: Some(color),
+            })
+            .collect(),
+            EXPLOSION_FRAME_INTERVAL_MILLIS,
+            true,
+        )),
+        Box::new(TerminalTransform { coords }),
+        Box::new(TerminalRenderer {
+            display: '*',
+            layer: Layer::base(),
+            foreground_color: Some(Rgb::yellow()),
+            background_color: None,
+        }),
+    ]
+}
+
+pub fn make_particle_burst(coords: IntCoords2d, burst: ParticleBurst) -> Vec<Box<dyn Component>> {
+    vec![Box::new(PendingParticleBurst { coords, burst })]
+}
+
+pub fn make_particle(
+    origin: IntCoords2d,
+    velocity: Coords2d,
+    burst: &ParticleBurst,
+) -> Vec<Box<dyn Component>> {
+    let last_glyph_index = burst.glyphs.len().saturating_sub(1).max(1) as f32;
+
+    vec![
+        Box::new(Particle {
+            origin,
+            velocity,
+            age_timer: Timer::start_new(),
+        }),
+        Box::new(Animation::once(
+            burst
+                .glyphs
+                .iter()
+                .enumerate()
+                .map(|(index, display)| AnimationFrame {
+                    display: *display,
+                    color: Some(Rgb::lerp(
+                        &burst.start_color,
+                        &burst.end_color,
+                        index as f32 / last_glyph_index,
+                    )),
+                })
+                .collect(),
+            burst.lifetime_millis / burst.glyphs.len() as u128,
+            true,
+        )),
+        Box::new(TerminalTransform { coords: origin }),
+        Box::new(TerminalRenderer {
+            display: burst.glyphs[0],
+            layer: Layer::below(&Layer::base()),
+            foreground_color: Some(burst.start_color),
+            background_color: None,
+        }),
     ]
 }
 
+pub fn make_boss(coords: IntCoords2d, boss: Boss) -> Vec<Vec<Box<dyn Component>>> {
+    let sprite = Sprite::new(EnemyKind::Boss.sprite_art());
+
+    let mut cells = make_sprite_cells(
+        &sprite,
+        coords,
+        |display| {
+            if display == BOSS_WEAK_SPOT_DISPLAY_CHAR {
+                ThemeRole::BossWeakSpot
+            } else {
+                ThemeRole::Boss
+            }
+        },
+        BOSS_COLLISION_LAYER,
+    );
+
+    for ((_, display), cell) in sprite.cells().into_iter().zip(cells.iter_mut()) {
+        cell.push(Box::new(BossPart {
+            is_weak_spot: display == BOSS_WEAK_SPOT_DISPLAY_CHAR,
+        }));
+    }
+
+    let boss: Vec<Box<dyn Component>> = vec![
+        Box::new(boss),
+        Box::new(sprite),
+        Box::new(TerminalTransform { coords }),
+    ];
+
+    iter::once(boss).chain(cells).collect()
+}
+
 pub fn make_star(coords: Option<IntCoords2d>) -> Vec<Box<dyn Component>> {
     vec![
         Box::new(Star {
@@ -85,5 +357,147 @@ pub fn make_star(coords: Option<IntCoords2d>) -> Vec<Box<dyn Component>> {
             foreground_color: Some(Rgb::white()),
             background_color: None,
         }),
+        Box::new(Themed::new(ThemeRole::Star)),
+    ]
+}
+
+/// A time in seconds, to the tenth of a second.
+pub fn format_seconds(time_millis: u128) -> String {
+    format!("{}.{}", time_millis / 1000, (time_millis % 1000) / 100)
+}
+
+pub fn make_summary_card(
+    title: &str,
+    stats: &RunStats,
+    time_millis: u128,
+    bonus: Option<&WaveBonus>,
+    locale: &Locale,
+) -> Vec<Vec<Box<dyn Component>>> {
+    let kills = if stats.total_kills() == 0 {
+        String::from(locale.text("summary.no_kills"))
+    } else {
+        EnemyKind::ALL
+            .iter()
+            .filter(|kind| stats.kills_of(**kind) > 0)
+            .map(|kind| {
+                format!(
+                    "{} {}",
+                    locale.text(&format!("enemy.{}", kind.name().to_lowercase())),
+                    stats.kills_of(*kind)
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("  ")
+    };
+
+    let mut lines = vec![
+        (String::from(title), ThemeRole::Highlight),
+        (
+            locale.format("summary.time", &[("seconds", &format_seconds(time_millis))]),
+            ThemeRole::Text,
+        ),
+        (
+            locale.format(
+                "summary.shots",
+                &[
+                    ("shots", &stats.shots_fired),
+                    ("hits", &stats.hits),
+                    ("accuracy", &(stats.accuracy() * 100.0).round()),
+                ],
+            ),
+            ThemeRole::Text,
+        ),
+        (
+            locale.format("summary.kills", &[("kills", &kills)]),
+            ThemeRole::Text,
+        ),
+        (
+            locale.format(
+                "summary.losses",
+                &[
+                    ("lives", &stats.lives_lost),
+                    ("dodged", &stats.bullets_dodged),
+                ],
+            ),
+            ThemeRole::Text,
+        ),
+    ];
+
+    if let Some(bonus) = bonus {
+        lines.push((
+            locale.format(
+                "summary.bonus",
+                &[
+                    ("time", &bonus.time),
+                    ("no_damage", &bonus.no_damage),
+                    ("accuracy", &bonus.accuracy),
+                ],
+            ),
+            ThemeRole::Positive,
+        ));
+    }
+
+    let top_row = -(lines.len() as i64 / 2);
+    let classic_theme = Theme::classic();
+
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(index, (value, role))| -> Vec<Box<dyn Component>> {
+            vec![
+                Box::new(SummaryCard {}),
+                Box::new(Text {
+                    anchor: UiAnchor::Middle,
+                    justification: Alignment::Middle,
+                    offset: IntCoords2d::new(0, top_row + index as i64),
+                    value,
+                    foreground_color: Some(classic_theme.color(role)),
+                    background_color: Some(classic_theme.background),
+                }),
+                Box::new(Themed::new(role)),
+            ]
+        })
+        .collect()
+}
+
+/// Makes a toast. The slot is how many toasts are already showing, so they stack instead of overlapping.
+pub fn make_toast(value: String, role: ThemeRole, slot: usize) -> Vec<Box<dyn Component>> {
+    let classic_theme = Theme::classic();
+
+    vec![
+        Box::new(Toast {
+            timer: Timer::start_new(),
+        }),
+        Box::new(Text {
+            anchor: UiAnchor::MiddleBottom,
+            justification: Alignment::Middle,
+            offset: IntCoords2d::new(0, -3 - slot as i64),
+            value,
+            foreground_color: Some(classic_theme.color(role)),
+            background_color: Some(classic_theme.background),
+        }),
+        Box::new(Themed::new(role)),
+    ]
+}
+
+/// Makes a banner. The message is padded with a space either side, so its background sets it apart from whatever's
+/// behind it.
+pub fn make_banner(message: BannerMessage) -> Vec<Box<dyn Component>> {
+    let classic_theme = Theme::classic();
+
+    vec![
+        Box::new(Banner {
+            timer: Timer::start_new(),
+            duration_millis: message.duration_millis,
+        }),
+        Box::new(Text {
+            anchor: UiAnchor::Middle,
+            justification: Alignment::Middle,
+            offset: IntCoords2d::new(0, BANNER_ROW_OFFSET),
+            value: format!(" {} ", message.value),
+            foreground_color: Some(classic_theme.color(message.role)),
+            background_color: Some(classic_theme.background),
+        }),
+        Box::new(Themed::new(message.role)),
     ]
 }
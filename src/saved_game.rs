@@ -0,0 +1,547 @@
+use std::{collections::HashMap, str::FromStr};
+
+use thomas::IntCoords2d;
+
+use crate::{BulletType, EnemyKind, GameMode, RunStats};
+
+/// Bumped whenever the format changes in a way older saves can't be read with.
+pub const SAVED_GAME_VERSION: u32 = 3;
+
+/// Why a saved game couldn't be read.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SavedGameError {
+    /// Written by a version of the game that saves in a different format.
+    IncompatibleVersion(u32),
+    /// The value for the key is missing or couldn't be read.
+    Damaged(String),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SavedPlayer {
+    pub x: i64,
+    pub lives: u8,
+    /// How much longer the player has to wait before they can shoot again.
+    pub shoot_wait_millis: u128,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SavedEnemy {
+    pub kind: EnemyKind,
+    pub coords: IntCoords2d,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SavedBoss {
+    pub coords: IntCoords2d,
+    pub hp: u64,
+    pub max_hp: u64,
+    pub direction: IntCoords2d,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SavedBullet {
+    pub bullet_type: BulletType,
+    pub coords: IntCoords2d,
+    pub direction: IntCoords2d,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SavedBunker {
+    pub coords: IntCoords2d,
+    pub hp: u8,
+}
+
+/// How far survival's stream of rows had got.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SavedStream {
+    pub rows_spawned: u64,
+    pub seconds_scored: u64,
+}
+
+/// Everything needed to pick a run back up where it was left. Timers aren't saved, so anything waiting on one
+/// starts its wait over, apart from the player's shot cooldown and the clock on the wave.
+#[derive(Clone, Debug)]
+pub struct SavedGame {
+    /// One of `GameMode::RESUMABLE`.
+    pub mode: GameMode,
+    pub score: u64,
+    pub level: u64,
+    /// Whether the run was saved between waves, while the summary was showing.
+    pub is_between_waves: bool,
+    pub seed: u64,
+    /// How far through the seed's random numbers the run had got.
+    pub rng_position: u128,
+    pub player: SavedPlayer,
+    pub enemies: Vec<SavedEnemy>,
+    pub boss: Option<SavedBoss>,
+    pub bullets: Vec<SavedBullet>,
+    pub bunkers: Vec<SavedBunker>,
+    /// The way the formation last stepped, which a sweeping formation keeps going in.
+    pub march_direction: IntCoords2d,
+    pub extra_lives_awarded: u8,
+    pub combo_chain: u64,
+    /// The time spent on the wave so far.
+    pub wave_time_millis: u128,
+    /// Only saved in survival.
+    pub stream: Option<SavedStream>,
+    pub run_stats: RunStats,
+    pub wave_stats: RunStats,
+}
+impl SavedGame {
+    pub fn from_saved(saved: &HashMap<String, String>) -> Result<Self, SavedGameError> {
+        let version: u32 = read(saved, "version")?;
+
+        if version != SAVED_GAME_VERSION {
+            return Err(SavedGameError::IncompatibleVersion(version));
+        }
+
+        let player = saved.get("player").ok_or_else(|| damaged("player"))?;
+        let [x, lives, shoot_wait_millis] = player.split(',').collect::<Vec<&str>>()[..] else {
+            return Err(damaged("player"));
+        };
+
+        let mode = saved.get("mode").ok_or_else(|| damaged("mode"))?;
+
+        Ok(Self {
+            mode: GameMode::RESUMABLE
+                .into_iter()
+                .find(|resumable| resumable.id() == mode)
+                .ok_or_else(|| damaged("mode"))?,
+            score: read(saved, "score")?,
+            level: read(saved, "level")?,
+            is_between_waves: read(saved, "between_waves")?,
+            seed: read(saved, "seed")?,
+            rng_position: read(saved, "rng_position")?,
+            player: SavedPlayer {
+                x: parse(x, "player")?,
+                lives: parse(lives, "player")?,
+                shoot_wait_millis: parse(shoot_wait_millis, "player")?,
+            },
+            enemies: read_entries(saved, "enemy", |key, value| {
+                let (kind, coords) = value.split_once(',').ok_or_else(|| damaged(key))?;
+
+                Ok(SavedEnemy {
+                    kind: EnemyKind::parse(kind).ok_or_else(|| damaged(key))?,
+                    coords: parse_coords(coords, key)?,
+                })
+            })?,
+            boss: read_optional(saved, "boss", |key, value| {
+                let values: Vec<&str> = value.split(',').collect();
+                let [x, y, hp, max_hp, direction] = values[..] else {
+                    return Err(damaged(key));
+                };
+
+                Ok(SavedBoss {
+                    coords: IntCoords2d::new(parse(x, key)?, parse(y, key)?),
+                    hp: parse(hp, key)?,
+                    max_hp: parse(max_hp, key)?,
+                    direction: IntCoords2d::new(parse(direction, key)?, 0),
+                })
+            })?,
+            bullets: read_entries(saved, "bullet", |key, value| {
+                let (bullet_type, values) = value.split_once(',').ok_or_else(|| damaged(key))?;
+                let values: Vec<i64> = parse_list(values, key, 4)?;
+
+                Ok(SavedBullet {
+                    bullet_type: match bullet_type {
+                        "player" => BulletType::Player,
+                        "enemy" => BulletType::Enemy,
+                        _ => return Err(damaged(key)),
+                    },
+                    coords: IntCoords2d::new(values[0], values[1]),
+                    direction: IntCoords2d::new(values[2], values[3]),
+                })
+            })?,
+            bunkers: read_entries(saved, "bunker", |key, value| {
+                let values: Vec<i64> = parse_list(value, key, 3)?;
+
+                Ok(SavedBunker {
+                    coords: IntCoords2d::new(values[0], values[1]),
+                    hp: u8::try_from(values[2])
+                        .ok()
+                        .filter(|hp| *hp > 0)
+                        .ok_or_else(|| damaged(key))?,
+                })
+            })?,
+            march_direction: IntCoords2d::new(read(saved, "march_direction")?, 0),
+            extra_lives_awarded: read(saved, "extra_lives_awarded")?,
+            combo_chain: read(saved, "combo")?,
+            wave_time_millis: read(saved, "wave_time")?,
+            stream: read_optional(saved, "stream", |key, value| {
+                let values: Vec<u64> = parse_list(value, key, 2)?;
+
+                Ok(SavedStream {
+                    rows_spawned: values[0],
+                    seconds_scored: values[1],
+                })
+            })?,
+            run_stats: read_stats(saved, "run")?,
+            wave_stats: read_stats(saved, "wave")?,
+        })
+    }
+
+    pub fn to_saved(&self) -> Vec<(String, String)> {
+        let mut saved = vec![
+            (String::from("version"), SAVED_GAME_VERSION.to_string()),
+            (String::from("mode"), String::from(self.mode.id())),
+            (String::from("score"), self.score.to_string()),
+            (String::from("level"), self.level.to_string()),
+            (
+                String::from("between_waves"),
+                self.is_between_waves.to_string(),
+            ),
+            (String::from("seed"), self.seed.to_string()),
+            (String::from("rng_position"), self.rng_position.to_string()),
+            (
+                String::from("player"),
+                format!(
+                    "{},{},{}",
+                    self.player.x, self.player.lives, self.player.shoot_wait_millis
+                ),
+            ),
+            (
+                String::from("march_direction"),
+                self.march_direction.x().to_string(),
+            ),
+            (
+                String::from("extra_lives_awarded"),
+                self.extra_lives_awarded.to_string(),
+            ),
+            (String::from("combo"), self.combo_chain.to_string()),
+            (String::from("wave_time"), self.wave_time_millis.to_string()),
+        ];
+
+        if let Some(stream) = &self.stream {
+            saved.push((
+                String::from("stream"),
+                format!("{},{}", stream.rows_spawned, stream.seconds_scored),
+            ));
+        }
+
+        for (index, enemy) in self.enemies.iter().enumerate() {
+            saved.push((
+                format!("enemy.{index}"),
+                format!(
+                    "{},{},{}",
+                    enemy.kind.name().to_lowercase(),
+                    enemy.coords.x(),
+                    enemy.coords.y()
+                ),
+            ));
+        }
+
+        if let Some(boss) = &self.boss {
+            saved.push((
+                String::from("boss"),
+                format!(
+                    "{},{},{},{},{}",
+                    boss.coords.x(),
+                    boss.coords.y(),
+                    boss.hp,
+                    boss.max_hp,
+                    boss.direction.x()
+                ),
+            ));
+        }
+
+        for (index, bullet) in self.bullets.iter().enumerate() {
+            saved.push((
+                format!("bullet.{index}"),
+                format!(
+                    "{},{},{},{},{}",
+                    match bullet.bullet_type {
+                        BulletType::Player => "player",
+                        BulletType::Enemy => "enemy",
+                    },
+                    bullet.coords.x(),
+                    bullet.coords.y(),
+                    bullet.direction.x(),
+                    bullet.direction.y()
+                ),
+            ));
+        }
+
+        for (index, bunker) in self.bunkers.iter().enumerate() {
+            saved.push((
+                format!("bunker.{index}"),
+                format!("{},{},{}", bunker.coords.x(), bunker.coords.y(), bunker.hp),
+            ));
+        }
+
+        saved.append(&mut write_stats("run", &self.run_stats));
+        saved.append(&mut write_stats("wave", &self.wave_stats));
+
+        saved
+    }
+}
+
+fn damaged(key: &str) -> SavedGameError {
+    SavedGameError::Damaged(String::from(key))
+}
+
+fn parse<T: FromStr>(value: &str, key: &str) -> Result<T, SavedGameError> {
+    value.parse().map_err(|_| damaged(key))
+}
+
+fn read<T: FromStr>(saved: &HashMap<String, String>, key: &str) -> Result<T, SavedGameError> {
+    parse(saved.get(key).ok_or_else(|| damaged(key))?, key)
+}
+
+fn read_list<T: FromStr>(
+    saved: &HashMap<String, String>,
+    key: &str,
+    length: usize,
+) -> Result<Vec<T>, SavedGameError> {
+    parse_list(saved.get(key).ok_or_else(|| damaged(key))?, key, length)
+}
+
+fn parse_list<T: FromStr>(value: &str, key: &str, length: usize) -> Result<Vec<T>, SavedGameError> {
+    let values = value
+        .split(',')
+        .filter(|value| !value.is_empty())
+        .map(|value| parse(value, key))
+        .collect::<Result<Vec<T>, SavedGameError>>()?;
+
+    if values.len() == length {
+        Ok(values)
+    } else {
+        Err(damaged(key))
+    }
+}
+
+fn parse_coords(value: &str, key: &str) -> Result<IntCoords2d, SavedGameError> {
+    let values: Vec<i64> = parse_list(value, key, 2)?;
+
+    Ok(IntCoords2d::new(values[0], values[1]))
+}
+
+fn read_optional<T>(
+    saved: &HashMap<String, String>,
+    key: &str,
+    parse: impl Fn(&str, &str) -> Result<T, SavedGameError>,
+) -> Result<Option<T>, SavedGameError> {
+    saved.get(key).map(|value| parse(key, value)).transpose()
+}
+
+/// Reads every entry saved as `<prefix>.<index>`.
+fn read_entries<T>(
+    saved: &HashMap<String, String>,
+    prefix: &str,
+    parse: impl Fn(&str, &str) -> Result<T, SavedGameError>,
+) -> Result<Vec<T>, SavedGameError> {
+    saved
+        .iter()
+        .filter(|(key, _)| {
+            key.strip_prefix(prefix)
+                .and_then(|rest| rest.strip_prefix('.'))
+                .is_some_and(|index| index.parse::<usize>().is_ok())
+        })
+        .map(|(key, value)| parse(key, value))
+        .collect()
+}
+
+/// Saved as `<prefix>=<shots>,<hits>,<lives lost>,<bullets dodged>`, with the kills of each kind and the time
+/// taken for each wave under their own keys.
+fn read_stats(saved: &HashMap<String, String>, prefix: &str) -> Result<RunStats, SavedGameError> {
+    let counts: Vec<u64> = read_list(saved, prefix, 4)?;
+    let wave_times_key = format!("{prefix}.wave_times");
+
+    Ok(RunStats {
+        shots_fired: counts[0],
+        hits: counts[1],
+        lives_lost: counts[2],
+        bullets_dodged: counts[3],
+        enemies_killed: EnemyKind::ALL
+            .into_iter()
+            .map(|kind| {
+                let key = format!("{prefix}.kills.{}", kind.name().to_lowercase());
+
+                Ok((kind, saved.get(&key).map_or(Ok(0), |_| read(saved, &key))?))
+            })
+            .collect::<Result<HashMap<EnemyKind, u64>, SavedGameError>>()?,
+        wave_times_millis: saved
+            .get(&wave_times_key)
+            .map(|value| {
+                value
+                    .split(',')
+                    .filter(|value| !value.is_empty())
+                    .map(|value| parse(value, &wave_times_key))
+                    .collect()
+            })
+            .transpose()?
+            .unwrap_or_default(),
+    })
+}
+
+fn write_stats(prefix: &str, stats: &RunStats) -> Vec<(String, String)> {
+    let mut saved = vec![
+        (
+            String::from(prefix),
+            format!(
+                "{},{},{},{}",
+                stats.shots_fired, stats.hits, stats.lives_lost, stats.bullets_dodged
+            ),
+        ),
+        (
+            format!("{prefix}.wave_times"),
+            stats
+                .wave_times_millis
+                .iter()
+                .map(|time| time.to_string())
+                .collect::<Vec<String>>()
+                .join(","),
+        ),
+    ];
+
+    for kind in EnemyKind::ALL {
+        if stats.kills_of(kind) > 0 {
+            saved.push((
+                format!("{prefix}.kills.{}", kind.name().to_lowercase()),
+                stats.kills_of(kind).to_string(),
+            ));
+        }
+    }
+
+    saved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved_game() -> SavedGame {
+        SavedGame {
+            mode: GameMode::Classic,
+            score: 1250,
+            level: 3,
+            is_between_waves: false,
+            seed: 7,
+            rng_position: 42,
+            player: SavedPlayer {
+                x: 20,
+                lives: 2,
+                shoot_wait_millis: 150,
+            },
+            enemies: vec![SavedEnemy {
+                kind: EnemyKind::ALL[0],
+                coords: IntCoords2d::new(4, 6),
+            }],
+            boss: Some(SavedBoss {
+                coords: IntCoords2d::new(10, 3),
+                hp: 12,
+                max_hp: 30,
+                direction: IntCoords2d::new(-1, 0),
+            }),
+            bullets: vec![],
+            bunkers: vec![SavedBunker {
+                coords: IntCoords2d::new(8, 20),
+                hp: 3,
+            }],
+            march_direction: IntCoords2d::new(1, 0),
+            extra_lives_awarded: 1,
+            combo_chain: 4,
+            wave_time_millis: 12500,
+            stream: None,
+            run_stats: RunStats::default(),
+            wave_stats: RunStats::default(),
+        }
+    }
+
+    fn to_map(saved: Vec<(String, String)>) -> HashMap<String, String> {
+        saved.into_iter().collect()
+    }
+
+    #[test]
+    fn reads_back_what_it_saves() {
+        let read = SavedGame::from_saved(&to_map(saved_game().to_saved())).unwrap();
+
+        assert_eq!(read.score, 1250);
+        assert_eq!(read.player, saved_game().player);
+        assert_eq!(read.enemies, saved_game().enemies);
+        assert_eq!(read.boss, saved_game().boss);
+        assert_eq!(read.bunkers, saved_game().bunkers);
+        assert_eq!(read.combo_chain, 4);
+        assert_eq!(read.wave_time_millis, 12500);
+    }
+
+    #[test]
+    fn reads_back_a_survival_run() {
+        let survival = SavedGame {
+            mode: GameMode::Survival,
+            boss: None,
+            stream: Some(SavedStream {
+                rows_spawned: 14,
+                seconds_scored: 95,
+            }),
+            ..saved_game()
+        };
+
+        let read = SavedGame::from_saved(&to_map(survival.to_saved())).unwrap();
+
+        assert_eq!(read.mode, GameMode::Survival);
+        assert_eq!(read.stream, survival.stream);
+    }
+
+    #[test]
+    fn rejects_modes_that_cant_be_resumed() {
+        let mut saved = to_map(saved_game().to_saved());
+
+        saved.insert(String::from("mode"), String::from(GameMode::Daily.id()));
+
+        assert_eq!(
+            SavedGame::from_saved(&saved).unwrap_err(),
+            SavedGameError::Damaged(String::from("mode"))
+        );
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let mut saved = to_map(saved_game().to_saved());
+
+        saved.insert(
+            String::from("version"),
+            (SAVED_GAME_VERSION + 1).to_string(),
+        );
+
+        assert_eq!(
+            SavedGame::from_saved(&saved).unwrap_err(),
+            SavedGameError::IncompatibleVersion(SAVED_GAME_VERSION + 1)
+        );
+    }
+
+    #[test]
+    fn rejects_saves_without_a_version() {
+        let mut saved = to_map(saved_game().to_saved());
+
+        saved.remove("version");
+
+        assert_eq!(
+            SavedGame::from_saved(&saved).unwrap_err(),
+            SavedGameError::Damaged(String::from("version"))
+        );
+    }
+
+    #[test]
+    fn rejects_unreadable_versions() {
+        let mut saved = to_map(saved_game().to_saved());
+
+        saved.insert(String::from("version"), String::from("two"));
+
+        assert_eq!(
+            SavedGame::from_saved(&saved).unwrap_err(),
+            SavedGameError::Damaged(String::from("version"))
+        );
+    }
+
+    #[test]
+    fn rejects_negative_boss_hp() {
+        let mut saved = to_map(saved_game().to_saved());
+
+        saved.insert(String::from("boss"), String::from("10,3,-1,30,-1"));
+
+        assert_eq!(
+            SavedGame::from_saved(&saved).unwrap_err(),
+            SavedGameError::Damaged(String::from("boss"))
+        );
+    }
+}
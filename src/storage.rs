@@ -0,0 +1,73 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::{self, ErrorKind},
+    path::PathBuf,
+};
+
+const DATA_DIR_ENV_VAR: &str = "SPACE_INVADERS_DATA_DIR";
+const DATA_DIR_NAME: &str = ".space_invaders";
+
+/// The directory where the game keeps everything it persists between sessions. Can be overridden with the
+/// `SPACE_INVADERS_DATA_DIR` environment variable.
+pub fn data_dir() -> PathBuf {
+    if let Some(dir) = env::var_os(DATA_DIR_ENV_VAR) {
+        return PathBuf::from(dir);
+    }
+
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(DATA_DIR_NAME))
+        .unwrap_or_else(|| PathBuf::from(DATA_DIR_NAME))
+}
+
+/// Reads a `key=value` file from the data directory. A missing file is treated as empty.
+pub fn load_key_values(file_name: &str) -> io::Result<HashMap<String, String>> {
+    let contents = match fs::read_to_string(data_dir().join(file_name)) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(e),
+    };
+
+    Ok(parse_key_values(&contents))
+}
+
+/// Parses `key=value` lines. Blank lines, lines starting with `#` and lines without an `=` are skipped.
+pub fn parse_key_values(contents: &str) -> HashMap<String, String> {
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (String::from(key.trim()), String::from(value.trim())))
+        .collect()
+}
+
+/// Writes a `key=value` file to the data directory, replacing whatever was there.
+pub fn save_key_values(file_name: &str, entries: &[(String, String)]) -> io::Result<()> {
+    save_file(
+        file_name,
+        &entries
+            .iter()
+            .map(|(key, value)| format!("{key}={value}\n"))
+            .collect::<String>(),
+    )
+}
+
+/// Writes a file to the data directory. The file is written in full before it replaces the old one, so a crash
+/// mid-write can't leave a half-written file behind.
+pub fn save_file(file_name: &str, contents: &str) -> io::Result<()> {
+    let dir = data_dir();
+    fs::create_dir_all(&dir)?;
+
+    let temp_path = dir.join(format!("{file_name}.tmp"));
+    fs::write(&temp_path, contents)?;
+    fs::rename(temp_path, dir.join(file_name))
+}
+
+/// Deletes a file from the data directory. A file that's already gone isn't an error.
+pub fn delete_file(file_name: &str) -> io::Result<()> {
+    match fs::remove_file(data_dir().join(file_name)) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
@@ -11,4 +11,70 @@ mod sys_hud;
 pub use sys_hud::*;
 
 mod sys_stars;
-pub use sys_stars::*;
\ No newline at end of file
+pub use sys_stars::*;
+
+mod sys_combo;
+pub use sys_combo::*;
+
+mod sys_stats;
+pub use sys_stats::*;
+
+mod sys_session;
+pub use sys_session::*;
+
+mod sys_achievements;
+pub use sys_achievements::*;
+
+mod sys_bonuses;
+pub use sys_bonuses::*;
+
+mod sys_boss;
+pub use sys_boss::*;
+
+mod sys_sprites;
+pub use sys_sprites::*;
+
+mod sys_animation;
+pub use sys_animation::*;
+
+mod sys_particles;
+pub use sys_particles::*;
+
+mod sys_screen_effects;
+pub use sys_screen_effects::*;
+
+mod sys_theme;
+pub use sys_theme::*;
+
+mod sys_scoring;
+pub use sys_scoring::*;
+
+mod sys_effects;
+pub use sys_effects::*;
+
+mod sys_audio;
+pub use sys_audio::*;
+
+mod sys_menu;
+pub use sys_menu::*;
+
+mod sys_high_scores;
+pub use sys_high_scores::*;
+
+mod sys_localization;
+pub use sys_localization::*;
+
+mod sys_saved_game;
+pub use sys_saved_game::*;
+
+mod sys_editor;
+pub use sys_editor::*;
+
+mod sys_campaign;
+pub use sys_campaign::*;
+
+mod sys_time_attack;
+pub use sys_time_attack::*;
+
+mod sys_daily;
+pub use sys_daily::*;
@@ -0,0 +1,253 @@
+use thomas::{
+    Alignment, GameCommand, GameCommandsArg, Input, IntCoords2d, Keycode, Query, QueryResultList,
+    System, SystemsGenerator, Text, UiAnchor, EVENT_CLEANUP, EVENT_INIT, EVENT_UPDATE,
+};
+
+use crate::{
+    load_key_values, make_toast, pad_to_width, save_key_values, AchievementDefinition,
+    Achievements, AchievementsScreen, Combo, EnemyKind, GamePhase, GameState, Locale, Localization,
+    Player, Scorekeeper, Stats, Theme, ThemeRole, Themed, Toast, ACHIEVEMENTS,
+    ACHIEVEMENT_COMBO_MASTER, ACHIEVEMENT_DOUBLE_DIGITS, ACHIEVEMENT_EXTERMINATOR,
+    ACHIEVEMENT_FLAWLESS, ACHIEVEMENT_GIANT_SLAYER, ACHIEVEMENT_LAST_STAND, COMBO_MAX_MULTIPLIER,
+    SCREEN_WIDTH,
+};
+
+const ACHIEVEMENTS_FILE_NAME: &str = "achievements.txt";
+
+pub struct AchievementsSystemsGenerator {}
+impl SystemsGenerator for AchievementsSystemsGenerator {
+    fn generate(&self) -> Vec<(&'static str, System)> {
+        vec![
+            (
+                EVENT_INIT,
+                System::new(vec![], |_, commands| {
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(
+                            Achievements::from_saved(
+                                &load_key_values(ACHIEVEMENTS_FILE_NAME).unwrap_or_default(),
+                            ),
+                        )]));
+                }),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<Achievements>(),
+                        Query::new().has::<Stats>(),
+                        Query::new().has::<Scorekeeper>(),
+                        Query::new().has::<Player>(),
+                        Query::new().has::<Combo>(),
+                        Query::new().has::<GameState>(),
+                        Query::new().has::<Toast>(),
+                        Query::new().has::<Localization>(),
+                    ],
+                    evaluate_achievements,
+                ),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has_where::<Input>(|input| input.is_key_down(&Keycode::Tab)),
+                        Query::new().has::<GameState>(),
+                        Query::new().has::<Achievements>(),
+                        Query::new().has::<AchievementsScreen>(),
+                        Query::new().has::<Localization>(),
+                        Query::new().has::<Stats>(),
+                        Query::new().has::<Combo>(),
+                    ],
+                    toggle_achievements_screen,
+                ),
+            ),
+            (
+                EVENT_CLEANUP,
+                System::new(vec![Query::new().has::<Achievements>()], |results, _| {
+                    if let [achievements_results, ..] = &results[..] {
+                        save_achievements(&mut achievements_results.get_only_mut::<Achievements>());
+                    }
+                }),
+            ),
+        ]
+    }
+}
+
+fn evaluate_achievements(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [achievements_results, stats_results, scorekeeper_results, player_results, combo_results, game_state_results, toast_results, localization_results, ..] =
+        &results[..]
+    {
+        let mut achievements = achievements_results.get_only_mut::<Achievements>();
+        let stats = stats_results.get_only::<Stats>();
+        let scorekeeper = scorekeeper_results.get_only::<Scorekeeper>();
+        let player = player_results.get_only::<Player>();
+        let combo = combo_results.get_only::<Combo>();
+        let game_state = game_state_results.get_only::<GameState>();
+        let localization = localization_results.get_only::<Localization>();
+
+        let run_kills = stats.run.total_kills();
+        if run_kills < achievements.counted_run_kills {
+            achievements.counted_run_kills = 0;
+        }
+        let lifetime_kills = achievements.progress_of(ACHIEVEMENT_EXTERMINATOR) + run_kills
+            - achievements.counted_run_kills;
+        achievements.counted_run_kills = run_kills;
+
+        let mut progress_updates = vec![
+            (ACHIEVEMENT_DOUBLE_DIGITS, scorekeeper.level),
+            (
+                ACHIEVEMENT_GIANT_SLAYER,
+                stats.run.kills_of(EnemyKind::Boss),
+            ),
+            (ACHIEVEMENT_EXTERMINATOR, lifetime_kills),
+        ];
+
+        if combo.multiplier() == COMBO_MAX_MULTIPLIER {
+            progress_updates.push((ACHIEVEMENT_COMBO_MASTER, 1));
+        }
+
+        if game_state.phase == GamePhase::WaveSummary {
+            if stats.wave.shots_fired > 0 && stats.wave.hits == stats.wave.shots_fired {
+                progress_updates.push((ACHIEVEMENT_FLAWLESS, 1));
+            }
+
+            if player.lives == 1 {
+                progress_updates.push((ACHIEVEMENT_LAST_STAND, 1));
+            }
+        }
+
+        let newly_unlocked: Vec<&AchievementDefinition> = progress_updates
+            .into_iter()
+            .filter_map(|(id, progress)| achievements.raise_progress(id, progress))
+            .collect();
+
+        for (index, achievement) in newly_unlocked.iter().enumerate() {
+            commands.borrow_mut().issue(GameCommand::AddEntity(make_toast(
+                localization.current().format(
+                    "toast.achievement_unlocked",
+                    &[("name", &achievement.name(localization.current()))],
+                ),
+                ThemeRole::Highlight,
+                toast_results.len() + index,
+            )));
+        }
+
+        if !newly_unlocked.is_empty() || !game_state.is_playing() {
+            save_achievements(&mut achievements);
+        }
+    }
+}
+
+fn toggle_achievements_screen(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [tab_pressed_results, game_state_results, achievements_results, screen_results, localization_results, stats_results, combo_results, ..] =
+        &results[..]
+    {
+        if tab_pressed_results.is_empty() {
+            return;
+        }
+
+        let mut game_state = game_state_results.get_only_mut::<GameState>();
+        let mut stats = stats_results.get_only_mut::<Stats>();
+        let mut combo = combo_results.get_only_mut::<Combo>();
+
+        match game_state.phase {
+            GamePhase::Playing => {
+                let achievements = achievements_results.get_only::<Achievements>();
+                let localization = localization_results.get_only::<Localization>();
+
+                let classic_theme = Theme::classic();
+
+                for (row, (value, role)) in
+                    get_achievements_screen_lines(&achievements, localization.current())
+                        .into_iter()
+                        .enumerate()
+                {
+                    commands.borrow_mut().issue(GameCommand::AddEntity(vec![
+                        Box::new(AchievementsScreen {}),
+                        Box::new(Text {
+                            anchor: UiAnchor::TopLeft,
+                            justification: Alignment::Left,
+                            offset: IntCoords2d::new(0, row as i64),
+                            value: pad_to_width(&value, SCREEN_WIDTH as usize),
+                            foreground_color: Some(classic_theme.color(role)),
+                            background_color: Some(classic_theme.background),
+                        }),
+                        Box::new(Themed::new(role)),
+                    ]));
+                }
+
+                // Looking at the screen doesn't count against the wave's time or let the combo run out.
+                stats.wave_timer.pause();
+                combo.window_timer.pause();
+
+                game_state.enter(GamePhase::Achievements);
+            }
+            GamePhase::Achievements => {
+                for screen_result in screen_results {
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::DestroyEntity(*screen_result.entity()));
+                }
+
+                stats.wave_timer.resume();
+                combo.window_timer.resume();
+
+                game_state.enter(GamePhase::Playing);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn get_achievements_screen_lines(
+    achievements: &Achievements,
+    locale: &Locale,
+) -> Vec<(String, ThemeRole)> {
+    let mut lines = vec![
+        (
+            format!(
+                " {}",
+                locale.format(
+                    "achievements.title",
+                    &[
+                        ("unlocked", &achievements.unlocked_count()),
+                        ("total", &ACHIEVEMENTS.len())
+                    ],
+                )
+            ),
+            ThemeRole::Text,
+        ),
+        (String::from(""), ThemeRole::Text),
+    ];
+
+    for achievement in &ACHIEVEMENTS {
+        let is_unlocked = achievements.is_unlocked(achievement);
+        let role = if is_unlocked {
+            ThemeRole::Highlight
+        } else {
+            ThemeRole::Muted
+        };
+
+        lines.push((
+            format!(
+                " [{}] {}{:>5}/{}",
+                if is_unlocked { 'x' } else { ' ' },
+                pad_to_width(&achievement.name(locale), 32),
+                achievements.progress_of(achievement.id),
+                achievement.goal
+            ),
+            role,
+        ));
+        lines.push((format!("     {}", achievement.description(locale)), role));
+    }
+
+    lines
+}
+
+fn save_achievements(achievements: &mut Achievements) {
+    if achievements.has_unsaved_progress
+        && save_key_values(ACHIEVEMENTS_FILE_NAME, &achievements.to_saved()).is_ok()
+    {
+        achievements.has_unsaved_progress = false;
+    }
+}
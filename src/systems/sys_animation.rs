@@ -0,0 +1,57 @@
+use thomas::{
+    Component, GameCommand, GameCommandsArg, Query, QueryResultList, System, SystemsGenerator,
+    TerminalRenderer, EVENT_UPDATE,
+};
+
+use crate::{Animation, AnimationTiming};
+
+pub struct AnimationSystemsGenerator {}
+impl SystemsGenerator for AnimationSystemsGenerator {
+    fn generate(&self) -> Vec<(&'static str, System)> {
+        vec![(
+            EVENT_UPDATE,
+            System::new(
+                vec![Query::new().has::<Animation>().has::<TerminalRenderer>()],
+                animate,
+            ),
+        )]
+    }
+}
+
+fn animate(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [animation_results, ..] = &results[..] {
+        for animation_result in animation_results {
+            let mut animation = animation_result.components().get_mut::<Animation>();
+            let mut renderer = animation_result.components().get_mut::<TerminalRenderer>();
+
+            if animation.is_finished() {
+                commands
+                    .borrow_mut()
+                    .issue(if animation.destroy_when_finished {
+                        GameCommand::DestroyEntity(*animation_result.entity())
+                    } else {
+                        GameCommand::RemoveComponentFromEntity(
+                            *animation_result.entity(),
+                            Animation::name(),
+                        )
+                    });
+
+                continue;
+            }
+
+            if let AnimationTiming::Interval(frame_interval_millis) = animation.timing {
+                if animation.frame_timer.elapsed_millis() >= frame_interval_millis
+                    && (animation.is_looping
+                        || animation.current_frame + 1 < animation.frames.len())
+                {
+                    animation.advance();
+                }
+            }
+
+            let frame = animation.frame();
+
+            renderer.display = frame.display;
+            renderer.foreground_color = frame.color;
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::{cell::RefCell, io, rc::Rc};
+
+use thomas::{
+    GameCommand, GameCommandsArg, Query, QueryResultList, System, SystemsGenerator,
+    EVENT_AFTER_UPDATE, EVENT_CLEANUP, EVENT_INIT, EVENT_UPDATE,
+};
+
+use crate::{
+    Audio, AudioSink, Boss, BulletType, GameEvent, GameEvents, GameState, SilentSink, SoundCue,
+};
+
+/// How often the boss saucer's drone repeats while it's on screen.
+const UFO_DRONE_INTERVAL_MILLIS: u128 = 150;
+
+/// Turns gameplay events into sound cues and sends them to the sink chosen at launch.
+pub struct AudioSystemsGenerator {
+    pub sink: Rc<RefCell<Option<Box<dyn AudioSink>>>>,
+    /// Where a sink that fails to write itself out at shutdown leaves the error, since the terminal still belongs to
+    /// the renderer then.
+    pub finish_error: Rc<RefCell<Option<io::Error>>>,
+}
+impl SystemsGenerator for AudioSystemsGenerator {
+    fn generate(&self) -> Vec<(&'static str, System)> {
+        let sink = Rc::clone(&self.sink);
+        let finish_error = Rc::clone(&self.finish_error);
+
+        vec![
+            (
+                EVENT_INIT,
+                System::new(vec![], move |_, commands| {
+                    let sink = sink
+                        .borrow_mut()
+                        .take()
+                        .unwrap_or_else(|| Box::new(SilentSink {}));
+
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(Audio::new(sink))]));
+                }),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<Audio>(),
+                        Query::new().has::<Boss>(),
+                        Query::new().has_where::<GameState>(|state| state.is_playing()),
+                    ],
+                    play_ufo_drone,
+                ),
+            ),
+            (
+                EVENT_AFTER_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<GameEvents>(),
+                        Query::new().has::<Audio>(),
+                    ],
+                    play_event_cues,
+                ),
+            ),
+            (
+                EVENT_CLEANUP,
+                System::new(vec![Query::new().has::<Audio>()], move |results, _| {
+                    if let [audio_results, ..] = &results[..] {
+                        if let Err(error) = audio_results.get_only_mut::<Audio>().sink.finish() {
+                            *finish_error.borrow_mut() = Some(error);
+                        }
+                    }
+                }),
+            ),
+        ]
+    }
+}
+
+fn play_ufo_drone(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [audio_results, boss_results, playing_state_results, ..] = &results[..] {
+        let mut audio = audio_results.get_only_mut::<Audio>();
+
+        if boss_results.is_empty() || playing_state_results.is_empty() {
+            audio.drone_timer.restart();
+            return;
+        }
+
+        if audio.drone_timer.elapsed_millis() >= UFO_DRONE_INTERVAL_MILLIS {
+            audio.play(SoundCue::UfoDrone);
+            audio.drone_timer.restart();
+        }
+    }
+}
+
+fn play_event_cues(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [game_events_results, audio_results, ..] = &results[..] {
+        let mut audio = audio_results.get_only_mut::<Audio>();
+
+        for event in game_events_results.get_only::<GameEvents>().iter() {
+            match event {
+                GameEvent::ShotFired {
+                    bullet_type: BulletType::Player,
+                    ..
+                } => audio.play(SoundCue::Shoot),
+                GameEvent::EnemyDestroyed { .. } => audio.play(SoundCue::EnemyKilled),
+                GameEvent::PlayerHit { .. } => audio.play(SoundCue::PlayerHit),
+                GameEvent::FormationStepped => audio.play_march_note(),
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use thomas::{
+    GameCommand, GameCommandsArg, Query, QueryResultList, System, SystemsGenerator, EVENT_INIT,
+    EVENT_UPDATE,
+};
+
+use crate::{
+    make_toast, BonusRules, Bonuses, Localization, Player, Scorekeeper, ThemeRole, Toast,
+    PLAYER_MAX_LIVES,
+};
+
+pub struct BonusSystemsGenerator {
+    pub rules: BonusRules,
+}
+impl SystemsGenerator for BonusSystemsGenerator {
+    fn generate(&self) -> Vec<(&'static str, System)> {
+        let rules = self.rules;
+
+        vec![
+            (
+                EVENT_INIT,
+                System::new(vec![], move |_, commands| {
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(Bonuses {
+                            rules,
+                            extra_lives_awarded: 0,
+                        })]));
+                }),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<Bonuses>(),
+                        Query::new().has::<Scorekeeper>(),
+                        Query::new().has::<Player>(),
+                        Query::new().has::<Toast>(),
+                        Query::new().has::<Localization>(),
+                    ],
+                    award_extra_lives,
+                ),
+            ),
+        ]
+    }
+}
+
+fn award_extra_lives(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [bonuses_results, scorekeeper_results, player_results, toast_results, localization_results, ..] =
+        &results[..]
+    {
+        let mut bonuses = bonuses_results.get_only_mut::<Bonuses>();
+        let scorekeeper = scorekeeper_results.get_only::<Scorekeeper>();
+        let mut player = player_results.get_only_mut::<Player>();
+
+        while let Some(threshold) = bonuses.next_extra_life_threshold() {
+            // A full set of lives holds the extra life back until there's room for it.
+            if scorekeeper.score < threshold
+                || player.lives == 0
+                || player.lives >= PLAYER_MAX_LIVES
+            {
+                break;
+            }
+
+            bonuses.extra_lives_awarded += 1;
+            player.lives += 1;
+
+            commands.borrow_mut().issue(GameCommand::AddEntity(make_toast(
+                String::from(
+                    localization_results
+                        .get_only::<Localization>()
+                        .current()
+                        .text("toast.extra_life"),
+                ),
+                ThemeRole::Positive,
+                toast_results.len(),
+            )));
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use thomas::{
+    GameCommand, GameCommandsArg, IntCoords2d, Query, QueryResultList, System, SystemsGenerator,
+    TerminalCollision, TerminalTransform, EVENT_UPDATE,
+};
+
+use crate::{
+    make_bullet, Boss, BossPart, BossPhase, BulletType, DestroyedBy, EnemyKind, GameEvent,
+    GameEvents, GameState, Player, Sprite, BOSS_COLLISION_LAYER, BOSS_WEAK_SPOT_DAMAGE,
+    BOSS_WEAK_SPOT_DISPLAY_CHAR, PLAYER_BULLET_COLLISION_LAYER, SCREEN_WIDTH,
+};
+
+const BOSS_MOVE_WAIT_TIME: u128 = 400;
+const BOSS_FRENZY_MOVE_WAIT_TIME: u128 = 200;
+
+const BOSS_VOLLEY_WAIT_TIME: u128 = 900;
+const BOSS_SPREAD_WAIT_TIME: u128 = 1200;
+const BOSS_FRENZY_WAIT_TIME: u128 = 350;
+
+pub struct BossSystemsGenerator {}
+impl SystemsGenerator for BossSystemsGenerator {
+    fn generate(&self) -> Vec<(&'static str, System)> {
+        vec![
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new()
+                            .has::<Boss>()
+                            .has::<Sprite>()
+                            .has::<TerminalTransform>(),
+                        Query::new().has_where::<GameState>(|state| state.is_playing()),
+                    ],
+                    move_boss,
+                ),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new()
+                            .has::<Boss>()
+                            .has::<Sprite>()
+                            .has::<TerminalTransform>(),
+                        Query::new().has::<Player>().has::<TerminalTransform>(),
+                        Query::new().has_where::<GameState>(|state| state.is_playing()),
+                    ],
+                    attack,
+                ),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has_where::<TerminalCollision>(|collision| {
+                            let has_player_bullet = collision.bodies.iter().any(|(_, collider)| {
+                                collider.layer == PLAYER_BULLET_COLLISION_LAYER
+                            });
+                            let has_boss = collision
+                                .bodies
+                                .iter()
+                                .any(|(_, collider)| collider.layer == BOSS_COLLISION_LAYER);
+
+                            has_player_bullet && has_boss
+                        }),
+                        Query::new()
+                            .has::<Boss>()
+                            .has::<Sprite>()
+                            .has::<TerminalTransform>(),
+                        Query::new().has::<BossPart>(),
+                        Query::new().has::<GameEvents>(),
+                    ],
+                    player_bullet_hits_boss_collisions,
+                ),
+            ),
+        ]
+    }
+}
+
+fn move_boss(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [boss_results, playing_state_results, ..] = &results[..] {
+        if playing_state_results.is_empty() || boss_results.is_empty() {
+            return;
+        }
+
+        let mut boss = boss_results.get_only_mut::<Boss>();
+        let mut transform = boss_results.get_only_mut::<TerminalTransform>();
+        let half_width = boss_results.get_only::<Sprite>().width() / 2;
+
+        let move_wait_time = match boss.phase() {
+            BossPhase::Frenzy => BOSS_FRENZY_MOVE_WAIT_TIME,
+            _ => BOSS_MOVE_WAIT_TIME,
+        };
+
+        if boss.move_timer.elapsed_millis() >= move_wait_time {
+            if transform.coords.x() - half_width <= 1 {
+                boss.direction = IntCoords2d::right();
+            } else if transform.coords.x() + half_width >= SCREEN_WIDTH as i64 - 2 {
+                boss.direction = IntCoords2d::left();
+            }
+
+            transform.coords += boss.direction;
+
+            boss.move_timer.restart();
+        }
+    }
+}
+
+fn attack(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [boss_results, player_results, playing_state_results, ..] = &results[..] {
+        if playing_state_results.is_empty() || boss_results.is_empty() {
+            return;
+        }
+
+        let mut boss = boss_results.get_only_mut::<Boss>();
+        let sprite = boss_results.get_only::<Sprite>();
+        let boss_coords = boss_results.get_only::<TerminalTransform>().coords;
+        let player_coords = player_results.get_only::<TerminalTransform>().coords;
+
+        let muzzle_row = boss_coords.y() + sprite.height();
+        let half_width = sprite.width() / 2;
+
+        let (attack_wait_time, shots) = match boss.phase() {
+            BossPhase::Volley => (
+                BOSS_VOLLEY_WAIT_TIME,
+                sprite
+                    .cells()
+                    .into_iter()
+                    .filter(|(_, display)| *display == BOSS_WEAK_SPOT_DISPLAY_CHAR)
+                    .map(|(offset, _)| {
+                        (
+                            IntCoords2d::new(boss_coords.x() + offset.x(), muzzle_row),
+                            IntCoords2d::up(),
+                        )
+                    })
+                    .collect::<Vec<(IntCoords2d, IntCoords2d)>>(),
+            ),
+            BossPhase::Spread => (
+                BOSS_SPREAD_WAIT_TIME,
+                [-1, 0, 1]
+                    .iter()
+                    .map(|x_direction| {
+                        (
+                            IntCoords2d::new(boss_coords.x() + x_direction, muzzle_row),
+                            IntCoords2d::new(*x_direction, 1),
+                        )
+                    })
+                    .collect(),
+            ),
+            BossPhase::Frenzy => (
+                BOSS_FRENZY_WAIT_TIME,
+                vec![(
+                    IntCoords2d::new(
+                        player_coords
+                            .x()
+                            .clamp(boss_coords.x() - half_width, boss_coords.x() + half_width),
+                        muzzle_row,
+                    ),
+                    IntCoords2d::up(),
+                )],
+            ),
+        };
+
+        if boss.attack_timer.elapsed_millis() >= attack_wait_time {
+            for (start_pos, direction) in shots {
+                commands
+                    .borrow_mut()
+                    .issue(GameCommand::AddEntity(make_bullet(
+                        start_pos,
+                        direction,
+                        BulletType::Enemy,
+                    )));
+            }
+
+            boss.attack_timer.restart();
+        }
+    }
+}
+
+fn player_bullet_hits_boss_collisions(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [bullet_collision_results, boss_results, boss_part_results, game_events_results, ..] =
+        &results[..]
+    {
+        if bullet_collision_results.is_empty() || boss_results.is_empty() {
+            return;
+        }
+
+        let mut boss = boss_results.get_only_mut::<Boss>();
+        let boss_coords = boss_results.get_only::<TerminalTransform>().coords;
+        let mut game_events = game_events_results.get_only_mut::<GameEvents>();
+
+        for bullet_collision in bullet_collision_results {
+            if boss.is_defeated() {
+                break;
+            }
+
+            let collision = bullet_collision.components().get::<TerminalCollision>();
+
+            let boss_part_entity = collision
+                .bodies
+                .iter()
+                .find(|(_, collider)| collider.layer == BOSS_COLLISION_LAYER)
+                .unwrap()
+                .0;
+
+            let is_weak_spot = boss_part_results
+                .iter()
+                .find(|boss_part_result| *boss_part_result.entity() == boss_part_entity)
+                .map(|boss_part_result| {
+                    boss_part_result.components().get::<BossPart>().is_weak_spot
+                })
+                .unwrap_or(false);
+
+            boss.take_damage(if is_weak_spot {
+                BOSS_WEAK_SPOT_DAMAGE
+            } else {
+                1
+            });
+
+            if !boss.is_defeated() {
+                game_events.emit(GameEvent::EnemyHit {
+                    kind: EnemyKind::Boss,
+                    coords: boss_coords,
+                });
+
+                continue;
+            }
+
+            game_events.emit(GameEvent::EnemyDestroyed {
+                kind: EnemyKind::Boss,
+                coords: boss_coords,
+                by: DestroyedBy::PlayerBullet,
+            });
+
+            commands
+                .borrow_mut()
+                .issue(GameCommand::DestroyEntity(*boss_results[0].entity()));
+        }
+    }
+}
@@ -1,11 +1,13 @@
 use thomas::{
     GameCommand, GameCommandsArg, Query, QueryResultList, System, SystemsGenerator,
-    TerminalCollision, TerminalTransform, EVENT_UPDATE,
+    TerminalCollider, TerminalCollision, TerminalRenderer, TerminalTransform, EVENT_UPDATE,
 };
 
 use crate::{
-    Bullet, Player, Scorekeeper, ENEMY_BULLET_COLLISION_LAYER, ENEMY_COLLISION_LAYER,
-    ENEMY_POINT_VALUE, PLAYER_BULLET_COLLISION_LAYER, PLAYER_COLLISION_LAYER, SCREEN_HEIGHT,
+    get_sprite_owner, make_particle_burst, Bullet, Bunker, Combo, DestroyedBy, Enemy, GameEvent,
+    GameEvents, GameState, ParticleBurst, Player, Sprite, SpriteCell, Stats,
+    BUNKER_COLLISION_LAYER, BUNKER_DISPLAY_CHARS, ENEMY_BULLET_COLLISION_LAYER,
+    ENEMY_COLLISION_LAYER, PLAYER_BULLET_COLLISION_LAYER, PLAYER_COLLISION_LAYER, SCREEN_HEIGHT,
 };
 
 const BULLET_MOVE_WAIT_TIME_MILLIS: u128 = 50;
@@ -17,7 +19,15 @@ impl SystemsGenerator for BulletSystemsGenerator {
             (
                 EVENT_UPDATE,
                 System::new(
-                    vec![Query::new().has::<Bullet>().has::<TerminalTransform>()],
+                    vec![
+                        Query::new()
+                            .has::<Bullet>()
+                            .has::<TerminalTransform>()
+                            .has::<TerminalCollider>(),
+                        Query::new().has::<Combo>(),
+                        Query::new().has::<Stats>(),
+                        Query::new().has_where::<GameState>(|state| state.is_playing()),
+                    ],
                     move_bullets,
                 ),
             ),
@@ -36,7 +46,10 @@ impl SystemsGenerator for BulletSystemsGenerator {
 
                             has_player_bullet && has_enemy
                         }),
-                        Query::new().has::<Scorekeeper>(),
+                        Query::new().has::<Enemy>().has::<TerminalTransform>(),
+                        Query::new().has::<Sprite>(),
+                        Query::new().has::<SpriteCell>(),
+                        Query::new().has::<GameEvents>(),
                     ],
                     player_bullet_hits_enemy_collisions,
                 ),
@@ -56,7 +69,8 @@ impl SystemsGenerator for BulletSystemsGenerator {
 
                             has_enemy_bullet && has_player
                         }),
-                        Query::new().has::<Player>(),
+                        Query::new().has::<Player>().has::<TerminalTransform>(),
+                        Query::new().has::<GameEvents>(),
                     ],
                     enemy_bullet_hits_player_collisions,
                 ),
@@ -64,19 +78,49 @@ impl SystemsGenerator for BulletSystemsGenerator {
             (
                 EVENT_UPDATE,
                 System::new(
-                    vec![Query::new().has_where::<TerminalCollision>(|collision| {
-                        let are_bullets_colliding =
-                            collision.bodies.iter().any(|(_, collider)| {
+                    vec![
+                        Query::new().has_where::<TerminalCollision>(|collision| {
+                            let has_bullet = collision.bodies.iter().any(|(_, collider)| {
                                 collider.layer == ENEMY_BULLET_COLLISION_LAYER
-                            }) && collision.bodies.iter().any(|(_, collider)| {
-                                collider.layer == PLAYER_BULLET_COLLISION_LAYER
+                                    || collider.layer == PLAYER_BULLET_COLLISION_LAYER
                             });
+                            let has_bunker = collision
+                                .bodies
+                                .iter()
+                                .any(|(_, collider)| collider.layer == BUNKER_COLLISION_LAYER);
 
-                        !are_bullets_colliding && collision.bodies.iter().any(|(_, collider)| {
-                            collider.layer == ENEMY_BULLET_COLLISION_LAYER
-                                || collider.layer == PLAYER_BULLET_COLLISION_LAYER
-                        })
-                    })],
+                            has_bullet && has_bunker
+                        }),
+                        Query::new()
+                            .has::<Bunker>()
+                            .has::<TerminalTransform>()
+                            .has::<TerminalRenderer>(),
+                        Query::new().has::<Combo>(),
+                        Query::new().has::<GameEvents>(),
+                    ],
+                    bullet_hits_bunker_collisions,
+                ),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has_where::<TerminalCollision>(|collision| {
+                            let are_bullets_colliding =
+                                collision.bodies.iter().any(|(_, collider)| {
+                                    collider.layer == ENEMY_BULLET_COLLISION_LAYER
+                                }) && collision.bodies.iter().any(|(_, collider)| {
+                                    collider.layer == PLAYER_BULLET_COLLISION_LAYER
+                                });
+
+                            !are_bullets_colliding
+                                && collision.bodies.iter().any(|(_, collider)| {
+                                    collider.layer == ENEMY_BULLET_COLLISION_LAYER
+                                        || collider.layer == PLAYER_BULLET_COLLISION_LAYER
+                                })
+                        }),
+                        Query::new().has::<Bullet>().has::<TerminalTransform>(),
+                    ],
                     cleanup_bullets_on_collision,
                 ),
             ),
@@ -85,15 +129,26 @@ impl SystemsGenerator for BulletSystemsGenerator {
 }
 
 fn move_bullets(results: Vec<QueryResultList>, commands: GameCommandsArg) {
-    if let [bullets_query, ..] = &results[..] {
+    if let [bullets_query, combo_results, stats_results, playing_state_results, ..] = &results[..] {
+        if playing_state_results.is_empty() {
+            return;
+        }
+
         for bullet_result in bullets_query {
             let mut bullet = bullet_result.components().get_mut::<Bullet>();
             let mut transform = bullet_result.components().get_mut::<TerminalTransform>();
+            let collider = bullet_result.components().get::<TerminalCollider>();
 
             if transform.coords.y() < 0 || transform.coords.y() > SCREEN_HEIGHT as i64 {
                 commands
                     .borrow_mut()
                     .issue(GameCommand::DestroyEntity(*bullet_result.entity()));
+
+                if collider.layer == PLAYER_BULLET_COLLISION_LAYER {
+                    combo_results.get_only_mut::<Combo>().break_chain();
+                } else if collider.layer == ENEMY_BULLET_COLLISION_LAYER {
+                    stats_results.get_only_mut::<Stats>().record_dodge();
+                }
             }
 
             if bullet.move_timer.elapsed_millis() >= BULLET_MOVE_WAIT_TIME_MILLIS {
@@ -106,57 +161,166 @@ fn move_bullets(results: Vec<QueryResultList>, commands: GameCommandsArg) {
 }
 
 fn player_bullet_hits_enemy_collisions(results: Vec<QueryResultList>, commands: GameCommandsArg) {
-    if let [bullet_collision_results, scorekeeper_results, ..] = &results[..] {
-        let mut scorekeeper = scorekeeper_results[0].components().get_mut::<Scorekeeper>();
+    if let [bullet_collision_results, enemy_results, sprite_results, sprite_cell_results, game_events_results, ..] =
+        &results[..]
+    {
+        let mut game_events = game_events_results.get_only_mut::<GameEvents>();
+        let mut destroyed_enemies = vec![];
 
         for bullet_collision in bullet_collision_results {
             let collision = bullet_collision.components().get::<TerminalCollision>();
 
-            commands.borrow_mut().issue(GameCommand::DestroyEntity(
+            let enemy_entity = get_sprite_owner(
                 collision
                     .bodies
                     .iter()
                     .find(|(_, collider)| collider.layer == ENEMY_COLLISION_LAYER)
                     .unwrap()
                     .0,
-            ));
+                sprite_results,
+                sprite_cell_results,
+            );
 
-            scorekeeper.score += ENEMY_POINT_VALUE;
-            if scorekeeper.score > scorekeeper.high_score {
-                scorekeeper.high_score = scorekeeper.score;
+            // Two bullets can hit the same invader in the same frame, but it only dies once.
+            if destroyed_enemies.contains(&enemy_entity) {
+                continue;
+            }
+
+            destroyed_enemies.push(enemy_entity);
+
+            commands
+                .borrow_mut()
+                .issue(GameCommand::DestroyEntity(enemy_entity));
+
+            if let Some(enemy_result) = enemy_results
+                .iter()
+                .find(|enemy_result| *enemy_result.entity() == enemy_entity)
+            {
+                game_events.emit(GameEvent::EnemyDestroyed {
+                    kind: enemy_result.components().get::<Enemy>().kind,
+                    coords: enemy_result.components().get::<TerminalTransform>().coords,
+                    by: DestroyedBy::PlayerBullet,
+                });
             }
         }
     }
 }
 
 fn enemy_bullet_hits_player_collisions(results: Vec<QueryResultList>, _: GameCommandsArg) {
-    if let [bullet_collision_results, player_results, ..] = &results[..] {
-        let mut player = player_results[0].components().get_mut::<Player>();
+    if let [bullet_collision_results, player_results, game_events_results, ..] = &results[..] {
+        let mut lives_left = player_results.get_only::<Player>().lives;
+        let player_coords = player_results.get_only::<TerminalTransform>().coords;
+        let mut game_events = game_events_results.get_only_mut::<GameEvents>();
 
         for _ in bullet_collision_results {
-            if player.lives > 0 {
-                player.lives -= 1;
+            if lives_left > 0 {
+                lives_left -= 1;
+
+                game_events.emit(GameEvent::PlayerHit {
+                    coords: player_coords,
+                });
+            }
+        }
+    }
+}
+
+fn bullet_hits_bunker_collisions(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [bullet_collision_results, bunker_results, combo_results, game_events_results, ..] =
+        &results[..]
+    {
+        let mut game_events = game_events_results.get_only_mut::<GameEvents>();
+
+        for bullet_collision in bullet_collision_results {
+            let collision = bullet_collision.components().get::<TerminalCollision>();
+
+            let bunker_entity = collision
+                .bodies
+                .iter()
+                .find(|(_, collider)| collider.layer == BUNKER_COLLISION_LAYER)
+                .unwrap()
+                .0;
+
+            // A shot that hits a bunker missed, as far as the combo is concerned.
+            if collision
+                .bodies
+                .iter()
+                .any(|(_, collider)| collider.layer == PLAYER_BULLET_COLLISION_LAYER)
+            {
+                combo_results.get_only_mut::<Combo>().break_chain();
             }
+
+            let Some(bunker_result) = bunker_results
+                .iter()
+                .find(|bunker_result| *bunker_result.entity() == bunker_entity)
+            else {
+                continue;
+            };
+
+            let mut bunker = bunker_result.components().get_mut::<Bunker>();
+
+            // Already shot away by another bullet this frame.
+            if bunker.hp == 0 {
+                continue;
+            }
+
+            bunker.hp -= 1;
+
+            if bunker.hp == 0 {
+                commands
+                    .borrow_mut()
+                    .issue(GameCommand::DestroyEntity(bunker_entity));
+            } else {
+                bunker_result
+                    .components()
+                    .get_mut::<TerminalRenderer>()
+                    .display = BUNKER_DISPLAY_CHARS[bunker.hp as usize - 1];
+            }
+
+            game_events.emit(GameEvent::BunkerHit {
+                coords: bunker_result.components().get::<TerminalTransform>().coords,
+            });
         }
     }
 }
 
 fn cleanup_bullets_on_collision(results: Vec<QueryResultList>, commands: GameCommandsArg) {
-    if let [bullet_collision_results, ..] = &results[..] {
+    if let [bullet_collision_results, bullet_results, ..] = &results[..] {
         for bullet_collision in bullet_collision_results {
             let collision = bullet_collision.components().get::<TerminalCollision>();
 
-            commands.borrow_mut().issue(GameCommand::DestroyEntity(
-                collision
-                    .bodies
-                    .iter()
-                    .find(|(_, collider)| {
-                        collider.layer == ENEMY_BULLET_COLLISION_LAYER
-                            || collider.layer == PLAYER_BULLET_COLLISION_LAYER
-                    })
-                    .unwrap()
-                    .0,
-            ))
+            let bullet_entity = collision
+                .bodies
+                .iter()
+                .find(|(_, collider)| {
+                    collider.layer == ENEMY_BULLET_COLLISION_LAYER
+                        || collider.layer == PLAYER_BULLET_COLLISION_LAYER
+                })
+                .unwrap()
+                .0;
+
+            // Enemies, the player and bunkers get their own bursts when they're hit.
+            let is_impact = !collision.bodies.iter().any(|(_, collider)| {
+                collider.layer == ENEMY_COLLISION_LAYER
+                    || collider.layer == PLAYER_COLLISION_LAYER
+                    || collider.layer == BUNKER_COLLISION_LAYER
+            });
+
+            if let Some(bullet_result) = bullet_results
+                .iter()
+                .find(|bullet_result| *bullet_result.entity() == bullet_entity)
+                .filter(|_| is_impact)
+            {
+                commands
+                    .borrow_mut()
+                    .issue(GameCommand::AddEntity(make_particle_burst(
+                        bullet_result.components().get::<TerminalTransform>().coords,
+                        ParticleBurst::BULLET_IMPACT,
+                    )));
+            }
+
+            commands
+                .borrow_mut()
+                .issue(GameCommand::DestroyEntity(bullet_entity))
         }
     }
 }
@@ -0,0 +1,270 @@
+use thomas::{
+    Alignment, GameCommand, GameCommandsArg, Input, IntCoords2d, Keycode, Query, QueryResultList,
+    System, SystemsGenerator, Text, UiAnchor, EVENT_AFTER_UPDATE, EVENT_INIT, EVENT_UPDATE,
+};
+
+use crate::{
+    load_key_values, save_key_values, Campaign, CampaignProgress, CampaignRun, GameEvent,
+    GameEvents, GameMode, GamePhase, GameState, InterludeLine, Levels, Locale, Localization,
+    Scorekeeper, ThemeRole, Themes, CAMPAIGN_PROGRESS_FILE_NAME,
+};
+
+/// Plays a campaign's waves in order, tells its story between them and keeps track of how far the player has got.
+pub struct CampaignSystemsGenerator {
+    pub campaign: Campaign,
+}
+impl SystemsGenerator for CampaignSystemsGenerator {
+    fn generate(&self) -> Vec<(&'static str, System)> {
+        let campaign = self.campaign.clone();
+
+        vec![
+            (
+                EVENT_INIT,
+                System::new(vec![], move |_, commands| {
+                    let progress = CampaignProgress::from_saved(
+                        &load_key_values(CAMPAIGN_PROGRESS_FILE_NAME).unwrap_or_default(),
+                        &campaign.slug(),
+                    );
+
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(CampaignRun::new(
+                            campaign.clone(),
+                            progress,
+                        ))]));
+                }),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has_where::<Input>(|input| input.is_key_down(&Keycode::Enter)),
+                        Query::new().has::<CampaignRun>(),
+                        Query::new()
+                            .has_where::<GameState>(|state| state.phase == GamePhase::Interlude),
+                    ],
+                    |results, _| {
+                        if let [enter_pressed_results, campaign_run_results, interlude_state_results, ..] =
+                            &results[..]
+                        {
+                            if !enter_pressed_results.is_empty()
+                                && !interlude_state_results.is_empty()
+                            {
+                                campaign_run_results
+                                    .get_only_mut::<CampaignRun>()
+                                    .is_interlude_over = true;
+                            }
+                        }
+                    },
+                ),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<CampaignRun>(),
+                        Query::new().has::<GameState>(),
+                        Query::new().has::<Themes>(),
+                        Query::new().has::<Localization>(),
+                        Query::new().has::<InterludeLine>().has::<Text>(),
+                    ],
+                    draw_interlude,
+                ),
+            ),
+            (
+                // Starting the campaign waits until after the update, so the key that chose it from the menu isn't
+                // also taken as reading the first interlude.
+                EVENT_AFTER_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<CampaignRun>(),
+                        Query::new().has::<GameState>(),
+                        Query::new().has::<Levels>(),
+                        Query::new().has::<Scorekeeper>(),
+                        Query::new().has::<GameEvents>(),
+                    ],
+                    run_campaign,
+                ),
+            ),
+        ]
+    }
+}
+
+fn run_campaign(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [campaign_run_results, game_state_results, levels_results, scorekeeper_results, game_events_results, ..] =
+        &results[..]
+    {
+        let mut campaign_run = campaign_run_results.get_only_mut::<CampaignRun>();
+        let mut game_state = game_state_results.get_only_mut::<GameState>();
+
+        if let Some(start_wave) = campaign_run.start_wave.take() {
+            let mut levels = levels_results.get_only_mut::<Levels>();
+
+            campaign_run.stashed_levels = Some(std::mem::replace(
+                &mut levels.levels,
+                campaign_run.campaign.levels(),
+            ));
+            campaign_run.interlude_wave = 0;
+            // There's nothing to read yet. The starting wave's interlude is shown as it's about to be spawned.
+            campaign_run.is_interlude_over = true;
+
+            scorekeeper_results.get_only_mut::<Scorekeeper>().level = start_wave - 1;
+
+            game_state.mode = GameMode::Campaign;
+            game_state.enter(GamePhase::Interlude);
+            return;
+        }
+
+        if game_state.mode != GameMode::Campaign {
+            return;
+        }
+
+        let mut has_progressed = false;
+
+        for event in game_events_results.get_only::<GameEvents>().iter() {
+            match *event {
+                GameEvent::WaveStarted { level, .. } => {
+                    let is_new_checkpoint = campaign_run
+                        .campaign
+                        .wave(level)
+                        .is_some_and(|wave| wave.is_checkpoint)
+                        && campaign_run
+                            .progress
+                            .checkpoint
+                            .is_none_or(|checkpoint| checkpoint < level);
+
+                    if is_new_checkpoint {
+                        campaign_run.progress.checkpoint = Some(level);
+                        has_progressed = true;
+                    }
+                }
+                GameEvent::CampaignCompleted { .. } => {
+                    // Finishing starts the next attempt from the top.
+                    campaign_run.progress = CampaignProgress {
+                        checkpoint: None,
+                        is_completed: true,
+                    };
+                    has_progressed = true;
+                }
+                _ => {}
+            }
+        }
+
+        if has_progressed {
+            save_progress(&campaign_run);
+        }
+
+        // The run is over, whichever way it ended.
+        if game_state.phase == GamePhase::Menu {
+            if let Some(stashed_levels) = campaign_run.stashed_levels.take() {
+                levels_results.get_only_mut::<Levels>().levels = stashed_levels;
+            }
+
+            game_state.mode = GameMode::Classic;
+        }
+    }
+}
+
+fn save_progress(campaign_run: &CampaignRun) {
+    let mut saved = load_key_values(CAMPAIGN_PROGRESS_FILE_NAME).unwrap_or_default();
+
+    campaign_run
+        .progress
+        .write_to(&mut saved, &campaign_run.campaign.slug());
+
+    let mut entries: Vec<(String, String)> = saved.into_iter().collect();
+    entries.sort();
+
+    // Not being able to save isn't worth interrupting the game over. The progress still counts for this session.
+    let _ = save_key_values(CAMPAIGN_PROGRESS_FILE_NAME, &entries);
+}
+
+fn draw_interlude(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [campaign_run_results, game_state_results, themes_results, localization_results, interlude_line_results, ..] =
+        &results[..]
+    {
+        let campaign_run = campaign_run_results.get_only::<CampaignRun>();
+
+        if game_state_results.get_only::<GameState>().phase != GamePhase::Interlude
+            || campaign_run.is_interlude_over
+        {
+            for interlude_line_result in interlude_line_results {
+                commands
+                    .borrow_mut()
+                    .issue(GameCommand::DestroyEntity(*interlude_line_result.entity()));
+            }
+
+            return;
+        }
+
+        let theme = themes_results.get_only::<Themes>().current().clone();
+        let localization = localization_results.get_only::<Localization>();
+        let lines = get_interlude_lines(&campaign_run, localization.current());
+
+        if interlude_line_results.len() == lines.len() {
+            for (interlude_line_result, (value, role)) in interlude_line_results.iter().zip(lines) {
+                let mut text = interlude_line_result.components().get_mut::<Text>();
+
+                text.value = value;
+                text.foreground_color = Some(theme.color(role));
+            }
+        } else {
+            for interlude_line_result in interlude_line_results {
+                commands
+                    .borrow_mut()
+                    .issue(GameCommand::DestroyEntity(*interlude_line_result.entity()));
+            }
+
+            let top_row = -(lines.len() as i64 / 2);
+
+            for (row, (value, role)) in lines.into_iter().enumerate() {
+                commands.borrow_mut().issue(GameCommand::AddEntity(vec![
+                    Box::new(InterludeLine {}),
+                    Box::new(Text {
+                        anchor: UiAnchor::Middle,
+                        justification: Alignment::Middle,
+                        offset: IntCoords2d::new(0, top_row + row as i64),
+                        value,
+                        foreground_color: Some(theme.color(role)),
+                        background_color: None,
+                    }),
+                ]));
+            }
+        }
+    }
+}
+
+fn get_interlude_lines(campaign_run: &CampaignRun, locale: &Locale) -> Vec<(String, ThemeRole)> {
+    let wave = campaign_run.interlude_wave;
+    let title = match campaign_run.campaign.wave(wave) {
+        Some(_) if campaign_run.campaign.is_final_wave(wave) => {
+            String::from(locale.text("interlude.final_boss"))
+        }
+        Some(campaign_wave) if campaign_wave.boss_hp.is_some() => {
+            locale.format("interlude.boss", &[("level", &wave)])
+        }
+        Some(_) => locale.format("interlude.wave", &[("level", &wave)]),
+        None => String::from(locale.text("interlude.epilogue")),
+    };
+
+    let mut lines = vec![
+        (campaign_run.campaign.name.clone(), ThemeRole::Highlight),
+        (title, ThemeRole::Text),
+        (String::new(), ThemeRole::Text),
+    ];
+
+    lines.extend(
+        campaign_run
+            .interlude(wave)
+            .iter()
+            .map(|line| (line.clone(), ThemeRole::Text)),
+    );
+
+    lines.push((String::new(), ThemeRole::Text));
+    lines.push((
+        String::from(locale.text("interlude.hint")),
+        ThemeRole::Muted,
+    ));
+
+    lines
+}
@@ -0,0 +1,34 @@
+use thomas::{GameCommand, Query, System, SystemsGenerator, EVENT_INIT, EVENT_UPDATE};
+
+use crate::{Combo, PausableTimer, COMBO_WINDOW_MILLIS};
+
+pub struct ComboSystemsGenerator {}
+impl SystemsGenerator for ComboSystemsGenerator {
+    fn generate(&self) -> Vec<(&'static str, System)> {
+        vec![
+            (
+                EVENT_INIT,
+                System::new(vec![], |_, commands| {
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(Combo {
+                            chain: 0,
+                            window_timer: PausableTimer::new(),
+                        })]));
+                }),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(vec![Query::new().has::<Combo>()], |results, _| {
+                    if let [combo_results, ..] = &results[..] {
+                        let mut combo = combo_results.get_only_mut::<Combo>();
+
+                        if combo.window_timer.elapsed_millis() >= COMBO_WINDOW_MILLIS {
+                            combo.break_chain();
+                        }
+                    }
+                }),
+            ),
+        ]
+    }
+}
@@ -0,0 +1,163 @@
+use thomas::{
+    GameCommand, GameCommandsArg, Query, QueryResultList, System, SystemsGenerator,
+    EVENT_AFTER_UPDATE, EVENT_INIT,
+};
+
+use crate::{
+    load_key_values, save_key_values, today, Banners, DailyChallenge, DailyModifier, DailyResults,
+    DailyRun, GameEvent, GameEvents, GameMode, GamePhase, GameRng, GameState, HighScore, Levels,
+    Localization, Player, Scorekeeper, Stats, ThemeRole, BANNER_DURATION_MILLIS,
+    DAILY_RESULTS_FILE_NAME,
+};
+
+/// Plays the day's challenge, and keeps the result of each player's first go each day between sessions.
+pub struct DailySystemsGenerator {
+    pub player_name: String,
+}
+impl SystemsGenerator for DailySystemsGenerator {
+    fn generate(&self) -> Vec<(&'static str, System)> {
+        let player_name = self.player_name.clone();
+
+        vec![
+            (
+                EVENT_INIT,
+                System::new(vec![], move |_, commands| {
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(DailyRun::new(
+                            DailyChallenge::today(),
+                            DailyResults::from_saved(
+                                &load_key_values(DAILY_RESULTS_FILE_NAME).unwrap_or_default(),
+                            ),
+                            player_name.clone(),
+                        ))]));
+                }),
+            ),
+            (
+                // Like the campaign, starting waits until after the update, so the run doesn't begin in the middle
+                // of the frame the menu was left in.
+                EVENT_AFTER_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<DailyRun>(),
+                        Query::new().has::<GameState>(),
+                        Query::new().has::<Levels>(),
+                        Query::new().has::<GameRng>(),
+                        Query::new().has::<Player>(),
+                        Query::new().has::<Scorekeeper>(),
+                        Query::new().has::<Stats>(),
+                        Query::new().has::<GameEvents>(),
+                        Query::new().has::<Banners>(),
+                        Query::new().has::<Localization>(),
+                    ],
+                    run_daily,
+                ),
+            ),
+        ]
+    }
+}
+
+fn run_daily(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [daily_run_results, game_state_results, levels_results, game_rng_results, player_results, scorekeeper_results, stats_results, game_events_results, banners_results, localization_results, ..] =
+        &results[..]
+    {
+        let mut daily_run = daily_run_results.get_only_mut::<DailyRun>();
+        let mut game_state = game_state_results.get_only_mut::<GameState>();
+
+        if daily_run.is_starting {
+            daily_run.is_starting = false;
+
+            // The game may have been left open past midnight.
+            if daily_run.challenge.day != today() {
+                daily_run.challenge = DailyChallenge::today();
+            }
+
+            daily_run.stashed_levels = Some(std::mem::replace(
+                &mut levels_results.get_only_mut::<Levels>().levels,
+                daily_run.challenge.levels.clone(),
+            ));
+
+            game_rng_results
+                .get_only_mut::<GameRng>()
+                .start_run_from(daily_run.challenge.seed);
+
+            if daily_run.challenge.has(DailyModifier::LastLife) {
+                player_results.get_only_mut::<Player>().lives = 1;
+            }
+
+            daily_run.is_scored = daily_run.todays_result().is_none();
+
+            if daily_run.is_scored {
+                // The go is used up as soon as it starts.
+                daily_run.record_todays_result(HighScore {
+                    score: 0,
+                    level: 0,
+                    time_millis: 0,
+                });
+                save_results(&daily_run.results);
+            } else {
+                banners_results.get_only_mut::<Banners>().show(
+                    String::from(
+                        localization_results
+                            .get_only::<Localization>()
+                            .current()
+                            .text("banner.daily_practice"),
+                    ),
+                    ThemeRole::Muted,
+                    BANNER_DURATION_MILLIS,
+                );
+            }
+
+            game_state.mode = GameMode::Daily;
+            game_state.enter(GamePhase::Playing);
+            return;
+        }
+
+        if game_state.mode != GameMode::Daily {
+            return;
+        }
+
+        if daily_run.is_scored {
+            let scorekeeper = scorekeeper_results.get_only::<Scorekeeper>();
+
+            for event in game_events_results.get_only::<GameEvents>().iter() {
+                // The result is kept up to date as each wave is cleared, so it still counts if the game is quit
+                // part-way through the next one.
+                let result = match *event {
+                    GameEvent::WaveCleared { .. } => HighScore {
+                        score: scorekeeper.score,
+                        level: scorekeeper.level,
+                        time_millis: stats_results.get_only::<Stats>().run_time_millis(),
+                    },
+                    GameEvent::GameOver {
+                        score,
+                        level,
+                        time_millis,
+                    } => HighScore {
+                        score,
+                        level,
+                        time_millis,
+                    },
+                    _ => continue,
+                };
+
+                daily_run.record_todays_result(result);
+                save_results(&daily_run.results);
+            }
+        }
+
+        // The run is over.
+        if game_state.phase == GamePhase::Menu {
+            if let Some(stashed_levels) = daily_run.stashed_levels.take() {
+                levels_results.get_only_mut::<Levels>().levels = stashed_levels;
+            }
+
+            game_state.mode = GameMode::Classic;
+        }
+    }
+}
+
+fn save_results(results: &DailyResults) {
+    // Not being able to save isn't worth interrupting the game over. The result still counts for this session.
+    let _ = save_key_values(DAILY_RESULTS_FILE_NAME, &results.to_saved());
+}
@@ -0,0 +1,473 @@
+use std::path::PathBuf;
+
+use thomas::{
+    Alignment, GameCommand, GameCommandsArg, Input, IntCoords2d, Keycode, Layer, Query,
+    QueryResultList, System, SystemsGenerator, TerminalRenderer, TerminalTransform, Text, UiAnchor,
+    EVENT_AFTER_UPDATE, EVENT_INIT, EVENT_UPDATE,
+};
+
+use crate::{
+    Bonuses, Boss, Bullet, Bunker, EditorCell, EditorLine, EditorStatus, Enemy, EnemyKind,
+    GameMode, GamePhase, GameRng, GameState, Level, LevelCell, LevelEditor, Levels, Locale,
+    Localization, MarchRule, PlacementError, Player, Scorekeeper, Settings, Stats, ThemeRole,
+    Themes, MAX_LEVEL_COLUMNS, MAX_LEVEL_MOVE_WAIT_MILLIS, MIN_LEVEL_MOVE_WAIT_MILLIS,
+    PLAYER_STARTING_LIVES, SCREEN_WIDTH, UI_Y_START_POSITION,
+};
+
+const EDITOR_TOP_ROW: u64 = 2;
+/// The bunker rows end just above the player, where the game puts them.
+const EDITOR_BUNKER_BOTTOM_ROW: u64 = UI_Y_START_POSITION - 2;
+/// Cells are as far apart as the invaders are in the game.
+const EDITOR_CELL_SPACING: u64 = 3;
+
+const EDITOR_SPEED_STEP_MILLIS: u128 = 50;
+
+/// The editor's text, as rows from the top of the screen.
+const EDITOR_TITLE_ROW: i64 = 0;
+const EDITOR_PARAMETERS_ROW: i64 = 1;
+const EDITOR_STATUS_ROW: i64 = 7;
+const EDITOR_HELP_ROW: i64 = 9;
+
+/// Builds levels on a grid over the playfield, play-tests them and saves them to level files.
+pub struct EditorSystemsGenerator {
+    /// The level file the editor works on.
+    pub path: PathBuf,
+    /// What's in the file already, if anything.
+    pub level: Option<Level>,
+    /// Open the editor as soon as the game starts, rather than waiting to be chosen from the menu.
+    pub should_open: bool,
+}
+impl SystemsGenerator for EditorSystemsGenerator {
+    fn generate(&self) -> Vec<(&'static str, System)> {
+        let path = self.path.clone();
+        let level = self.level.clone();
+        let should_open = self.should_open;
+
+        vec![
+            (
+                EVENT_INIT,
+                System::new(vec![], move |_, commands| {
+                    let mut level_editor = LevelEditor::new(path.clone(), level.clone());
+                    level_editor.is_opening = should_open;
+
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(level_editor)]));
+                }),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<Input>(),
+                        Query::new().has::<LevelEditor>(),
+                        Query::new()
+                            .has_where::<GameState>(|state| state.phase == GamePhase::Editor),
+                        Query::new().has::<Levels>(),
+                        Query::new().has::<Settings>(),
+                    ],
+                    edit_level,
+                ),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<LevelEditor>(),
+                        Query::new().has::<GameState>(),
+                        Query::new().has::<Themes>(),
+                        Query::new().has::<Localization>(),
+                        Query::new().has::<EditorCell>().has::<TerminalRenderer>(),
+                        Query::new().has::<EditorLine>().has::<Text>(),
+                    ],
+                    draw_editor,
+                ),
+            ),
+            (
+                // Switching in and out of the editor waits until after the update, so the key that did it isn't
+                // also taken by whatever's shown next.
+                EVENT_AFTER_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<Input>(),
+                        Query::new().has::<LevelEditor>(),
+                        Query::new().has::<GameState>(),
+                        Query::new().has::<Levels>(),
+                        Query::new().has::<Scorekeeper>(),
+                        Query::new().has::<Player>(),
+                        Query::new().has::<Stats>(),
+                        Query::new().has::<GameRng>(),
+                        Query::new().has::<Bonuses>(),
+                        Query::new().has::<Enemy>(),
+                        Query::new().has::<Boss>(),
+                        Query::new().has::<Bullet>(),
+                        Query::new().has::<Bunker>(),
+                    ],
+                    switch_editor,
+                ),
+            ),
+        ]
+    }
+}
+
+fn edit_level(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [input_results, level_editor_results, editor_state_results, levels_results, settings_results, ..] =
+        &results[..]
+    {
+        if editor_state_results.is_empty() {
+            return;
+        }
+
+        let input = input_results.get_only::<Input>();
+        let mut level_editor = level_editor_results.get_only_mut::<LevelEditor>();
+        let mut game_state = editor_state_results.get_only_mut::<GameState>();
+        let difficulty = settings_results.get_only::<Settings>().difficulty;
+
+        let is_key_down = |key: Keycode| input.is_key_down(&key);
+
+        let placement = if is_key_down(Keycode::S) {
+            Some(LevelCell::Enemy(EnemyKind::Squid))
+        } else if is_key_down(Keycode::C) {
+            Some(LevelCell::Enemy(EnemyKind::Crab))
+        } else if is_key_down(Keycode::O) {
+            Some(LevelCell::Enemy(EnemyKind::Octopus))
+        } else if is_key_down(Keycode::B) {
+            Some(LevelCell::Bunker)
+        } else if [Keycode::Space, Keycode::Delete, Keycode::Backspace]
+            .into_iter()
+            .any(is_key_down)
+        {
+            Some(LevelCell::Empty)
+        } else {
+            None
+        };
+
+        if let Some(cell) = placement {
+            level_editor.status = level_editor.place(cell).err().map(EditorStatus::CantPlace);
+        } else if is_key_down(Keycode::Up) {
+            level_editor.move_cursor(0, -1);
+        } else if is_key_down(Keycode::Down) {
+            level_editor.move_cursor(0, 1);
+        } else if is_key_down(Keycode::Left) {
+            level_editor.move_cursor(-1, 0);
+        } else if is_key_down(Keycode::Right) {
+            level_editor.move_cursor(1, 0);
+        } else if is_key_down(Keycode::LeftBracket) || is_key_down(Keycode::RightBracket) {
+            // The formation waits longer between steps the slower it is.
+            let move_wait_millis = level_editor
+                .level
+                .move_wait_millis
+                .unwrap_or_else(|| difficulty.enemy_move_wait_millis());
+
+            level_editor.level.move_wait_millis = Some(
+                if is_key_down(Keycode::LeftBracket) {
+                    move_wait_millis + EDITOR_SPEED_STEP_MILLIS
+                } else {
+                    move_wait_millis.saturating_sub(EDITOR_SPEED_STEP_MILLIS)
+                }
+                .clamp(MIN_LEVEL_MOVE_WAIT_MILLIS, MAX_LEVEL_MOVE_WAIT_MILLIS),
+            );
+        } else if is_key_down(Keycode::Comma) || is_key_down(Keycode::Dot) {
+            let shoot_chance = level_editor
+                .level
+                .shoot_chance
+                .unwrap_or_else(|| difficulty.enemy_shoot_chance());
+
+            level_editor.level.shoot_chance = Some(if is_key_down(Keycode::Comma) {
+                shoot_chance.saturating_sub(1)
+            } else {
+                u8::min(shoot_chance + 1, 100)
+            });
+        } else if is_key_down(Keycode::M) {
+            level_editor.level.march_rule = match level_editor.level.march_rule {
+                MarchRule::Random => MarchRule::Sweep,
+                MarchRule::Sweep => MarchRule::Random,
+            };
+        } else if (is_key_down(Keycode::P) || is_key_down(Keycode::Enter))
+            && !level_editor.has_invaders()
+        {
+            // A level without invaders can't be loaded back in, so it's neither played nor saved.
+            level_editor.status = Some(EditorStatus::NoInvaders);
+        } else if is_key_down(Keycode::P) {
+            let mut levels = levels_results.get_only_mut::<Levels>();
+
+            level_editor.stashed_levels = Some(std::mem::replace(
+                &mut levels.levels,
+                vec![level_editor.to_level()],
+            ));
+            level_editor.status = None;
+
+            game_state.mode = GameMode::PlayTest;
+            game_state.enter(GamePhase::Playing);
+        } else if is_key_down(Keycode::Enter) {
+            let level = level_editor.to_level();
+
+            level_editor.status = Some(match level.save(&level_editor.path) {
+                Ok(()) => EditorStatus::Saved(level_editor.path.display().to_string()),
+                Err(e) => EditorStatus::SaveFailed(e.to_string()),
+            });
+
+            // Shows the map the way it'll be read back in.
+            level_editor.lay_out(&level);
+        }
+    }
+}
+
+fn switch_editor(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [input_results, level_editor_results, game_state_results, levels_results, scorekeeper_results, player_results, stats_results, game_rng_results, bonuses_results, enemy_results, boss_results, bullet_results, bunker_results, ..] =
+        &results[..]
+    {
+        let input = input_results.get_only::<Input>();
+        let mut level_editor = level_editor_results.get_only_mut::<LevelEditor>();
+        let mut game_state = game_state_results.get_only_mut::<GameState>();
+
+        if level_editor.is_opening {
+            level_editor.is_opening = false;
+            level_editor.status = None;
+
+            game_state.enter(GamePhase::Editor);
+            return;
+        }
+
+        if game_state.mode != GameMode::PlayTest {
+            if game_state.phase == GamePhase::Editor && input.is_key_down(&Keycode::Escape) {
+                game_state.enter(GamePhase::Menu);
+            }
+
+            return;
+        }
+
+        let is_stopping_early = input.is_key_down(&Keycode::Escape)
+            && matches!(
+                game_state.phase,
+                GamePhase::Playing | GamePhase::WaveSummary | GamePhase::GameOver
+            );
+
+        // A run that ended on its own is already tidied up by the time it's back in the editor.
+        if is_stopping_early {
+            for entity in enemy_results
+                .iter()
+                .chain(boss_results.iter())
+                .chain(bullet_results.iter())
+                .chain(bunker_results.iter())
+                .map(|result| *result.entity())
+            {
+                commands
+                    .borrow_mut()
+                    .issue(GameCommand::DestroyEntity(entity));
+            }
+
+            let mut scorekeeper = scorekeeper_results.get_only_mut::<Scorekeeper>();
+            scorekeeper.score = 0;
+            scorekeeper.level = 0;
+
+            player_results.get_only_mut::<Player>().lives = PLAYER_STARTING_LIVES;
+
+            stats_results.get_only_mut::<Stats>().reset();
+            bonuses_results
+                .get_only_mut::<Bonuses>()
+                .extra_lives_awarded = 0;
+            game_rng_results.get_only_mut::<GameRng>().start_new_run();
+
+            game_state.enter(GamePhase::Editor);
+        }
+
+        if game_state.phase == GamePhase::Editor {
+            if let Some(stashed_levels) = level_editor.stashed_levels.take() {
+                levels_results.get_only_mut::<Levels>().levels = stashed_levels;
+            }
+
+            game_state.mode = GameMode::Classic;
+        }
+    }
+}
+
+fn draw_editor(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [level_editor_results, game_state_results, themes_results, localization_results, editor_cell_results, editor_line_results, ..] =
+        &results[..]
+    {
+        if game_state_results.get_only::<GameState>().phase != GamePhase::Editor {
+            for entity in editor_cell_results
+                .iter()
+                .chain(editor_line_results.iter())
+                .map(|result| *result.entity())
+            {
+                commands
+                    .borrow_mut()
+                    .issue(GameCommand::DestroyEntity(entity));
+            }
+
+            return;
+        }
+
+        let level_editor = level_editor_results.get_only::<LevelEditor>();
+        let theme = themes_results.get_only::<Themes>().current().clone();
+        let localization = localization_results.get_only::<Localization>();
+        let lines = get_editor_lines(&level_editor, localization.current());
+
+        if editor_cell_results.is_empty() {
+            for row in 0..LevelEditor::ROWS {
+                for col in 0..MAX_LEVEL_COLUMNS {
+                    commands.borrow_mut().issue(GameCommand::AddEntity(vec![
+                        Box::new(EditorCell { col, row }),
+                        Box::new(TerminalTransform {
+                            coords: get_cell_coords(col, row),
+                        }),
+                        Box::new(TerminalRenderer {
+                            display: ' ',
+                            layer: Layer::base(),
+                            foreground_color: None,
+                            background_color: None,
+                        }),
+                    ]));
+                }
+            }
+
+            for (row, _) in &lines {
+                commands.borrow_mut().issue(GameCommand::AddEntity(vec![
+                    Box::new(EditorLine {}),
+                    Box::new(Text {
+                        anchor: UiAnchor::MiddleTop,
+                        justification: Alignment::Middle,
+                        offset: IntCoords2d::new(0, *row),
+                        value: String::new(),
+                        foreground_color: None,
+                        background_color: None,
+                    }),
+                ]));
+            }
+
+            return;
+        }
+
+        for editor_cell_result in editor_cell_results {
+            let editor_cell = editor_cell_result.components().get::<EditorCell>();
+            let mut renderer = editor_cell_result
+                .components()
+                .get_mut::<TerminalRenderer>();
+            let cell = level_editor.cells[editor_cell.row][editor_cell.col];
+
+            renderer.display = cell.symbol();
+            renderer.foreground_color = Some(match cell {
+                LevelCell::Enemy(_) => theme.enemy,
+                LevelCell::Bunker => theme.bunker,
+                LevelCell::Empty => theme.muted,
+            });
+            renderer.background_color = None;
+
+            if level_editor.cursor == (editor_cell.col, editor_cell.row) {
+                renderer.foreground_color = Some(theme.background);
+                renderer.background_color = Some(theme.highlight);
+            }
+        }
+
+        for (editor_line_result, (_, (value, role))) in editor_line_results.iter().zip(lines) {
+            let mut text = editor_line_result.components().get_mut::<Text>();
+
+            text.value = value;
+            text.foreground_color = Some(theme.color(role));
+        }
+    }
+}
+
+fn get_cell_coords(col: usize, row: usize) -> IntCoords2d {
+    let grid_width = (MAX_LEVEL_COLUMNS as u64 - 1) * EDITOR_CELL_SPACING + 1;
+    let x = (SCREEN_WIDTH - grid_width) / 2 + col as u64 * EDITOR_CELL_SPACING;
+    let y = if LevelEditor::is_bunker_row(row) {
+        EDITOR_BUNKER_BOTTOM_ROW + 1 + row as u64 - LevelEditor::ROWS as u64
+    } else {
+        EDITOR_TOP_ROW + row as u64
+    };
+
+    IntCoords2d::new(x as i64, y as i64)
+}
+
+/// Each of the editor's lines of text and the row it goes on. There are always the same number of them.
+fn get_editor_lines(
+    level_editor: &LevelEditor,
+    locale: &Locale,
+) -> Vec<(i64, (String, ThemeRole))> {
+    let level = &level_editor.level;
+    let default = locale.text("editor.default");
+
+    let speed = level
+        .move_wait_millis
+        .map(|millis| locale.format("editor.speed", &[("value", &millis)]))
+        .unwrap_or_else(|| String::from(default));
+    let fire_rate = level
+        .shoot_chance
+        .map(|chance| locale.format("editor.fire_rate", &[("value", &chance)]))
+        .unwrap_or_else(|| String::from(default));
+    let march_key = format!("editor.march.{}", level.march_rule.name());
+    let march = locale.text(&march_key);
+
+    let status = match &level_editor.status {
+        Some(EditorStatus::Saved(path)) => (
+            locale.format("editor.saved", &[("path", path)]),
+            ThemeRole::Positive,
+        ),
+        Some(EditorStatus::SaveFailed(error)) => (
+            locale.format("editor.save_failed", &[("error", error)]),
+            ThemeRole::Highlight,
+        ),
+        Some(EditorStatus::CantPlace(PlacementError::InvadersOnly)) => (
+            String::from(locale.text("editor.invaders_only")),
+            ThemeRole::Highlight,
+        ),
+        Some(EditorStatus::CantPlace(PlacementError::BunkersOnly)) => (
+            String::from(locale.text("editor.bunkers_only")),
+            ThemeRole::Highlight,
+        ),
+        Some(EditorStatus::NoInvaders) => (
+            String::from(locale.text("editor.no_invaders")),
+            ThemeRole::Highlight,
+        ),
+        None => (String::new(), ThemeRole::Text),
+    };
+
+    vec![
+        (
+            EDITOR_TITLE_ROW,
+            (
+                locale.format("editor.title", &[("name", &level.name)]),
+                ThemeRole::Highlight,
+            ),
+        ),
+        (
+            EDITOR_PARAMETERS_ROW,
+            (
+                locale.format(
+                    "editor.parameters",
+                    &[
+                        ("speed", &speed),
+                        ("fire_rate", &fire_rate),
+                        ("march", &march),
+                    ],
+                ),
+                ThemeRole::Text,
+            ),
+        ),
+        (EDITOR_STATUS_ROW, status),
+        (
+            EDITOR_HELP_ROW,
+            (
+                String::from(locale.text("editor.help_cells")),
+                ThemeRole::Muted,
+            ),
+        ),
+        (
+            EDITOR_HELP_ROW + 1,
+            (
+                String::from(locale.text("editor.help_parameters")),
+                ThemeRole::Muted,
+            ),
+        ),
+        (
+            EDITOR_HELP_ROW + 2,
+            (
+                String::from(locale.text("editor.help_actions")),
+                ThemeRole::Muted,
+            ),
+        ),
+    ]
+}
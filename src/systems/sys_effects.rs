@@ -0,0 +1,158 @@
+use thomas::{
+    GameCommand, GameCommandsArg, Query, QueryResult, QueryResultList, Rgb, System,
+    SystemsGenerator, TerminalRenderer, EVENT_AFTER_UPDATE,
+};
+
+use crate::{
+    make_explosion, make_particle_burst, Animation, AnimationFrame, EnemyKind, GameEvent,
+    GameEvents, ParticleBurst, Player, ScreenEffects, Sprite, SpriteCell, BOSS_COLOR,
+    PLAYER_HIT_BLINK_INTERVAL_MILLIS, PLAYER_HIT_BLINK_TIMES,
+};
+
+/// The explosions, particles, blinking and screen effects that play in response to gameplay events.
+pub struct EffectsSystemsGenerator {}
+impl SystemsGenerator for EffectsSystemsGenerator {
+    fn generate(&self) -> Vec<(&'static str, System)> {
+        vec![(
+            EVENT_AFTER_UPDATE,
+            System::new(
+                vec![
+                    Query::new().has::<GameEvents>(),
+                    Query::new().has::<ScreenEffects>(),
+                    Query::new().has::<Player>().has::<TerminalRenderer>(),
+                    Query::new().has::<Player>().has::<Sprite>(),
+                    Query::new().has::<SpriteCell>().has::<TerminalRenderer>(),
+                    Query::new().has::<Animation>(),
+                ],
+                play_effects,
+            ),
+        )]
+    }
+}
+
+fn play_effects(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [game_events_results, screen_effects_results, player_renderer_results, player_sprite_results, sprite_cell_results, animation_results, ..] =
+        &results[..]
+    {
+        let mut screen_effects = screen_effects_results.get_only_mut::<ScreenEffects>();
+        let mut was_player_hit = false;
+
+        for event in game_events_results.get_only::<GameEvents>().iter() {
+            match *event {
+                GameEvent::EnemyDestroyed {
+                    kind: EnemyKind::Boss,
+                    coords,
+                    ..
+                } => {
+                    screen_effects.shake(3);
+                    screen_effects.flash(BOSS_COLOR);
+
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(make_particle_burst(
+                            coords,
+                            ParticleBurst::ENEMY_DEATH,
+                        )));
+
+                    for (offset, _) in Sprite::new(EnemyKind::Boss.sprite_art()).cells() {
+                        commands
+                            .borrow_mut()
+                            .issue(GameCommand::AddEntity(make_explosion(coords + offset)));
+                    }
+                }
+                GameEvent::EnemyDestroyed { coords, .. } => {
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(make_explosion(coords)));
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(make_particle_burst(
+                            coords,
+                            ParticleBurst::ENEMY_DEATH,
+                        )));
+                }
+                GameEvent::PlayerHit { coords } => {
+                    was_player_hit = true;
+
+                    screen_effects.shake(1);
+
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(make_particle_burst(
+                            coords,
+                            ParticleBurst::PLAYER_HIT,
+                        )));
+                }
+                GameEvent::BunkerHit { coords } => {
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(make_particle_burst(
+                            coords,
+                            ParticleBurst::BUNKER_CHIP,
+                        )));
+                }
+                GameEvent::GameOver { .. } => {
+                    screen_effects.shake(2);
+                    screen_effects.flash(Rgb::red());
+                }
+                _ => {}
+            }
+        }
+
+        if was_player_hit {
+            let player_sprite_id = player_sprite_results
+                .try_get_only::<Sprite>()
+                .map(|sprite| sprite.id);
+
+            blink(
+                player_renderer_results
+                    .iter()
+                    .chain(sprite_cell_results.iter().filter(|sprite_cell_result| {
+                        Some(
+                            sprite_cell_result
+                                .components()
+                                .get::<SpriteCell>()
+                                .sprite_id,
+                        ) == player_sprite_id
+                    })),
+                animation_results,
+                &commands,
+            );
+        }
+    }
+}
+
+/// Blinks each entity, restarting the blink of any that are already blinking.
+fn blink<'a>(
+    renderer_results: impl Iterator<Item = &'a QueryResult>,
+    animation_results: &QueryResultList,
+    commands: &GameCommandsArg,
+) {
+    for renderer_result in renderer_results {
+        if let Some(animation_result) = animation_results
+            .iter()
+            .find(|animation_result| animation_result.entity() == renderer_result.entity())
+        {
+            animation_result
+                .components()
+                .get_mut::<Animation>()
+                .restart();
+        } else {
+            let renderer = renderer_result.components().get::<TerminalRenderer>();
+
+            commands
+                .borrow_mut()
+                .issue(GameCommand::AddComponentsToEntity(
+                    *renderer_result.entity(),
+                    vec![Box::new(Animation::blink(
+                        AnimationFrame {
+                            display: renderer.display,
+                            color: renderer.foreground_color,
+                        },
+                        PLAYER_HIT_BLINK_TIMES,
+                        PLAYER_HIT_BLINK_INTERVAL_MILLIS,
+                    ))],
+                ));
+        }
+    }
+}
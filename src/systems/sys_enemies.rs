@@ -1,36 +1,105 @@
 use rand::prelude::*;
 use thomas::{
-    GameCommand, GameCommandsArg, IntCoords2d, Query, QueryResultList, System, SystemsGenerator,
-    TerminalTransform, Timer, EVENT_INIT, EVENT_UPDATE,
+    GameCommand, GameCommandsArg, IntCoords2d, Query, QueryResult, QueryResultList, System,
+    SystemsGenerator, TerminalCollision, TerminalTransform, Timer, EVENT_INIT, EVENT_UPDATE,
 };
 
 use crate::{
-    make_bullet, make_enemy, Bullet, BulletType, Enemy, EnemyMovement, Scorekeeper, SCREEN_WIDTH,
+    get_sprite_owner, make_boss, make_bullet, make_bunker, make_enemy, make_summary_card,
+    Animation, AnimationTiming, Bonuses, Boss, Bullet, BulletType, Bunker, CampaignRun,
+    DestroyedBy, Dive, DiveLauncher, DiveStage, Enemy, EnemyKind, EnemyMovement, FormationStream,
+    GameEvent, GameEvents, GameMode, GamePhase, GameRng, GameState, Level, LevelCell, Levels,
+    Localization, MarchRule, PausableTimer, Player, Scorekeeper, Settings, Sprite, SpriteCell,
+    Stats, WaveSpawner, BOSS_BASE_HP, BOSS_HP_PER_APPEARANCE, BOSS_WAVE_INTERVAL, BUNKER_MAX_HP,
+    ENEMY_COLLISION_LAYER, PLAYER_COLLISION_LAYER, SCREEN_HEIGHT, SCREEN_WIDTH, TIME_ATTACK_WAVES,
+    UI_Y_START_POSITION,
 };
 
-const ENEMY_GRID_WIDTH: u64 = 10;
-const ENEMY_GRID_HEIGHT: u64 = 4;
 const ENEMY_PADDING: u64 = 2;
+const ENEMY_GRID_TOP_ROW: u64 = 2;
+const ENEMY_SPRITE_PADDING: u64 = 3;
+const ENEMY_SPRITE_ROW_HEIGHT: u64 = 3;
+/// The lowest bunker row sits just above the player.
+const BUNKER_BOTTOM_ROW: u64 = UI_Y_START_POSITION - 2;
+
+const WAVE_SUMMARY_WAIT_TIME: u128 = 3000;
+
+const SURVIVAL_STARTING_ROWS: u64 = 3;
+const SURVIVAL_ROW_COLUMNS: u64 = 10;
+/// The chance each spot in a new row is left empty.
+const SURVIVAL_GAP_CHANCE: u8 = 20;
+/// The stage goes up, and the rows come down faster, every so many rows.
+const SURVIVAL_ROWS_PER_STAGE: u64 = 4;
+const SURVIVAL_START_DESCEND_WAIT_TIME: u128 = 6000;
+const SURVIVAL_DESCEND_SPEEDUP_PER_STAGE: u128 = 500;
+const SURVIVAL_MIN_DESCEND_WAIT_TIME: u128 = 1500;
+const SURVIVAL_POINTS_PER_SECOND: u64 = 10;
+/// The formation has landed once any of it reaches the row just above the player.
+const INVASION_ROW: u64 = BUNKER_BOTTOM_ROW + 1;
 
 const ENEMY_SHOOT_WAIT_TIME: u128 = 200;
-const ENEMY_SHOOT_CHANCE: u8 = 10;
 
-const ENEMY_MOVE_WAIT_TIME: u128 = 800;
 const ENEMY_MIN_X: u64 = 3;
 const ENEMY_MAX_X: u64 = SCREEN_WIDTH - 4;
 
-pub struct EnemySystemsGenerator {}
+const DIVE_ATTACK_TIME: u128 = 2400;
+const DIVE_RETURN_TIME: u128 = 2000;
+const DIVE_MAX_GROUP_SIZE: usize = 3;
+/// How far a diving invader's path bows out to the side.
+const DIVE_SWING_WIDTH: f64 = 6.0;
+/// How far above the player a dive that loops back pulls up.
+const DIVE_PULL_UP_ROWS: i64 = 3;
+const DIVE_EXIT_CHANCE: f64 = 0.5;
+/// Where an invader that's dived out of the bottom of the screen comes back in.
+const DIVE_REENTRY_ROW: i64 = -1;
+const DIVE_SHOOT_CHANCE_MULTIPLIER: u8 = 3;
+
+pub struct EnemySystemsGenerator {
+    pub use_sprites: bool,
+    /// The levels the formations are laid out from.
+    pub levels: Vec<Level>,
+}
 impl SystemsGenerator for EnemySystemsGenerator {
     fn generate(&self) -> Vec<(&'static str, System)> {
+        let use_sprites = self.use_sprites;
+        let levels = self.levels.clone();
+
         vec![
             (
                 EVENT_INIT,
-                System::new(vec![], |_, commands| {
+                System::new(vec![], move |_, commands| {
                     commands
                         .borrow_mut()
                         .issue(GameCommand::AddEntity(vec![Box::new(EnemyMovement {
                             move_timer: Timer::start_new(),
-                        })]))
+                            direction: IntCoords2d::right(),
+                        })]));
+
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(Levels {
+                            levels: levels.clone(),
+                        })]));
+
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(DiveLauncher {
+                            launch_timer: Timer::start_new(),
+                        })]));
+
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(WaveSpawner {
+                            is_next_wave_due: false,
+                        })]));
+
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(FormationStream {
+                            descend_timer: Timer::new(),
+                            rows_spawned: 0,
+                            seconds_scored: 0,
+                        })]));
                 }),
             ),
             (
@@ -38,16 +107,107 @@ impl SystemsGenerator for EnemySystemsGenerator {
                 System::new(
                     vec![
                         Query::new().has::<Enemy>(),
+                        Query::new().has::<Boss>(),
+                        Query::new().has_where::<GameState>(|state| state.is_playing()),
+                        Query::new().has::<Scorekeeper>(),
+                        Query::new().has::<Stats>(),
+                        Query::new().has::<Bonuses>(),
+                        Query::new().has::<GameEvents>(),
+                        Query::new().has::<Localization>(),
+                    ],
+                    clear_wave,
+                ),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<Enemy>(),
+                        Query::new().has::<Boss>(),
                         Query::new().has::<Bullet>(),
+                        Query::new().has::<GameState>(),
+                        Query::new().has::<CampaignRun>(),
+                        Query::new().has::<WaveSpawner>(),
+                    ],
+                    advance_waves,
+                ),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has_where::<WaveSpawner>(|spawner| spawner.is_next_wave_due),
+                        Query::new().has::<GameState>(),
+                        Query::new().has::<Scorekeeper>(),
+                        Query::new().has::<CampaignRun>(),
+                    ],
+                    start_interlude,
+                ),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has_where::<WaveSpawner>(|spawner| spawner.is_next_wave_due),
+                        Query::new().has::<GameState>(),
                         Query::new().has::<Scorekeeper>(),
+                        Query::new().has::<Stats>(),
+                        Query::new().has::<GameEvents>(),
+                        Query::new().has::<Localization>(),
+                        Query::new().has::<CampaignRun>(),
                     ],
-                    spawn_enemies,
+                    complete_run,
                 ),
             ),
             (
                 EVENT_UPDATE,
                 System::new(
-                    vec![Query::new().has::<Enemy>().has::<TerminalTransform>()],
+                    vec![
+                        Query::new().has_where::<WaveSpawner>(|spawner| spawner.is_next_wave_due),
+                        Query::new().has::<GameState>(),
+                        Query::new().has::<Scorekeeper>(),
+                        Query::new().has::<Stats>(),
+                        Query::new().has::<GameRng>(),
+                        Query::new().has::<GameEvents>(),
+                        Query::new().has::<Levels>(),
+                        Query::new().has::<Bunker>(),
+                        Query::new().has::<CampaignRun>(),
+                    ],
+                    move |results, commands| spawn_wave(results, commands, use_sprites),
+                ),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<Enemy>().has::<TerminalTransform>(),
+                        Query::new().has::<FormationStream>(),
+                        Query::new().has_where::<GameState>(|state| {
+                            state.is_playing() && state.mode == GameMode::Survival
+                        }),
+                        Query::new().has::<Scorekeeper>(),
+                        Query::new().has::<Stats>(),
+                        Query::new().has::<GameRng>(),
+                        Query::new().has::<GameEvents>(),
+                        Query::new().has::<Player>(),
+                        Query::new().has::<Levels>(),
+                        Query::new().has::<Bunker>().has::<TerminalTransform>(),
+                    ],
+                    move |results, commands| stream_rows(results, commands, use_sprites),
+                ),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<Enemy>().has::<TerminalTransform>(),
+                        Query::new().has::<GameRng>(),
+                        Query::new().has_where::<GameState>(|state| state.is_playing()),
+                        Query::new().has::<Enemy>().has::<Sprite>(),
+                        Query::new().has::<Settings>(),
+                        Query::new().has::<Levels>(),
+                        Query::new().has::<Scorekeeper>(),
+                    ],
                     shoot,
                 ),
             ),
@@ -57,46 +217,582 @@ impl SystemsGenerator for EnemySystemsGenerator {
                     vec![
                         Query::new().has::<Enemy>().has::<TerminalTransform>(),
                         Query::new().has::<EnemyMovement>(),
-                        Query::new()
-                            .has::<Enemy>()
-                            .has_where::<TerminalTransform>(|transform| {
-                                transform.coords.x() <= ENEMY_MIN_X as i64
-                            }),
-                        Query::new()
-                            .has::<Enemy>()
-                            .has_where::<TerminalTransform>(|transform| {
-                                transform.coords.x() >= ENEMY_MAX_X as i64
-                            }),
+                        Query::new().has::<GameRng>(),
+                        Query::new().has_where::<GameState>(|state| state.is_playing()),
+                        Query::new().has_where::<Animation>(|animation| {
+                            animation.timing == AnimationTiming::FormationStep
+                        }),
+                        Query::new().has::<GameEvents>(),
+                        Query::new().has::<Settings>(),
+                        Query::new().has::<Levels>(),
+                        Query::new().has::<Scorekeeper>(),
                     ],
                     movement,
                 ),
             ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<DiveLauncher>(),
+                        Query::new().has::<Enemy>().has::<TerminalTransform>(),
+                        Query::new().has::<Player>().has::<TerminalTransform>(),
+                        Query::new().has::<GameRng>(),
+                        Query::new().has_where::<GameState>(|state| state.is_playing()),
+                        Query::new().has::<Settings>(),
+                    ],
+                    launch_dives,
+                ),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<Enemy>().has::<TerminalTransform>(),
+                        Query::new().has::<Player>().has::<TerminalTransform>(),
+                        Query::new().has_where::<GameState>(|state| state.is_playing()),
+                    ],
+                    dive,
+                ),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has_where::<TerminalCollision>(|collision| {
+                            collision
+                                .is_collision_between(ENEMY_COLLISION_LAYER, PLAYER_COLLISION_LAYER)
+                        }),
+                        Query::new().has::<Enemy>().has::<TerminalTransform>(),
+                        Query::new().has::<Sprite>(),
+                        Query::new().has::<SpriteCell>(),
+                        Query::new().has::<Player>().has::<TerminalTransform>(),
+                        Query::new().has::<GameEvents>(),
+                    ],
+                    diving_enemy_hits_player_collisions,
+                ),
+            ),
         ]
     }
 }
 
-fn spawn_enemies(results: Vec<QueryResultList>, commands: GameCommandsArg) {
-    if let [enemy_results, bullet_results, scorekeeper_results, ..] = &results[..] {
-        if enemy_results.len() == 0 {
-            for row in 0..ENEMY_GRID_HEIGHT {
-                for col in 0..ENEMY_GRID_WIDTH {
-                    commands
-                        .borrow_mut()
-                        .issue(GameCommand::AddEntity(make_enemy(IntCoords2d::new(
-                            get_enemy_x_pos(SCREEN_WIDTH, ENEMY_PADDING, ENEMY_GRID_WIDTH, col)
-                                as i64,
-                            row as i64,
-                        ))));
-                }
+fn clear_wave(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [enemy_results, boss_results, playing_state_results, scorekeeper_results, stats_results, bonuses_results, game_events_results, localization_results, ..] =
+        &results[..]
+    {
+        if playing_state_results.is_empty() || !enemy_results.is_empty() || !boss_results.is_empty()
+        {
+            return;
+        }
+
+        let mut game_state = playing_state_results.get_only_mut::<GameState>();
+        let mut scorekeeper = scorekeeper_results.get_only_mut::<Scorekeeper>();
+
+        // Survival streams its rows in rather than spawning waves, and a run that's only just started hasn't
+        // cleared one yet.
+        if game_state.mode == GameMode::Survival || scorekeeper.level == 0 {
+            return;
+        }
+
+        let wave_stats = stats_results.get_only_mut::<Stats>().finish_wave();
+
+        game_events_results
+            .get_only_mut::<GameEvents>()
+            .emit(GameEvent::WaveCleared {
+                level: scorekeeper.level,
+                time_millis: *wave_stats.wave_times_millis.last().unwrap_or(&0),
+                accuracy: wave_stats.accuracy(),
+            });
+
+        let wave_bonus = bonuses_results
+            .get_only::<Bonuses>()
+            .wave_bonus(&wave_stats);
+
+        scorekeeper.add_points(wave_bonus.total());
+
+        let localization = localization_results.get_only::<Localization>();
+
+        for card_line in make_summary_card(
+            &localization
+                .current()
+                .format("summary.wave_cleared", &[("level", &scorekeeper.level)]),
+            &wave_stats,
+            *wave_stats.wave_times_millis.last().unwrap_or(&0),
+            Some(&wave_bonus),
+            localization.current(),
+        ) {
+            commands
+                .borrow_mut()
+                .issue(GameCommand::AddEntity(card_line));
+        }
+
+        game_state.enter(GamePhase::WaveSummary);
+    }
+}
+
+/// Clears the bullets off an empty board and decides when the next wave is due.
+fn advance_waves(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [enemy_results, boss_results, bullet_results, game_state_results, campaign_run_results, wave_spawner_results, ..] =
+        &results[..]
+    {
+        let game_state = game_state_results.get_only::<GameState>();
+
+        if game_state.mode == GameMode::Survival
+            || !enemy_results.is_empty()
+            || !boss_results.is_empty()
+        {
+            return;
+        }
+
+        for bullet_result in bullet_results {
+            commands
+                .borrow_mut()
+                .issue(GameCommand::DestroyEntity(*bullet_result.entity()));
+        }
+
+        let is_next_wave_due = match game_state.phase {
+            // Only a run that's just started, since clearing a wave once it's under way shows the summary first.
+            GamePhase::Playing => true,
+            GamePhase::WaveSummary => {
+                game_state.phase_timer.elapsed_millis() >= WAVE_SUMMARY_WAIT_TIME
+            }
+            GamePhase::Interlude => {
+                campaign_run_results
+                    .get_only::<CampaignRun>()
+                    .is_interlude_over
+            }
+            GamePhase::Menu
+            | GamePhase::GameOver
+            | GamePhase::Achievements
+            | GamePhase::Editor
+            | GamePhase::RunComplete => false,
+        };
+
+        wave_spawner_results
+            .get_only_mut::<WaveSpawner>()
+            .is_next_wave_due = is_next_wave_due;
+    }
+}
+
+/// Each campaign wave's story is told before it starts, and the epilogue before the campaign's finished.
+fn start_interlude(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [wave_spawner_results, game_state_results, scorekeeper_results, campaign_run_results, ..] =
+        &results[..]
+    {
+        if wave_spawner_results.is_empty() {
+            return;
+        }
+
+        let mut game_state = game_state_results.get_only_mut::<GameState>();
+        let mut campaign_run = campaign_run_results.get_only_mut::<CampaignRun>();
+        let next_level = scorekeeper_results.get_only::<Scorekeeper>().level + 1;
+
+        if game_state.mode == GameMode::Campaign
+            && campaign_run.interlude_wave != next_level
+            && !campaign_run.interlude(next_level).is_empty()
+        {
+            campaign_run.interlude_wave = next_level;
+            campaign_run.is_interlude_over = false;
+
+            wave_spawner_results
+                .get_only_mut::<WaveSpawner>()
+                .is_next_wave_due = false;
+            game_state.enter(GamePhase::Interlude);
+        }
+    }
+}
+
+/// Ends a campaign that's run out of waves, or a time attack that's cleared its last one.
+fn complete_run(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [wave_spawner_results, game_state_results, scorekeeper_results, stats_results, game_events_results, localization_results, campaign_run_results, ..] =
+        &results[..]
+    {
+        if wave_spawner_results.is_empty() {
+            return;
+        }
+
+        let mut game_state = game_state_results.get_only_mut::<GameState>();
+        let scorekeeper = scorekeeper_results.get_only::<Scorekeeper>();
+        let stats = stats_results.get_only::<Stats>();
+        let localization = localization_results.get_only::<Localization>();
+        let campaign_run = campaign_run_results.get_only::<CampaignRun>();
+        let next_level = scorekeeper.level + 1;
+
+        let (event, title) = match game_state.mode {
+            GameMode::Campaign if campaign_run.campaign.wave(next_level).is_none() => (
+                GameEvent::CampaignCompleted {
+                    score: scorekeeper.score,
+                    time_millis: stats.run_time_millis(),
+                },
+                localization.current().format(
+                    "summary.campaign_complete",
+                    &[("name", &campaign_run.campaign.name)],
+                ),
+            ),
+            GameMode::TimeAttack if next_level > TIME_ATTACK_WAVES => (
+                GameEvent::TimeAttackCompleted {
+                    score: scorekeeper.score,
+                    time_millis: stats.run_time_millis(),
+                },
+                String::from(localization.current().text("summary.time_attack_complete")),
+            ),
+            _ => return,
+        };
+
+        game_events_results.get_only_mut::<GameEvents>().emit(event);
+
+        for card_line in make_summary_card(
+            &title,
+            &stats.run,
+            stats.run_time_millis(),
+            None,
+            localization.current(),
+        ) {
+            commands
+                .borrow_mut()
+                .issue(GameCommand::AddEntity(card_line));
+        }
+
+        wave_spawner_results
+            .get_only_mut::<WaveSpawner>()
+            .is_next_wave_due = false;
+        game_state.enter(GamePhase::RunComplete);
+    }
+}
+
+fn spawn_wave(results: Vec<QueryResultList>, commands: GameCommandsArg, use_sprites: bool) {
+    if let [wave_spawner_results, game_state_results, scorekeeper_results, stats_results, game_rng_results, game_events_results, levels_results, bunker_results, campaign_run_results, ..] =
+        &results[..]
+    {
+        if wave_spawner_results.is_empty() {
+            return;
+        }
+
+        let mut game_state = game_state_results.get_only_mut::<GameState>();
+        let mut scorekeeper = scorekeeper_results.get_only_mut::<Scorekeeper>();
+        let mut game_events = game_events_results.get_only_mut::<GameEvents>();
+        let next_level = scorekeeper.level + 1;
+
+        if scorekeeper.level == 0 {
+            game_events.emit(GameEvent::RunStarted {
+                seed: game_rng_results.get_only::<GameRng>().seed,
+            });
+        }
+
+        let boss_hp = get_boss_hp(
+            game_state.mode,
+            next_level,
+            &campaign_run_results.get_only::<CampaignRun>(),
+        );
+
+        if let Some(boss_hp) = boss_hp {
+            for boss_entity in make_boss(
+                IntCoords2d::new(
+                    get_enemy_x_pos(SCREEN_WIDTH, 0, 1, 0) as i64,
+                    ENEMY_GRID_TOP_ROW as i64,
+                ),
+                Boss::new(boss_hp),
+            ) {
+                commands
+                    .borrow_mut()
+                    .issue(GameCommand::AddEntity(boss_entity));
+            }
+        } else {
+            // Bunkers are rebuilt for every formation, but boss waves are fought over what's left of them.
+            for bunker_result in bunker_results {
+                commands
+                    .borrow_mut()
+                    .issue(GameCommand::DestroyEntity(*bunker_result.entity()));
+            }
+
+            spawn_formation(
+                levels_results.get_only::<Levels>().for_wave(next_level),
+                use_sprites,
+                &commands,
+            );
+        }
+
+        scorekeeper.level = next_level;
+
+        game_events.emit(GameEvent::WaveStarted {
+            level: next_level,
+            is_boss_wave: boss_hp.is_some(),
+        });
+
+        wave_spawner_results
+            .get_only_mut::<WaveSpawner>()
+            .is_next_wave_due = false;
+        stats_results.get_only_mut::<Stats>().start_wave();
+        game_state.enter(GamePhase::Playing);
+    }
+}
+
+/// The boss's hp if the wave is a boss wave. A campaign says which of its waves are, and every other mode has one
+/// every few waves that gets tougher each time.
+fn get_boss_hp(mode: GameMode, level: u64, campaign_run: &CampaignRun) -> Option<u64> {
+    match mode {
+        GameMode::Campaign => campaign_run
+            .campaign
+            .wave(level)
+            .and_then(|wave| wave.boss_hp),
+        GameMode::Classic
+        | GameMode::PlayTest
+        | GameMode::Survival
+        | GameMode::TimeAttack
+        | GameMode::Daily => level.is_multiple_of(BOSS_WAVE_INTERVAL).then(|| {
+            let appearance = level / BOSS_WAVE_INTERVAL;
+
+            BOSS_BASE_HP + BOSS_HP_PER_APPEARANCE * (appearance - 1)
+        }),
+    }
+}
+
+fn spawn_formation(level: &Level, use_sprites: bool, commands: &GameCommandsArg) {
+    let (padding, row_height) = if use_sprites {
+        (ENEMY_SPRITE_PADDING, ENEMY_SPRITE_ROW_HEIGHT)
+    } else {
+        (ENEMY_PADDING, 1)
+    };
+    let columns = level.columns() as u64;
+
+    for (row, cells) in level.invader_rows.iter().enumerate() {
+        for (col, cell) in cells.iter().enumerate() {
+            let LevelCell::Enemy(kind) = *cell else {
+                continue;
+            };
+
+            for enemy_entity in make_enemy(
+                IntCoords2d::new(
+                    get_enemy_x_pos(SCREEN_WIDTH, padding, columns, col as u64) as i64,
+                    (ENEMY_GRID_TOP_ROW + row as u64 * row_height) as i64,
+                ),
+                kind,
+                use_sprites,
+            ) {
+                commands
+                    .borrow_mut()
+                    .issue(GameCommand::AddEntity(enemy_entity));
+            }
+        }
+    }
+
+    let bunker_rows = level.bunker_rows.len() as u64;
+
+    for (row, cells) in level.bunker_rows.iter().enumerate() {
+        let y = BUNKER_BOTTOM_ROW + 1 + row as u64 - bunker_rows;
+
+        for (col, cell) in cells.iter().enumerate() {
+            if *cell != LevelCell::Bunker {
+                continue;
             }
 
-            for bullet_result in bullet_results {
+            // Each block fills the width of its column, so neighbouring blocks join up.
+            let x = get_enemy_x_pos(SCREEN_WIDTH, padding, columns, col as u64) - padding / 2;
+
+            for offset in 0..=padding {
                 commands
                     .borrow_mut()
-                    .issue(GameCommand::DestroyEntity(*bullet_result.entity()));
+                    .issue(GameCommand::AddEntity(make_bunker(
+                        IntCoords2d::new((x + offset) as i64, y as i64),
+                        BUNKER_MAX_HP,
+                    )));
             }
+        }
+    }
+}
 
-            scorekeeper_results.get_only_mut::<Scorekeeper>().level += 1;
+fn stream_rows(results: Vec<QueryResultList>, commands: GameCommandsArg, use_sprites: bool) {
+    if let [enemy_results, formation_stream_results, survival_state_results, scorekeeper_results, stats_results, game_rng_results, game_events_results, player_results, levels_results, bunker_results, ..] =
+        &results[..]
+    {
+        if survival_state_results.is_empty() {
+            return;
+        }
+
+        let mut formation_stream = formation_stream_results.get_only_mut::<FormationStream>();
+        let mut scorekeeper = scorekeeper_results.get_only_mut::<Scorekeeper>();
+        let mut stats = stats_results.get_only_mut::<Stats>();
+        let mut game_rng = game_rng_results.get_only_mut::<GameRng>();
+        let mut game_events = game_events_results.get_only_mut::<GameEvents>();
+
+        let (padding, row_height) = if use_sprites {
+            (ENEMY_SPRITE_PADDING, ENEMY_SPRITE_ROW_HEIGHT)
+        } else {
+            (ENEMY_PADDING, 1)
+        };
+
+        if scorekeeper.level == 0 {
+            // The rows come in over the level's bunkers.
+            for bunker_result in bunker_results {
+                commands
+                    .borrow_mut()
+                    .issue(GameCommand::DestroyEntity(*bunker_result.entity()));
+            }
+
+            let level = levels_results.get_only::<Levels>().for_wave(1).clone();
+
+            spawn_formation(
+                &Level {
+                    invader_rows: vec![],
+                    ..level
+                },
+                use_sprites,
+                &commands,
+            );
+
+            for row in 0..SURVIVAL_STARTING_ROWS {
+                spawn_stream_row(
+                    ENEMY_GRID_TOP_ROW + row * row_height,
+                    None,
+                    padding,
+                    use_sprites,
+                    &mut game_rng,
+                    &commands,
+                );
+            }
+
+            *formation_stream = FormationStream {
+                descend_timer: Timer::start_new(),
+                rows_spawned: SURVIVAL_STARTING_ROWS,
+                seconds_scored: 0,
+            };
+            scorekeeper.level = 1;
+
+            game_events.emit(GameEvent::WaveStarted {
+                level: 1,
+                is_boss_wave: false,
+            });
+
+            stats.start_wave();
+            return;
+        }
+
+        let seconds_survived = (stats.run_time_millis() / 1000) as u64;
+
+        if seconds_survived > formation_stream.seconds_scored {
+            scorekeeper.add_points(
+                (seconds_survived - formation_stream.seconds_scored) * SURVIVAL_POINTS_PER_SECOND,
+            );
+
+            formation_stream.seconds_scored = seconds_survived;
+        }
+
+        let descend_wait_millis = SURVIVAL_START_DESCEND_WAIT_TIME
+            .saturating_sub(SURVIVAL_DESCEND_SPEEDUP_PER_STAGE * (scorekeeper.level - 1) as u128)
+            .max(SURVIVAL_MIN_DESCEND_WAIT_TIME);
+
+        // A formation that's been wiped out is replaced straight away.
+        if !enemy_results.is_empty()
+            && formation_stream.descend_timer.elapsed_millis() < descend_wait_millis
+        {
+            return;
+        }
+
+        let mut has_landed = false;
+
+        for enemy_result in enemy_results {
+            let mut transform = enemy_result.components().get_mut::<TerminalTransform>();
+
+            // A diver's slot comes down with the rest of the formation.
+            if let Some(dive) = enemy_result.components().get_mut::<Enemy>().dive.as_mut() {
+                dive.slot += IntCoords2d::new(0, row_height as i64);
+                continue;
+            }
+
+            transform.coords += IntCoords2d::new(0, row_height as i64);
+
+            has_landed |= transform.coords.y() + row_height as i64 > INVASION_ROW as i64;
+
+            // Invaders crush any bunker they come down on.
+            for bunker_result in bunker_results {
+                if bunker_result.components().get::<TerminalTransform>().coords == transform.coords
+                {
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::DestroyEntity(*bunker_result.entity()));
+                }
+            }
+        }
+
+        // Landing ends the run however many lives are left.
+        if has_landed {
+            player_results.get_only_mut::<Player>().lives = 0;
+            return;
+        }
+
+        spawn_stream_row(
+            ENEMY_GRID_TOP_ROW,
+            enemy_results
+                .iter()
+                .map(|enemy_result| {
+                    enemy_result
+                        .components()
+                        .get::<Enemy>()
+                        .formation_coords(
+                            enemy_result.components().get::<TerminalTransform>().coords,
+                        )
+                        .x()
+                })
+                .min(),
+            padding,
+            use_sprites,
+            &mut game_rng,
+            &commands,
+        );
+
+        formation_stream.rows_spawned += 1;
+        formation_stream.descend_timer.restart();
+
+        if formation_stream.rows_spawned % SURVIVAL_ROWS_PER_STAGE == 0 {
+            scorekeeper.level += 1;
+
+            game_events.emit(GameEvent::WaveStarted {
+                level: scorekeeper.level,
+                is_boss_wave: false,
+            });
+        }
+    }
+}
+
+/// Spawns a row of random invaders. It lines up with the columns of the formation, if there's one on screen, whose
+/// leftmost invader is at `formation_x`.
+fn spawn_stream_row(
+    y: u64,
+    formation_x: Option<i64>,
+    padding: u64,
+    use_sprites: bool,
+    game_rng: &mut GameRng,
+    commands: &GameCommandsArg,
+) {
+    let spacing = (padding + 1) as i64;
+    let centred_x = get_enemy_x_pos(SCREEN_WIDTH, padding, SURVIVAL_ROW_COLUMNS, 0) as i64;
+    let mut left_x = centred_x
+        + formation_x
+            .map(|x| (x - centred_x).rem_euclid(spacing))
+            .unwrap_or(0);
+
+    while left_x + (SURVIVAL_ROW_COLUMNS as i64 - 1) * spacing > ENEMY_MAX_X as i64 {
+        left_x -= spacing;
+    }
+
+    for col in 0..SURVIVAL_ROW_COLUMNS as i64 {
+        if game_rng.rng.gen_range(1..=100) <= SURVIVAL_GAP_CHANCE {
+            continue;
+        }
+
+        let kind = [EnemyKind::Squid, EnemyKind::Crab, EnemyKind::Octopus]
+            .choose(&mut game_rng.rng)
+            .copied()
+            .unwrap_or(EnemyKind::Octopus);
+
+        for enemy_entity in make_enemy(
+            IntCoords2d::new(left_x + col * spacing, y as i64),
+            kind,
+            use_sprites,
+        ) {
+            commands
+                .borrow_mut()
+                .issue(GameCommand::AddEntity(enemy_entity));
         }
     }
 }
@@ -110,19 +806,55 @@ fn get_enemy_x_pos(total_area_width: u64, padding: u64, num_enemies_in_row: u64,
 }
 
 fn shoot(results: Vec<QueryResultList>, commands: GameCommandsArg) {
-    if let [enemy_results, ..] = &results[..] {
+    if let [enemy_results, game_rng_results, playing_state_results, enemy_sprite_results, settings_results, levels_results, scorekeeper_results, ..] =
+        &results[..]
+    {
+        if playing_state_results.is_empty() {
+            return;
+        }
+
+        let mut game_rng = game_rng_results.get_only_mut::<GameRng>();
+        let shoot_chance = levels_results
+            .get_only::<Levels>()
+            .for_wave(scorekeeper_results.get_only::<Scorekeeper>().level)
+            .shoot_chance
+            .unwrap_or_else(|| {
+                settings_results
+                    .get_only::<Settings>()
+                    .difficulty
+                    .enemy_shoot_chance()
+            });
+
         for enemy_result in enemy_results {
             let mut enemy = enemy_result.components().get_mut::<Enemy>();
             let transform = enemy_result.components().get::<TerminalTransform>();
+            let muzzle_offset = enemy_sprite_results
+                .iter()
+                .find(|enemy_sprite_result| enemy_sprite_result.entity() == enemy_result.entity())
+                .map(|enemy_sprite_result| {
+                    IntCoords2d::new(0, enemy_sprite_result.components().get::<Sprite>().height())
+                })
+                .unwrap_or(IntCoords2d::up());
 
             if enemy.shoot_timer.elapsed_millis() >= ENEMY_SHOOT_WAIT_TIME {
-                let shoot_roll: u8 = thread_rng().gen_range(1..=100);
+                let shoot_roll: u8 = game_rng.rng.gen_range(1..=100);
+                let is_attacking = enemy
+                    .dive
+                    .as_ref()
+                    .is_some_and(|dive| dive.stage == DiveStage::Attack);
 
-                if shoot_roll <= ENEMY_SHOOT_CHANCE {
+                // Divers fire on their way down.
+                if shoot_roll
+                    <= if is_attacking {
+                        shoot_chance.saturating_mul(DIVE_SHOOT_CHANCE_MULTIPLIER)
+                    } else {
+                        shoot_chance
+                    }
+                {
                     commands
                         .borrow_mut()
                         .issue(GameCommand::AddEntity(make_bullet(
-                            transform.coords + IntCoords2d::up(),
+                            transform.coords + muzzle_offset,
                             IntCoords2d::up(),
                             BulletType::Enemy,
                         )));
@@ -135,31 +867,401 @@ fn shoot(results: Vec<QueryResultList>, commands: GameCommandsArg) {
 }
 
 fn movement(results: Vec<QueryResultList>, _: GameCommandsArg) {
-    if let [enemy_results, enemy_movement_results, enemies_at_min_x_results, enemies_at_max_x_results, ..] =
+    if let [enemy_results, enemy_movement_results, game_rng_results, playing_state_results, formation_animation_results, game_events_results, settings_results, levels_results, scorekeeper_results, ..] =
         &results[..]
     {
+        if playing_state_results.is_empty() {
+            return;
+        }
+
         let mut enemy_movement = enemy_movement_results.get_only_mut::<EnemyMovement>();
+        let levels = levels_results.get_only::<Levels>();
+        let level = levels.for_wave(scorekeeper_results.get_only::<Scorekeeper>().level);
+
+        let move_wait_millis = level.move_wait_millis.unwrap_or_else(|| {
+            settings_results
+                .get_only::<Settings>()
+                .difficulty
+                .enemy_move_wait_millis()
+        });
+
+        if enemy_movement.move_timer.elapsed_millis() >= move_wait_millis {
+            let formation_xs: Vec<i64> = enemy_results
+                .iter()
+                .map(|enemy_result| {
+                    enemy_result
+                        .components()
+                        .get::<Enemy>()
+                        .formation_coords(
+                            enemy_result.components().get::<TerminalTransform>().coords,
+                        )
+                        .x()
+                })
+                .collect();
 
-        if enemy_movement.move_timer.elapsed_millis() >= ENEMY_MOVE_WAIT_TIME {
-            let direction = if enemies_at_min_x_results.len() > 0 {
-                IntCoords2d::right()
-            } else if enemies_at_max_x_results.len() > 0 {
-                IntCoords2d::left()
-            } else {
-                let direction_roll: u8 = thread_rng().gen_range(0..=1);
-                match direction_roll {
-                    0 => IntCoords2d::left(),
-                    _ => IntCoords2d::right(),
+            let direction = match get_edge_direction(&formation_xs) {
+                Some(direction) => direction,
+                None if level.march_rule == MarchRule::Sweep => enemy_movement.direction,
+                None => {
+                    let direction_roll: u8 = game_rng_results
+                        .get_only_mut::<GameRng>()
+                        .rng
+                        .gen_range(0..=1);
+                    match direction_roll {
+                        0 => IntCoords2d::left(),
+                        _ => IntCoords2d::right(),
+                    }
                 }
             };
 
+            enemy_movement.direction = direction;
+
             for enemy_result in enemy_results {
                 let mut transform = enemy_result.components().get_mut::<TerminalTransform>();
 
-                transform.coords += direction;
+                match enemy_result.components().get_mut::<Enemy>().dive.as_mut() {
+                    Some(dive) => dive.slot += direction,
+                    None => transform.coords += direction,
+                }
+            }
+
+            for formation_animation_result in formation_animation_results {
+                formation_animation_result
+                    .components()
+                    .get_mut::<Animation>()
+                    .advance();
+            }
+
+            if !enemy_results.is_empty() {
+                game_events_results
+                    .get_only_mut::<GameEvents>()
+                    .emit(GameEvent::FormationStepped);
             }
 
             enemy_movement.move_timer.restart();
         }
     }
 }
+
+/// Which way the formation has to step to stay on screen, if it's reached an edge. Divers count by their slots, so
+/// the place a diver returns to never leaves the screen either.
+fn get_edge_direction(formation_xs: &[i64]) -> Option<IntCoords2d> {
+    if formation_xs.iter().any(|x| *x <= ENEMY_MIN_X as i64) {
+        Some(IntCoords2d::right())
+    } else if formation_xs.iter().any(|x| *x >= ENEMY_MAX_X as i64) {
+        Some(IntCoords2d::left())
+    } else {
+        None
+    }
+}
+
+fn launch_dives(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [launcher_results, enemy_results, player_results, game_rng_results, playing_state_results, settings_results, ..] =
+        &results[..]
+    {
+        let mut launcher = launcher_results.get_only_mut::<DiveLauncher>();
+
+        if playing_state_results.is_empty() || enemy_results.is_empty() {
+            launcher.launch_timer.restart();
+            return;
+        }
+
+        let dive_wait_millis = settings_results
+            .get_only::<Settings>()
+            .difficulty
+            .enemy_dive_wait_millis();
+
+        if launcher.launch_timer.elapsed_millis() < dive_wait_millis {
+            return;
+        }
+
+        launcher.launch_timer.restart();
+
+        let mut game_rng = game_rng_results.get_only_mut::<GameRng>();
+        let in_formation: Vec<(&QueryResult, IntCoords2d)> = enemy_results
+            .iter()
+            .filter(|enemy_result| !enemy_result.components().get::<Enemy>().is_diving())
+            .map(|enemy_result| {
+                (
+                    enemy_result,
+                    enemy_result.components().get::<TerminalTransform>().coords,
+                )
+            })
+            .collect();
+
+        let Some(&(_, leader_coords)) = in_formation.choose(&mut game_rng.rng) else {
+            return;
+        };
+
+        // The leader's wingmen are its nearest neighbours in its row, so the group dives in formation.
+        let mut group: Vec<&(&QueryResult, IntCoords2d)> = in_formation
+            .iter()
+            .filter(|(_, coords)| coords.y() == leader_coords.y())
+            .collect();
+
+        group.sort_by_key(|(_, coords)| (coords.x() - leader_coords.x()).abs());
+        group.truncate(game_rng.rng.gen_range(1..=DIVE_MAX_GROUP_SIZE));
+
+        let sweep = player_results.get_only::<TerminalTransform>().coords.x() - leader_coords.x();
+        let swing = if game_rng.rng.gen_bool(0.5) { -1 } else { 1 };
+        let is_exiting = game_rng.rng.gen_bool(DIVE_EXIT_CHANCE);
+
+        for (enemy_result, coords) in group {
+            enemy_result.components().get_mut::<Enemy>().dive = Some(Dive {
+                slot: *coords,
+                from: *coords,
+                sweep,
+                swing,
+                is_exiting,
+                stage: DiveStage::Attack,
+                stage_timer: PausableTimer::start_new(),
+            });
+        }
+    }
+}
+
+fn dive(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [enemy_results, player_results, playing_state_results, ..] = &results[..] {
+        if playing_state_results.is_empty() {
+            for enemy_result in enemy_results {
+                if let Some(dive) = enemy_result.components().get_mut::<Enemy>().dive.as_mut() {
+                    dive.stage_timer.pause();
+                }
+            }
+
+            return;
+        }
+
+        let player_y = player_results.get_only::<TerminalTransform>().coords.y();
+
+        for enemy_result in enemy_results {
+            let mut enemy = enemy_result.components().get_mut::<Enemy>();
+            let mut transform = enemy_result.components().get_mut::<TerminalTransform>();
+            let Some(dive) = enemy.dive.as_mut() else {
+                continue;
+            };
+
+            dive.stage_timer.resume();
+
+            let has_returned = match dive.stage {
+                DiveStage::Attack => {
+                    let progress = get_dive_progress(&dive.stage_timer, DIVE_ATTACK_TIME);
+                    let to = IntCoords2d::new(
+                        dive.from.x() + dive.sweep,
+                        if dive.is_exiting {
+                            SCREEN_HEIGHT as i64
+                        } else {
+                            player_y - DIVE_PULL_UP_ROWS
+                        },
+                    );
+
+                    transform.coords = get_dive_coords(dive.from, to, dive.swing, progress);
+
+                    if progress >= 1.0 {
+                        dive.from = if dive.is_exiting {
+                            IntCoords2d::new(transform.coords.x(), DIVE_REENTRY_ROW)
+                        } else {
+                            transform.coords
+                        };
+                        dive.stage = DiveStage::Return;
+                        dive.stage_timer.restart();
+                    }
+
+                    false
+                }
+                DiveStage::Return => {
+                    let progress = get_dive_progress(&dive.stage_timer, DIVE_RETURN_TIME);
+
+                    // Bowing out the other way on the way back closes the loop.
+                    transform.coords = get_dive_coords(dive.from, dive.slot, -dive.swing, progress);
+
+                    progress >= 1.0
+                }
+            };
+
+            if has_returned {
+                enemy.dive = None;
+            }
+        }
+    }
+}
+
+/// How far through a stage of a dive that takes `stage_millis` the invader is, from 0 to 1.
+fn get_dive_progress(stage_timer: &PausableTimer, stage_millis: u128) -> f64 {
+    (stage_timer.elapsed_millis() as f64 / stage_millis as f64).min(1.0)
+}
+
+/// The point `progress` of the way along a path from `from` to `to` that bows out towards `swing` in the middle.
+fn get_dive_coords(from: IntCoords2d, to: IntCoords2d, swing: i64, progress: f64) -> IntCoords2d {
+    let bow = swing as f64 * DIVE_SWING_WIDTH * (progress * std::f64::consts::PI).sin();
+    let x = from.x() as f64 + (to.x() - from.x()) as f64 * progress + bow;
+    let y = from.y() as f64 + (to.y() - from.y()) as f64 * progress;
+
+    IntCoords2d::new(
+        (x.round() as i64).clamp(ENEMY_MIN_X as i64, ENEMY_MAX_X as i64),
+        y.round() as i64,
+    )
+}
+
+/// A diver that runs into the player crashes, costing the player a life.
+fn diving_enemy_hits_player_collisions(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [collision_results, enemy_results, sprite_results, sprite_cell_results, player_results, game_events_results, ..] =
+        &results[..]
+    {
+        let mut lives_left = player_results.get_only::<Player>().lives;
+        let player_coords = player_results.get_only::<TerminalTransform>().coords;
+        let mut game_events = game_events_results.get_only_mut::<GameEvents>();
+        let mut crashed_enemies = vec![];
+
+        for collision_result in collision_results {
+            let collision = collision_result.components().get::<TerminalCollision>();
+
+            let Some(&(enemy_entity, _)) = collision
+                .bodies
+                .iter()
+                .find(|(_, collider)| collider.layer == ENEMY_COLLISION_LAYER)
+            else {
+                continue;
+            };
+
+            let enemy_entity = get_sprite_owner(enemy_entity, sprite_results, sprite_cell_results);
+
+            // Every cell of a sprite can hit at once, but it only crashes once.
+            if crashed_enemies.contains(&enemy_entity) {
+                continue;
+            }
+
+            let Some(enemy_result) = enemy_results.iter().find(|enemy_result| {
+                *enemy_result.entity() == enemy_entity
+                    && enemy_result.components().get::<Enemy>().is_diving()
+            }) else {
+                continue;
+            };
+
+            crashed_enemies.push(enemy_entity);
+
+            commands
+                .borrow_mut()
+                .issue(GameCommand::DestroyEntity(enemy_entity));
+
+            game_events.emit(GameEvent::EnemyDestroyed {
+                kind: enemy_result.components().get::<Enemy>().kind,
+                coords: enemy_result.components().get::<TerminalTransform>().coords,
+                by: DestroyedBy::Collision,
+            });
+
+            if lives_left > 0 {
+                lives_left -= 1;
+
+                game_events.emit(GameEvent::PlayerHit {
+                    coords: player_coords,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diver(coords: IntCoords2d, slot: IntCoords2d) -> (Enemy, IntCoords2d) {
+        let enemy = Enemy {
+            shoot_timer: Timer::new(),
+            kind: EnemyKind::ALL[0],
+            dive: Some(Dive {
+                slot,
+                from: coords,
+                sweep: 0,
+                swing: 1,
+                is_exiting: false,
+                stage: DiveStage::Return,
+                stage_timer: PausableTimer::new(),
+            }),
+        };
+
+        (enemy, coords)
+    }
+
+    fn in_formation(coords: IntCoords2d) -> (Enemy, IntCoords2d) {
+        let enemy = Enemy {
+            shoot_timer: Timer::new(),
+            kind: EnemyKind::ALL[0],
+            dive: None,
+        };
+
+        (enemy, coords)
+    }
+
+    fn formation_xs(enemies: &[(Enemy, IntCoords2d)]) -> Vec<i64> {
+        enemies
+            .iter()
+            .map(|(enemy, coords)| enemy.formation_coords(*coords).x())
+            .collect()
+    }
+
+    #[test]
+    fn turns_away_from_the_edges() {
+        assert_eq!(
+            get_edge_direction(&[ENEMY_MIN_X as i64, 20]),
+            Some(IntCoords2d::right())
+        );
+        assert_eq!(
+            get_edge_direction(&[20, ENEMY_MAX_X as i64]),
+            Some(IntCoords2d::left())
+        );
+        assert_eq!(get_edge_direction(&[20, 30]), None);
+    }
+
+    #[test]
+    fn a_divers_slot_turns_the_formation() {
+        // The diver itself is far from the edge, but the slot it's heading back to is on it.
+        let enemies = [
+            in_formation(IntCoords2d::new(20, 4)),
+            diver(
+                IntCoords2d::new(20, 15),
+                IntCoords2d::new(ENEMY_MAX_X as i64, 4),
+            ),
+        ];
+
+        assert_eq!(
+            get_edge_direction(&formation_xs(&enemies)),
+            Some(IntCoords2d::left())
+        );
+    }
+
+    #[test]
+    fn a_diver_returns_in_line_while_the_formation_is_at_an_edge() {
+        let mut enemies = [
+            in_formation(IntCoords2d::new(ENEMY_MAX_X as i64 - 6, 4)),
+            diver(
+                IntCoords2d::new(20, 15),
+                IntCoords2d::new(ENEMY_MAX_X as i64 - 3, 4),
+            ),
+        ];
+
+        // A sweeping formation marching right, stepping as movement does, until it's turned at the edge.
+        let mut direction = IntCoords2d::right();
+
+        for _ in 0..10 {
+            direction = get_edge_direction(&formation_xs(&enemies)).unwrap_or(direction);
+
+            for (enemy, coords) in enemies.iter_mut() {
+                match enemy.dive.as_mut() {
+                    Some(dive) => dive.slot += direction,
+                    None => *coords += direction,
+                }
+            }
+        }
+
+        let (enemy, coords) = &enemies[1];
+        let dive = enemy.dive.as_ref().unwrap();
+
+        assert!(dive.slot.x() <= ENEMY_MAX_X as i64);
+        assert_eq!(
+            get_dive_coords(*coords, dive.slot, -dive.swing, 1.0),
+            dive.slot
+        );
+        assert_eq!(dive.slot.x() - enemies[0].1.x(), 3);
+    }
+}
@@ -0,0 +1,94 @@
+use thomas::{
+    GameCommand, GameCommandsArg, Query, QueryResultList, System, SystemsGenerator,
+    EVENT_AFTER_UPDATE, EVENT_INIT, EVENT_UPDATE,
+};
+
+use crate::{
+    load_key_values, save_key_values, GameEvent, GameEvents, GameState, HighScore, HighScores,
+    Scorekeeper, TIME_ATTACK_WAVES,
+};
+
+const HIGH_SCORES_FILE_NAME: &str = "high_scores.txt";
+
+/// Keeps the tables of best runs, which carry over between sessions.
+pub struct HighScoresSystemsGenerator {}
+impl SystemsGenerator for HighScoresSystemsGenerator {
+    fn generate(&self) -> Vec<(&'static str, System)> {
+        vec![
+            (
+                EVENT_INIT,
+                System::new(vec![], |_, commands| {
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(
+                            HighScores::from_saved(
+                                &load_key_values(HIGH_SCORES_FILE_NAME).unwrap_or_default(),
+                            ),
+                        )]));
+                }),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<HighScores>(),
+                        Query::new().has::<Scorekeeper>(),
+                    ],
+                    |results, _| {
+                        if let [high_scores_results, scorekeeper_results, ..] = &results[..] {
+                            let mut scorekeeper = scorekeeper_results.get_only_mut::<Scorekeeper>();
+
+                            if let Some(best) = high_scores_results.get_only::<HighScores>().best()
+                            {
+                                scorekeeper.high_score = u64::max(scorekeeper.high_score, best);
+                            }
+                        }
+                    },
+                ),
+            ),
+            (
+                EVENT_AFTER_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<GameEvents>(),
+                        Query::new().has::<HighScores>(),
+                        Query::new().has::<GameState>(),
+                    ],
+                    record_high_scores,
+                ),
+            ),
+        ]
+    }
+}
+
+fn record_high_scores(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [game_events_results, high_scores_results, game_state_results, ..] = &results[..] {
+        let mode = game_state_results.get_only::<GameState>().mode;
+        let mut high_scores = high_scores_results.get_only_mut::<HighScores>();
+
+        for event in game_events_results.get_only::<GameEvents>().iter() {
+            let entry = match *event {
+                // A time attack that isn't finished has no time to be ranked by.
+                GameEvent::GameOver {
+                    score,
+                    level,
+                    time_millis,
+                } if !HighScores::is_timed(mode) => HighScore {
+                    score,
+                    level,
+                    time_millis,
+                },
+                GameEvent::TimeAttackCompleted { score, time_millis } => HighScore {
+                    score,
+                    level: TIME_ATTACK_WAVES,
+                    time_millis,
+                },
+                _ => continue,
+            };
+
+            if high_scores.record(mode, entry) {
+                let _ = save_key_values(HIGH_SCORES_FILE_NAME, &high_scores.to_saved());
+            }
+        }
+    }
+}
@@ -1,14 +1,97 @@
 use thomas::{
-    Alignment, GameCommand, Identity, IntCoords2d, Query, Rgb, System, SystemsGenerator, Text,
-    UiAnchor, EVENT_INIT, EVENT_UPDATE,
+    Alignment, GameCommand, GameCommandsArg, IntCoords2d, Query, QueryResultList, System,
+    SystemsGenerator, Text, UiAnchor, EVENT_AFTER_UPDATE, EVENT_INIT, EVENT_UPDATE,
 };
 
-use crate::{Player, Scorekeeper, PLAYER_DISPLAY_CHAR};
+use crate::{
+    format_seconds, make_banner, Banner, Banners, Boss, CampaignRun, Combo, GameEvent, GameEvents,
+    GameMode, GameState, Hud, HudBinding, HudWidget, Localization, Player, Scorekeeper, Stats,
+    Theme, ThemeRole, Themed, Themes, TimeAttack, Toast, BANNER_DURATION_MILLIS,
+    GAME_OVER_BANNER_DURATION_MILLIS,
+};
+
+const BOSS_HEALTH_BAR_WIDTH: u64 = 20;
+
+const TOAST_DURATION_MILLIS: u128 = 3000;
 
-const LIVES_TAG_ID: &str = "lives-tag";
-const LEVEL_TAG_ID: &str = "level-tag";
-const SCORE_TAG_ID: &str = "score-tag";
-const HIGH_SCORE_TAG_ID: &str = "high-score-tag";
+/// Where a HUD widget goes, what it shows and how it's styled.
+struct HudWidgetLayout {
+    binding: HudBinding,
+    message_key: &'static str,
+    anchor: UiAnchor,
+    justification: Alignment,
+    offset: (i64, i64),
+    role: ThemeRole,
+}
+
+fn hud_layout() -> [HudWidgetLayout; 8] {
+    [
+        HudWidgetLayout {
+            binding: HudBinding::Lives,
+            message_key: "hud.lives",
+            anchor: UiAnchor::BottomLeft,
+            justification: Alignment::Left,
+            offset: (0, 0),
+            role: ThemeRole::Text,
+        },
+        HudWidgetLayout {
+            binding: HudBinding::Level,
+            message_key: "hud.level",
+            anchor: UiAnchor::BottomLeft,
+            justification: Alignment::Left,
+            offset: (0, -1),
+            role: ThemeRole::Text,
+        },
+        HudWidgetLayout {
+            binding: HudBinding::Score,
+            message_key: "hud.score",
+            anchor: UiAnchor::BottomRight,
+            justification: Alignment::Right,
+            offset: (0, -1),
+            role: ThemeRole::Text,
+        },
+        HudWidgetLayout {
+            binding: HudBinding::HighScore,
+            message_key: "hud.high_score",
+            anchor: UiAnchor::BottomRight,
+            justification: Alignment::Right,
+            offset: (0, 0),
+            role: ThemeRole::Text,
+        },
+        HudWidgetLayout {
+            binding: HudBinding::Combo,
+            message_key: "hud.combo",
+            anchor: UiAnchor::MiddleBottom,
+            justification: Alignment::Middle,
+            offset: (0, -1),
+            role: ThemeRole::Highlight,
+        },
+        HudWidgetLayout {
+            binding: HudBinding::BossHealth,
+            message_key: "hud.boss",
+            anchor: UiAnchor::MiddleTop,
+            justification: Alignment::Middle,
+            offset: (0, 0),
+            role: ThemeRole::Boss,
+        },
+        HudWidgetLayout {
+            binding: HudBinding::Timer,
+            message_key: "hud.time",
+            anchor: UiAnchor::TopLeft,
+            justification: Alignment::Left,
+            offset: (0, 0),
+            role: ThemeRole::Text,
+        },
+        HudWidgetLayout {
+            binding: HudBinding::Split,
+            message_key: "hud.split",
+            anchor: UiAnchor::TopRight,
+            justification: Alignment::Right,
+            offset: (0, 0),
+            role: ThemeRole::Text,
+        },
+    ]
+}
 
 pub struct HudSystemsGenerator {}
 impl SystemsGenerator for HudSystemsGenerator {
@@ -17,120 +100,332 @@ impl SystemsGenerator for HudSystemsGenerator {
             (
                 EVENT_INIT,
                 System::new(vec![], |_, commands| {
-                    commands.borrow_mut().issue(GameCommand::AddEntity(vec![
-                        Box::new(Text {
-                            anchor: UiAnchor::BottomLeft,
-                            justification: Alignment::Left,
-                            offset: IntCoords2d::zero(),
-                            value: String::from(""),
-                            foreground_color: Some(Rgb::white()),
-                            background_color: None,
-                        }),
-                        Box::new(Identity {
-                            id: String::from(LIVES_TAG_ID),
-                            name: String::from(""),
-                        }),
-                    ]));
-
-                    commands.borrow_mut().issue(GameCommand::AddEntity(vec![
-                        Box::new(Text {
-                            anchor: UiAnchor::BottomLeft,
-                            justification: Alignment::Left,
-                            offset: IntCoords2d::down(),
-                            value: String::from(""),
-                            foreground_color: Some(Rgb::white()),
-                            background_color: None,
-                        }),
-                        Box::new(Identity {
-                            id: String::from(LEVEL_TAG_ID),
-                            name: String::from(""),
-                        }),
-                    ]));
-
-                    commands.borrow_mut().issue(GameCommand::AddEntity(vec![
-                        Box::new(Text {
-                            anchor: UiAnchor::BottomRight,
-                            justification: Alignment::Right,
-                            offset: IntCoords2d::down(),
-                            value: String::from(""),
-                            foreground_color: Some(Rgb::white()),
-                            background_color: None,
-                        }),
-                        Box::new(Identity {
-                            id: String::from(SCORE_TAG_ID),
-                            name: String::from(""),
-                        }),
-                    ]));
-
-                    commands.borrow_mut().issue(GameCommand::AddEntity(vec![
-                        Box::new(Text {
-                            anchor: UiAnchor::BottomRight,
-                            justification: Alignment::Right,
-                            offset: IntCoords2d::zero(),
-                            value: String::from(""),
-                            foreground_color: Some(Rgb::white()),
-                            background_color: None,
-                        }),
-                        Box::new(Identity {
-                            id: String::from(HIGH_SCORE_TAG_ID),
-                            name: String::from(""),
-                        }),
-                    ]));
+                    let classic_theme = Theme::classic();
+
+                    for layout in hud_layout() {
+                        commands.borrow_mut().issue(GameCommand::AddEntity(vec![
+                            Box::new(Text {
+                                anchor: layout.anchor,
+                                justification: layout.justification,
+                                offset: IntCoords2d::new(layout.offset.0, layout.offset.1),
+                                value: String::from(""),
+                                foreground_color: Some(classic_theme.color(layout.role)),
+                                background_color: None,
+                            }),
+                            Box::new(HudWidget {
+                                binding: layout.binding,
+                                message_key: layout.message_key,
+                            }),
+                            Box::new(Themed::new(layout.role)),
+                        ]));
+                    }
+
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(Hud::default())]));
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(Banners::default())]));
                 }),
             ),
             (
                 EVENT_UPDATE,
                 System::new(
                     vec![
-                        Query::new()
-                            .has_where::<Identity>(|id| id.id == LIVES_TAG_ID)
-                            .has::<Text>(),
-                        Query::new()
-                            .has_where::<Identity>(|id| id.id == LEVEL_TAG_ID)
-                            .has::<Text>(),
-                        Query::new()
-                            .has_where::<Identity>(|id| id.id == SCORE_TAG_ID)
-                            .has::<Text>(),
-                        Query::new()
-                            .has_where::<Identity>(|id| id.id == HIGH_SCORE_TAG_ID)
-                            .has::<Text>(),
+                        Query::new().has::<Hud>(),
                         Query::new().has::<Player>(),
+                        Query::new().has::<Themes>(),
+                    ],
+                    publish_lives,
+                ),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<Hud>(),
                         Query::new().has::<Scorekeeper>(),
+                        Query::new().has::<Combo>(),
                     ],
-                    |results, _| {
-                        if let [lives_tag_results, level_tag_results, score_tag_results, high_score_tag_results, player_results, scorekeeper_results, ..] =
-                            &results[..]
-                        {
-                            let player = player_results[0].components().get::<Player>();
-                            let scorekeeper =
-                                scorekeeper_results[0].components().get::<Scorekeeper>();
-
-                            let mut lives_tag = lives_tag_results[0].components().get_mut::<Text>();
-                            let mut score_tag = score_tag_results[0].components().get_mut::<Text>();
-                            let mut high_score_tag =
-                                high_score_tag_results[0].components().get_mut::<Text>();
-                            let mut level_tag = level_tag_results.get_only_mut::<Text>();
-
-                            lives_tag.value = format!(
-                                "Lives: {}",
-                                (0..player.lives)
-                                    .map(|_| PLAYER_DISPLAY_CHAR.to_string())
-                                    .collect::<Vec<String>>()
-                                    .join("")
-                            );
-
-                            level_tag.value = format!("Level: {}", scorekeeper.level);
-
-                            score_tag.value = format!("Score: {}", scorekeeper.score);
-
-                            high_score_tag.value = format!(
-                                "Hi-score: {}",
-                                u64::max(scorekeeper.high_score, scorekeeper.score)
-                            );
-                        }
-                    },
+                    publish_score,
+                ),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![Query::new().has::<Hud>(), Query::new().has::<Boss>()],
+                    publish_boss_health,
+                ),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<Hud>(),
+                        Query::new().has::<GameState>(),
+                        Query::new().has::<Stats>(),
+                        Query::new().has::<TimeAttack>(),
+                    ],
+                    publish_time_attack,
                 ),
             ),
+            // After the systems above, so the widgets show this frame's values.
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new()
+                            .has::<HudWidget>()
+                            .has::<Text>()
+                            .has::<Themed>(),
+                        Query::new().has::<Hud>(),
+                        Query::new().has::<Localization>(),
+                    ],
+                    update_widgets,
+                ),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![Query::new().has::<Banners>(), Query::new().has::<Banner>()],
+                    show_banners,
+                ),
+            ),
+            (
+                EVENT_AFTER_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<GameEvents>(),
+                        Query::new().has::<Banners>(),
+                        Query::new().has::<Localization>(),
+                        Query::new().has::<GameState>(),
+                        Query::new().has::<CampaignRun>(),
+                    ],
+                    announce_events,
+                ),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(vec![Query::new().has::<Toast>()], |results, commands| {
+                    if let [toast_results, ..] = &results[..] {
+                        for toast_result in toast_results {
+                            if toast_result.components().get::<Toast>().timer.elapsed_millis()
+                                >= TOAST_DURATION_MILLIS
+                            {
+                                commands
+                                    .borrow_mut()
+                                    .issue(GameCommand::DestroyEntity(*toast_result.entity()));
+                            }
+                        }
+                    }
+                }),
+            ),
         ]
     }
 }
+
+fn publish_lives(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [hud_results, player_results, themes_results, ..] = &results[..] {
+        let player_glyph = themes_results.get_only::<Themes>().current().player_glyph;
+
+        hud_results.get_only_mut::<Hud>().set(
+            HudBinding::Lives,
+            Some(
+                (0..player_results.get_only::<Player>().lives)
+                    .map(|_| player_glyph.to_string())
+                    .collect::<Vec<String>>()
+                    .join(""),
+            ),
+        );
+    }
+}
+
+fn publish_score(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [hud_results, scorekeeper_results, combo_results, ..] = &results[..] {
+        let mut hud = hud_results.get_only_mut::<Hud>();
+        let scorekeeper = scorekeeper_results.get_only::<Scorekeeper>();
+        let combo = combo_results.get_only::<Combo>();
+
+        hud.set(HudBinding::Level, Some(scorekeeper.level.to_string()));
+        hud.set(HudBinding::Score, Some(scorekeeper.score.to_string()));
+        hud.set(
+            HudBinding::HighScore,
+            Some(u64::max(scorekeeper.high_score, scorekeeper.score).to_string()),
+        );
+        hud.set(
+            HudBinding::Combo,
+            (combo.chain > 1).then(|| format!("{} x{}", combo.chain, combo.multiplier())),
+        );
+    }
+}
+
+fn publish_boss_health(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [hud_results, boss_results, ..] = &results[..] {
+        let boss = boss_results.try_get_only::<Boss>();
+
+        hud_results.get_only_mut::<Hud>().set(
+            HudBinding::BossHealth,
+            boss.as_ref().map(|boss| {
+                let filled_width = (boss.hp * BOSS_HEALTH_BAR_WIDTH).div_ceil(boss.max_hp);
+
+                format!(
+                    "[{}{}] P{}",
+                    "#".repeat(filled_width as usize),
+                    "-".repeat((BOSS_HEALTH_BAR_WIDTH - filled_width) as usize),
+                    boss.phase().number()
+                )
+            }),
+        );
+    }
+}
+
+fn publish_time_attack(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [hud_results, game_state_results, stats_results, time_attack_results, ..] = &results[..]
+    {
+        let mut hud = hud_results.get_only_mut::<Hud>();
+        let is_time_attack =
+            game_state_results.get_only::<GameState>().mode == GameMode::TimeAttack;
+        let time_attack = time_attack_results.get_only::<TimeAttack>();
+
+        hud.set(
+            HudBinding::Timer,
+            is_time_attack
+                .then(|| format_seconds(stats_results.get_only::<Stats>().run_time_millis())),
+        );
+        hud.set(
+            HudBinding::Split,
+            is_time_attack
+                .then(|| time_attack.describe_latest_split())
+                .flatten(),
+        );
+
+        // Ahead of the best run is good news and behind it is bad.
+        hud.set_role(
+            HudBinding::Split,
+            match time_attack.latest_delta() {
+                Some(delta) if delta <= 0 => ThemeRole::Positive,
+                Some(_) => ThemeRole::Negative,
+                None => ThemeRole::Text,
+            },
+        );
+    }
+}
+
+fn update_widgets(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [widget_results, hud_results, localization_results, ..] = &results[..] {
+        let hud = hud_results.get_only::<Hud>();
+        let localization = localization_results.get_only::<Localization>();
+
+        for widget_result in widget_results {
+            let widget = widget_result.components().get::<HudWidget>();
+
+            if let Some(role) = hud.role(widget.binding) {
+                let mut themed = widget_result.components().get_mut::<Themed>();
+
+                if themed.role != role {
+                    themed.role = role;
+                    themed.applied_theme = None;
+                }
+            }
+
+            widget_result.components().get_mut::<Text>().value =
+                widget.render(localization.current(), &hud);
+        }
+    }
+}
+
+fn show_banners(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [banners_results, banner_results, ..] = &results[..] {
+        if let Some(banner_result) = banner_results.iter().next() {
+            let banner = banner_result.components().get::<Banner>();
+
+            if banner.timer.elapsed_millis() >= banner.duration_millis {
+                commands
+                    .borrow_mut()
+                    .issue(GameCommand::DestroyEntity(*banner_result.entity()));
+            }
+        } else if let Some(message) = banners_results.get_only_mut::<Banners>().queued.pop_front() {
+            commands
+                .borrow_mut()
+                .issue(GameCommand::AddEntity(make_banner(message)));
+        }
+    }
+}
+
+fn announce_events(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [game_events_results, banners_results, localization_results, game_state_results, campaign_run_results, ..] =
+        &results[..]
+    {
+        let mut banners = banners_results.get_only_mut::<Banners>();
+        let localization = localization_results.get_only::<Localization>();
+        let locale = localization.current();
+        let is_campaign = game_state_results.get_only::<GameState>().mode == GameMode::Campaign;
+        let campaign_run = campaign_run_results.get_only::<CampaignRun>();
+
+        for event in game_events_results.get_only::<GameEvents>().iter() {
+            match *event {
+                GameEvent::WaveStarted {
+                    level: 1,
+                    is_boss_wave: false,
+                } => {
+                    banners.show(
+                        String::from(locale.text("banner.get_ready")),
+                        ThemeRole::Highlight,
+                        BANNER_DURATION_MILLIS,
+                    );
+                }
+                GameEvent::WaveStarted {
+                    level,
+                    is_boss_wave,
+                } => {
+                    let (value, role) = if is_boss_wave
+                        && is_campaign
+                        && campaign_run.campaign.is_final_wave(level)
+                    {
+                        (
+                            String::from(locale.text("banner.final_boss")),
+                            ThemeRole::Boss,
+                        )
+                    } else if is_boss_wave {
+                        (
+                            locale.format("banner.boss_wave", &[("level", &level)]),
+                            ThemeRole::Boss,
+                        )
+                    } else {
+                        (
+                            locale.format("banner.wave", &[("level", &level)]),
+                            ThemeRole::Highlight,
+                        )
+                    };
+
+                    banners.show(value, role, BANNER_DURATION_MILLIS);
+                }
+                GameEvent::GameOver { .. } => {
+                    banners.show(
+                        String::from(locale.text("banner.game_over")),
+                        ThemeRole::Highlight,
+                        GAME_OVER_BANNER_DURATION_MILLIS,
+                    );
+                }
+                GameEvent::CampaignCompleted { .. } => {
+                    banners.show(
+                        String::from(locale.text("banner.campaign_complete")),
+                        ThemeRole::Positive,
+                        GAME_OVER_BANNER_DURATION_MILLIS,
+                    );
+                }
+                GameEvent::TimeAttackCompleted { time_millis, .. } => {
+                    banners.show(
+                        locale.format(
+                            "banner.time_attack_complete",
+                            &[("seconds", &format_seconds(time_millis))],
+                        ),
+                        ThemeRole::Highlight,
+                        GAME_OVER_BANNER_DURATION_MILLIS,
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+}
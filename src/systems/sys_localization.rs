@@ -0,0 +1,49 @@
+use thomas::{
+    GameCommand, Priority, Query, System, SystemsGenerator, Text, EVENT_AFTER_UPDATE, EVENT_INIT,
+};
+
+use crate::{fit_to_cells, Localization};
+
+pub struct LocalizationSystemsGenerator {
+    /// The code of the language to start in. English when the game doesn't have it.
+    pub locale_code: String,
+}
+impl SystemsGenerator for LocalizationSystemsGenerator {
+    fn generate(&self) -> Vec<(&'static str, System)> {
+        let locale_code = self.locale_code.clone();
+
+        vec![
+            (
+                EVENT_INIT,
+                System::new(vec![], move |_, commands| {
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(Localization::new(
+                            &locale_code,
+                        ))]));
+                }),
+            ),
+            (
+                EVENT_AFTER_UPDATE,
+                // Runs after everything else has set this frame's text, so wide characters are laid out wherever
+                // the text came from.
+                System::new_with_priority(
+                    Priority::lower_than(&Priority::default()),
+                    vec![Query::new().has::<Text>()],
+                    |results, _| {
+                        if let [text_results, ..] = &results[..] {
+                            for text_result in text_results {
+                                let mut text = text_result.components().get_mut::<Text>();
+                                let fitted = fit_to_cells(&text.value);
+
+                                if fitted != text.value {
+                                    text.value = fitted;
+                                }
+                            }
+                        }
+                    },
+                ),
+            ),
+        ]
+    }
+}
@@ -0,0 +1,839 @@
+use thomas::{
+    Alignment, GameCommand, GameCommandsArg, Input, IntCoords2d, Keycode, Query, QueryResultList,
+    System, SystemsGenerator, Text, UiAnchor, EVENT_CLEANUP, EVENT_INIT, EVENT_UPDATE,
+};
+
+use crate::{
+    center_in_width, display_width, format_seconds, save_key_values, Achievements, CampaignRun,
+    ControlScheme, DailyRun, Difficulty, GameMode, GamePhase, GameState, HighScores, LevelEditor,
+    Locale, Localization, Menu, MenuAction, MenuItem, MenuItemKind, MenuLine, MenuPage, MenuScreen,
+    MenuSetting, ResumableGame, SavedGameError, SavedSettings, ScreenEffects, Settings, Theme,
+    ThemeRole, Themed, Themes, ACHIEVEMENTS, MAX_HIGH_SCORES, MAX_SCREEN_EFFECT_INTENSITY,
+    SAVED_GAME_VERSION, SETTINGS_FILE_NAME,
+};
+
+const UP_KEYS: [Keycode; 2] = [Keycode::Up, Keycode::W];
+const DOWN_KEYS: [Keycode; 2] = [Keycode::Down, Keycode::S];
+const LEFT_KEYS: [Keycode; 2] = [Keycode::Left, Keycode::A];
+const RIGHT_KEYS: [Keycode; 2] = [Keycode::Right, Keycode::D];
+const CONFIRM_KEYS: [Keycode; 1] = [Keycode::Enter];
+const BACK_KEYS: [Keycode; 2] = [Keycode::Escape, Keycode::Backspace];
+
+/// The menus shown between runs, and the settings they change.
+pub struct MenuSystemsGenerator {
+    /// The settings in use, with any command line options applied.
+    pub settings: Settings,
+    /// The settings as they were loaded, which the player's changes are saved over.
+    pub saved_settings: Settings,
+}
+impl SystemsGenerator for MenuSystemsGenerator {
+    fn generate(&self) -> Vec<(&'static str, System)> {
+        let settings = self.settings.clone();
+        let saved_settings = self.saved_settings.clone();
+
+        vec![
+            (
+                EVENT_INIT,
+                System::new(vec![], move |_, commands| {
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(settings.clone())]));
+
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(SavedSettings {
+                            settings: saved_settings.clone(),
+                        })]));
+
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(Menu::new())]));
+                }),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<Input>(),
+                        Query::new().has::<Menu>(),
+                        Query::new().has_where::<GameState>(|state| state.phase == GamePhase::Menu),
+                        Query::new().has::<Settings>(),
+                        Query::new().has::<SavedSettings>(),
+                        Query::new().has::<Themes>(),
+                        Query::new().has::<ScreenEffects>(),
+                        Query::new().has::<HighScores>(),
+                        Query::new().has::<Localization>(),
+                        Query::new().has::<ResumableGame>(),
+                        Query::new().has::<LevelEditor>(),
+                        Query::new().has::<CampaignRun>(),
+                        Query::new().has::<DailyRun>(),
+                        Query::new().has::<Achievements>(),
+                    ],
+                    navigate_menu,
+                ),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<GameState>(),
+                        Query::new().has::<Menu>(),
+                        Query::new().has::<Settings>(),
+                        Query::new().has::<Themes>(),
+                        Query::new().has::<HighScores>(),
+                        Query::new().has::<Localization>(),
+                        Query::new().has::<ResumableGame>(),
+                        Query::new().has::<CampaignRun>(),
+                        Query::new().has::<DailyRun>(),
+                        Query::new().has::<Achievements>(),
+                        Query::new().has::<MenuLine>().has::<Text>().has::<Themed>(),
+                    ],
+                    draw_menu,
+                ),
+            ),
+            (
+                EVENT_CLEANUP,
+                System::new(
+                    vec![
+                        Query::new().has::<Settings>(),
+                        Query::new().has::<SavedSettings>(),
+                        Query::new().has::<Themes>(),
+                    ],
+                    |results, _| {
+                        if let [settings_results, saved_settings_results, themes_results, ..] =
+                            &results[..]
+                        {
+                            let mut settings = settings_results.get_only_mut::<Settings>();
+                            let theme = themes_results.get_only::<Themes>().current().slug();
+
+                            // The theme can also be switched during play, which only needs saving if it was.
+                            if settings.theme.as_ref() != Some(&theme) {
+                                settings.theme = Some(theme);
+
+                                save_change(
+                                    MenuSetting::Theme,
+                                    &settings,
+                                    &mut saved_settings_results.get_only_mut::<SavedSettings>(),
+                                );
+                            }
+                        }
+                    },
+                ),
+            ),
+        ]
+    }
+}
+
+fn navigate_menu(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [input_results, menu_results, menu_state_results, settings_results, saved_settings_results, themes_results, screen_effects_results, high_scores_results, localization_results, resumable_game_results, level_editor_results, campaign_run_results, daily_run_results, achievements_results, ..] =
+        &results[..]
+    {
+        if menu_state_results.is_empty() {
+            return;
+        }
+
+        let input = input_results.get_only::<Input>();
+        let mut menu = menu_results.get_only_mut::<Menu>();
+        let mut settings = settings_results.get_only_mut::<Settings>();
+        let mut themes = themes_results.get_only_mut::<Themes>();
+        let high_scores = high_scores_results.get_only::<HighScores>();
+        let mut localization = localization_results.get_only_mut::<Localization>();
+        let mut resumable_game = resumable_game_results.get_only_mut::<ResumableGame>();
+        let mut campaign_run = campaign_run_results.get_only_mut::<CampaignRun>();
+        let mut daily_run = daily_run_results.get_only_mut::<DailyRun>();
+        let achievements = achievements_results.get_only::<Achievements>();
+
+        let is_any_key_down = |keys: &[Keycode]| keys.iter().any(|key| input.is_key_down(key));
+
+        let screen = build_menu_screen(
+            menu.page,
+            &MenuSources {
+                settings: &settings,
+                themes: &themes,
+                high_scores: &high_scores,
+                resumable_game: &resumable_game,
+                campaign_run: &campaign_run,
+                daily_run: &daily_run,
+                achievements: &achievements,
+            },
+            localization.current(),
+        );
+
+        if menu.selected >= screen.items.len() || !screen.items[menu.selected].is_selectable() {
+            menu.selected = screen.first_selectable();
+        }
+
+        let Some(item) = screen.items.get(menu.selected) else {
+            return;
+        };
+
+        let setting_step = if is_any_key_down(&LEFT_KEYS) {
+            Some(-1)
+        } else if is_any_key_down(&RIGHT_KEYS) {
+            Some(1)
+        } else {
+            None
+        };
+
+        if is_any_key_down(&UP_KEYS) {
+            menu.selected = screen.step_selection(menu.selected, -1);
+        } else if is_any_key_down(&DOWN_KEYS) {
+            menu.selected = screen.step_selection(menu.selected, 1);
+        } else if is_any_key_down(&BACK_KEYS) {
+            menu.back();
+        } else if is_any_key_down(&CONFIRM_KEYS) {
+            match item.kind {
+                MenuItemKind::Action(MenuAction::Play) => {
+                    menu.reset();
+
+                    menu_state_results
+                        .get_only_mut::<GameState>()
+                        .enter(GamePhase::Playing);
+                }
+                MenuItemKind::Action(MenuAction::Survival) => {
+                    menu.reset();
+
+                    let mut game_state = menu_state_results.get_only_mut::<GameState>();
+                    game_state.mode = GameMode::Survival;
+                    game_state.enter(GamePhase::Playing);
+                }
+                MenuItemKind::Action(MenuAction::TimeAttack) => {
+                    menu.reset();
+
+                    let mut game_state = menu_state_results.get_only_mut::<GameState>();
+                    game_state.mode = GameMode::TimeAttack;
+                    game_state.enter(GamePhase::Playing);
+                }
+                MenuItemKind::Action(MenuAction::Continue) => {
+                    menu.reset();
+
+                    resumable_game.is_resuming = true;
+                }
+                MenuItemKind::Action(MenuAction::Edit) => {
+                    menu.reset();
+
+                    level_editor_results
+                        .get_only_mut::<LevelEditor>()
+                        .is_opening = true;
+                }
+                MenuItemKind::Action(MenuAction::Campaign { from_checkpoint }) => {
+                    menu.reset();
+
+                    campaign_run.start(from_checkpoint);
+                }
+                MenuItemKind::Action(MenuAction::Daily) => {
+                    menu.reset();
+
+                    daily_run.is_starting = true;
+                }
+                MenuItemKind::Action(MenuAction::Open(page)) => {
+                    let selected = build_menu_screen(
+                        page,
+                        &MenuSources {
+                            settings: &settings,
+                            themes: &themes,
+                            high_scores: &high_scores,
+                            resumable_game: &resumable_game,
+                            campaign_run: &campaign_run,
+                            daily_run: &daily_run,
+                            achievements: &achievements,
+                        },
+                        localization.current(),
+                    )
+                    .first_selectable();
+
+                    menu.open(page, selected);
+                }
+                MenuItemKind::Action(MenuAction::Back) => menu.back(),
+                MenuItemKind::Action(MenuAction::Quit) => {
+                    commands.borrow_mut().issue(GameCommand::Quit);
+                }
+                MenuItemKind::Slider { setting, .. }
+                | MenuItemKind::Toggle { setting, .. }
+                | MenuItemKind::Choice { setting, .. } => {
+                    change_setting(
+                        setting,
+                        1,
+                        true,
+                        &mut settings,
+                        &mut themes,
+                        &mut localization,
+                        &mut screen_effects_results.get_only_mut::<ScreenEffects>(),
+                    );
+
+                    save_change(
+                        setting,
+                        &settings,
+                        &mut saved_settings_results.get_only_mut::<SavedSettings>(),
+                    );
+                }
+                MenuItemKind::Label => {}
+            }
+        } else if let Some(step) = setting_step {
+            if let MenuItemKind::Slider { setting, .. }
+            | MenuItemKind::Toggle { setting, .. }
+            | MenuItemKind::Choice { setting, .. } = item.kind
+            {
+                change_setting(
+                    setting,
+                    step,
+                    false,
+                    &mut settings,
+                    &mut themes,
+                    &mut localization,
+                    &mut screen_effects_results.get_only_mut::<ScreenEffects>(),
+                );
+
+                save_change(
+                    setting,
+                    &settings,
+                    &mut saved_settings_results.get_only_mut::<SavedSettings>(),
+                );
+            }
+        }
+    }
+}
+
+/// Steps the setting forwards or backwards. Lists always wrap around. Sliders only wrap when
+/// `should_wrap` is set, so left and right stop at the ends while choosing a slider cycles it.
+fn change_setting(
+    setting: MenuSetting,
+    step: i64,
+    should_wrap: bool,
+    settings: &mut Settings,
+    themes: &mut Themes,
+    localization: &mut Localization,
+    screen_effects: &mut ScreenEffects,
+) {
+    match setting {
+        MenuSetting::Difficulty => {
+            settings.difficulty = step_through(&Difficulty::ALL, settings.difficulty, step);
+        }
+        MenuSetting::Controls => {
+            settings.controls = step_through(&ControlScheme::ALL, settings.controls, step);
+        }
+        MenuSetting::Theme => {
+            themes.current =
+                (themes.current as i64 + step).rem_euclid(themes.themes.len() as i64) as usize;
+
+            settings.theme = Some(themes.current().slug());
+        }
+        MenuSetting::Language => {
+            localization.current = (localization.current as i64 + step)
+                .rem_euclid(localization.locales.len() as i64)
+                as usize;
+
+            settings.language = Some(localization.current().code.clone());
+        }
+        MenuSetting::ShakeIntensity => {
+            settings.screen_effects.shake_intensity =
+                step_slider(settings.screen_effects.shake_intensity, step, should_wrap);
+        }
+        MenuSetting::FlashIntensity => {
+            settings.screen_effects.flash_intensity =
+                step_slider(settings.screen_effects.flash_intensity, step, should_wrap);
+        }
+        MenuSetting::Sprites => {
+            settings.use_sprites = !settings.use_sprites;
+        }
+    }
+
+    screen_effects.settings = settings.screen_effects;
+}
+
+fn step_through<T: Copy + PartialEq>(values: &[T], current: T, step: i64) -> T {
+    let index = values
+        .iter()
+        .position(|value| *value == current)
+        .unwrap_or(0);
+
+    values[(index as i64 + step).rem_euclid(values.len() as i64) as usize]
+}
+
+fn step_slider(value: u8, step: i64, should_wrap: bool) -> u8 {
+    let max = MAX_SCREEN_EFFECT_INTENSITY as i64;
+    let stepped = value as i64 + step;
+
+    if should_wrap {
+        stepped.rem_euclid(max + 1) as u8
+    } else {
+        stepped.clamp(0, max) as u8
+    }
+}
+
+/// Saves the player's change to a setting, leaving the rest as they were loaded.
+fn save_change(setting: MenuSetting, settings: &Settings, saved_settings: &mut SavedSettings) {
+    saved_settings.record(setting, settings);
+
+    // Not being able to save isn't worth interrupting the game over. The settings still apply to this session.
+    let _ = save_key_values(SETTINGS_FILE_NAME, &saved_settings.settings.to_saved());
+}
+
+/// The state the menu pages are built from.
+struct MenuSources<'a> {
+    settings: &'a Settings,
+    themes: &'a Themes,
+    high_scores: &'a HighScores,
+    resumable_game: &'a ResumableGame,
+    campaign_run: &'a CampaignRun,
+    daily_run: &'a DailyRun,
+    achievements: &'a Achievements,
+}
+
+fn build_menu_screen(page: MenuPage, sources: &MenuSources, locale: &Locale) -> MenuScreen {
+    let MenuSources {
+        settings,
+        themes,
+        high_scores,
+        resumable_game,
+        campaign_run,
+        daily_run,
+        achievements,
+    } = sources;
+    let back = MenuItem::action(locale.text("menu.back"), MenuAction::Back);
+
+    match page {
+        MenuPage::Main => {
+            let mut items = vec![];
+
+            if resumable_game.saved.is_some() {
+                items.push(MenuItem::action(
+                    locale.text("menu.continue"),
+                    MenuAction::Continue,
+                ));
+            } else if let Some(error) = &resumable_game.error {
+                items.push(MenuItem::label(match error {
+                    SavedGameError::IncompatibleVersion(version) => locale.format(
+                        "menu.saved_game_incompatible",
+                        &[("version", version), ("supported", &SAVED_GAME_VERSION)],
+                    ),
+                    SavedGameError::Damaged(key) => {
+                        locale.format("menu.saved_game_damaged", &[("key", key)])
+                    }
+                }));
+            }
+
+            items.append(&mut vec![
+                MenuItem::action(locale.text("menu.play"), MenuAction::Play),
+                MenuItem::action(locale.text("menu.modes"), MenuAction::Open(MenuPage::Modes)),
+                MenuItem::action(locale.text("menu.editor"), MenuAction::Edit),
+                MenuItem::action(
+                    locale.text("menu.options"),
+                    MenuAction::Open(MenuPage::Options),
+                ),
+                MenuItem::action(
+                    locale.text("menu.high_scores"),
+                    MenuAction::Open(MenuPage::HighScores),
+                ),
+                MenuItem::action(
+                    locale.text("menu.achievements"),
+                    MenuAction::Open(MenuPage::Achievements),
+                ),
+                MenuItem::action(locale.text("menu.quit"), MenuAction::Quit),
+            ]);
+
+            MenuScreen {
+                title: String::from(locale.text("menu.title")),
+                items,
+            }
+        }
+        MenuPage::Modes => {
+            let campaign_key = if campaign_run.progress.is_completed {
+                "menu.mode.campaign_completed"
+            } else {
+                "menu.mode.campaign"
+            };
+
+            let mut items = vec![
+                MenuItem::action(locale.text("menu.mode.classic"), MenuAction::Play),
+                MenuItem::action(locale.text("menu.mode.survival"), MenuAction::Survival),
+                MenuItem::action(locale.text("menu.mode.time_attack"), MenuAction::TimeAttack),
+                MenuItem::action(
+                    locale.text("menu.mode.daily"),
+                    MenuAction::Open(MenuPage::Daily),
+                ),
+                MenuItem::label(
+                    locale.format(campaign_key, &[("name", &campaign_run.campaign.name)]),
+                ),
+            ];
+
+            if let Some(checkpoint) = campaign_run.progress.checkpoint {
+                items.push(MenuItem {
+                    label: locale.format("menu.campaign.continue", &[("level", &checkpoint)]),
+                    kind: MenuItemKind::Action(MenuAction::Campaign {
+                        from_checkpoint: true,
+                    }),
+                });
+            }
+
+            items.push(MenuItem::action(
+                locale.text(if campaign_run.progress.checkpoint.is_some() {
+                    "menu.campaign.start_over"
+                } else {
+                    "menu.campaign.start"
+                }),
+                MenuAction::Campaign {
+                    from_checkpoint: false,
+                },
+            ));
+            items.push(back);
+
+            MenuScreen {
+                title: String::from(locale.text("menu.modes_title")),
+                items,
+            }
+        }
+        MenuPage::Daily => {
+            let mut items: Vec<MenuItem> = daily_run
+                .challenge
+                .modifiers
+                .iter()
+                .map(|modifier| {
+                    MenuItem::label(String::from(
+                        locale.text(&format!("daily.modifier.{}", modifier.id())),
+                    ))
+                })
+                .collect();
+
+            items.push(MenuItem::label(String::new()));
+
+            match daily_run.todays_result() {
+                Some(result) => {
+                    items.push(MenuItem::label(locale.format(
+                        "menu.daily.played",
+                        &[("score", &result.score), ("level", &result.level)],
+                    )));
+                    items.push(MenuItem::action(
+                        locale.text("menu.daily.practice"),
+                        MenuAction::Daily,
+                    ));
+                }
+                None => items.push(MenuItem::action(
+                    locale.text("menu.daily.play"),
+                    MenuAction::Daily,
+                )),
+            }
+
+            items.push(back);
+
+            MenuScreen {
+                title: locale.format("menu.daily_title", &[("date", &daily_run.challenge.date())]),
+                items,
+            }
+        }
+        MenuPage::Options => MenuScreen {
+            title: String::from(locale.text("menu.options_title")),
+            items: vec![
+                MenuItem {
+                    label: String::from(locale.text("menu.difficulty")),
+                    kind: MenuItemKind::Choice {
+                        setting: MenuSetting::Difficulty,
+                        value: String::from(locale.text(&format!(
+                            "difficulty.{}",
+                            settings.difficulty.name().to_lowercase()
+                        ))),
+                    },
+                },
+                MenuItem {
+                    label: String::from(locale.text("menu.controls")),
+                    kind: MenuItemKind::Choice {
+                        setting: MenuSetting::Controls,
+                        value: String::from(
+                            locale.text(&format!("controls.{}", settings.controls.id())),
+                        ),
+                    },
+                },
+                MenuItem {
+                    label: String::from(locale.text("menu.theme")),
+                    kind: MenuItemKind::Choice {
+                        setting: MenuSetting::Theme,
+                        value: themes.current().name.clone(),
+                    },
+                },
+                MenuItem {
+                    label: String::from(locale.text("menu.language")),
+                    kind: MenuItemKind::Choice {
+                        setting: MenuSetting::Language,
+                        value: String::from(locale.name()),
+                    },
+                },
+                MenuItem {
+                    label: String::from(locale.text("menu.shake")),
+                    kind: MenuItemKind::Slider {
+                        setting: MenuSetting::ShakeIntensity,
+                        value: settings.screen_effects.shake_intensity,
+                        max: MAX_SCREEN_EFFECT_INTENSITY,
+                    },
+                },
+                MenuItem {
+                    label: String::from(locale.text("menu.flash")),
+                    kind: MenuItemKind::Slider {
+                        setting: MenuSetting::FlashIntensity,
+                        value: settings.screen_effects.flash_intensity,
+                        max: MAX_SCREEN_EFFECT_INTENSITY,
+                    },
+                },
+                MenuItem {
+                    label: String::from(locale.text("menu.sprites")),
+                    kind: MenuItemKind::Toggle {
+                        setting: MenuSetting::Sprites,
+                        value: settings.use_sprites,
+                    },
+                },
+                back,
+            ],
+        },
+        MenuPage::HighScores => {
+            let mut items: Vec<MenuItem> = HighScores::MODES
+                .into_iter()
+                .map(|mode| {
+                    MenuItem::action(
+                        locale.text(&format!("menu.mode.{}", mode.id())),
+                        MenuAction::Open(MenuPage::Leaderboard(mode)),
+                    )
+                })
+                .collect();
+
+            items.push(MenuItem::action(
+                locale.text("menu.mode.daily"),
+                MenuAction::Open(MenuPage::DailyResults),
+            ));
+            items.push(back);
+
+            MenuScreen {
+                title: String::from(locale.text("menu.high_scores_title")),
+                items,
+            }
+        }
+        MenuPage::Leaderboard(mode) => {
+            let mut items: Vec<MenuItem> = high_scores
+                .entries(mode)
+                .iter()
+                .enumerate()
+                .map(|(index, entry)| {
+                    let rank = format!("{:>2}", index + 1);
+
+                    MenuItem::label(if HighScores::is_timed(mode) {
+                        locale.format(
+                            "menu.time_attack_entry",
+                            &[
+                                ("rank", &rank),
+                                ("time", &format!("{:>6}", format_seconds(entry.time_millis))),
+                                ("score", &entry.score),
+                            ],
+                        )
+                    } else {
+                        locale.format(
+                            "menu.high_score_entry",
+                            &[
+                                ("rank", &rank),
+                                ("score", &format!("{:>8}", entry.score)),
+                                ("level", &entry.level),
+                            ],
+                        )
+                    })
+                })
+                .collect();
+
+            if items.is_empty() {
+                items.push(MenuItem::label(String::from(locale.text("menu.no_scores"))));
+            }
+
+            items.push(back);
+
+            MenuScreen {
+                title: locale.format(
+                    "menu.leaderboard_title",
+                    &[("mode", &locale.text(&format!("menu.mode.{}", mode.id())))],
+                ),
+                items,
+            }
+        }
+        MenuPage::DailyResults => {
+            // Today's results, or the last day's until someone plays today.
+            let date = daily_run
+                .results
+                .latest_date()
+                .cloned()
+                .unwrap_or_else(|| daily_run.challenge.date());
+            let mut items: Vec<MenuItem> = daily_run
+                .results
+                .ranked(&date)
+                .into_iter()
+                .take(MAX_HIGH_SCORES)
+                .enumerate()
+                .map(|(index, (name, result))| {
+                    let entry = locale.format(
+                        "menu.daily_entry",
+                        &[
+                            ("rank", &format!("{:>2}", index + 1)),
+                            ("score", &format!("{:>8}", result.score)),
+                            ("level", &result.level),
+                            ("name", name),
+                        ],
+                    );
+
+                    MenuItem::label(if *name == daily_run.player_name {
+                        locale.format("menu.daily_entry_you", &[("entry", &entry)])
+                    } else {
+                        entry
+                    })
+                })
+                .collect();
+
+            if items.is_empty() {
+                items.push(MenuItem::label(String::from(locale.text("menu.no_scores"))));
+            }
+
+            items.push(back);
+
+            MenuScreen {
+                title: locale.format("menu.daily_results_title", &[("date", &date)]),
+                items,
+            }
+        }
+        MenuPage::Achievements => {
+            let mut items = vec![];
+
+            for achievement in &ACHIEVEMENTS {
+                let mark = if achievements.is_unlocked(achievement) {
+                    'x'
+                } else {
+                    ' '
+                };
+
+                items.push(MenuItem::label(locale.format(
+                    "menu.achievement_entry",
+                    &[
+                        ("mark", &mark),
+                        ("name", &achievement.name(locale)),
+                        ("progress", &achievements.progress_of(achievement.id)),
+                        ("goal", &achievement.goal),
+                    ],
+                )));
+                items.push(MenuItem::label(achievement.description(locale)));
+            }
+
+            items.push(back);
+
+            MenuScreen {
+                title: locale.format(
+                    "menu.achievements_title",
+                    &[
+                        ("unlocked", &achievements.unlocked_count()),
+                        ("total", &ACHIEVEMENTS.len()),
+                    ],
+                ),
+                items,
+            }
+        }
+    }
+}
+
+fn draw_menu(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [game_state_results, menu_results, settings_results, themes_results, high_scores_results, localization_results, resumable_game_results, campaign_run_results, daily_run_results, achievements_results, menu_line_results, ..] =
+        &results[..]
+    {
+        if game_state_results.get_only::<GameState>().phase != GamePhase::Menu {
+            for menu_line_result in menu_line_results {
+                commands
+                    .borrow_mut()
+                    .issue(GameCommand::DestroyEntity(*menu_line_result.entity()));
+            }
+
+            return;
+        }
+
+        let menu = menu_results.get_only::<Menu>();
+        let themes = themes_results.get_only::<Themes>();
+        let localization = localization_results.get_only::<Localization>();
+        let screen = build_menu_screen(
+            menu.page,
+            &MenuSources {
+                settings: &settings_results.get_only::<Settings>(),
+                themes: &themes,
+                high_scores: &high_scores_results.get_only::<HighScores>(),
+                resumable_game: &resumable_game_results.get_only::<ResumableGame>(),
+                campaign_run: &campaign_run_results.get_only::<CampaignRun>(),
+                daily_run: &daily_run_results.get_only::<DailyRun>(),
+                achievements: &achievements_results.get_only::<Achievements>(),
+            },
+            localization.current(),
+        );
+
+        let lines = get_menu_lines(&screen, menu.selected, localization.current());
+
+        if menu_line_results.len() == lines.len() {
+            let theme = themes.current();
+
+            for (menu_line_result, (value, role)) in menu_line_results.iter().zip(lines) {
+                let mut text = menu_line_result.components().get_mut::<Text>();
+
+                text.value = value;
+                text.foreground_color = Some(theme.color(role));
+                text.background_color = Some(theme.background);
+
+                menu_line_result.components().get_mut::<Themed>().role = role;
+            }
+        } else {
+            for menu_line_result in menu_line_results {
+                commands
+                    .borrow_mut()
+                    .issue(GameCommand::DestroyEntity(*menu_line_result.entity()));
+            }
+
+            let classic_theme = Theme::classic();
+            let top_row = -(lines.len() as i64 / 2);
+
+            for (row, (value, role)) in lines.into_iter().enumerate() {
+                commands.borrow_mut().issue(GameCommand::AddEntity(vec![
+                    Box::new(MenuLine {}),
+                    Box::new(Text {
+                        anchor: UiAnchor::Middle,
+                        justification: Alignment::Middle,
+                        offset: IntCoords2d::new(0, top_row + row as i64),
+                        value,
+                        foreground_color: Some(classic_theme.color(role)),
+                        background_color: Some(classic_theme.background),
+                    }),
+                    Box::new(Themed::new(role)),
+                ]));
+            }
+        }
+    }
+}
+
+/// Every line is padded to the same width, so the menu's background hides whatever's behind it.
+fn get_menu_lines(
+    screen: &MenuScreen,
+    selected: usize,
+    locale: &Locale,
+) -> Vec<(String, ThemeRole)> {
+    let mut lines = vec![
+        (screen.title.clone(), ThemeRole::Highlight),
+        (String::from(""), ThemeRole::Text),
+    ];
+
+    for (index, item) in screen.items.iter().enumerate() {
+        lines.push(if index == selected {
+            (format!("> {} <", item.display(locale)), ThemeRole::Highlight)
+        } else {
+            (item.display(locale), ThemeRole::Text)
+        });
+    }
+
+    lines.push((String::from(""), ThemeRole::Text));
+    lines.push((String::from(locale.text("menu.hint")), ThemeRole::Muted));
+
+    let width = lines
+        .iter()
+        .map(|(value, _)| display_width(value))
+        .max()
+        .unwrap_or(0)
+        + 2;
+
+    lines
+        .into_iter()
+        .map(|(value, role)| (center_in_width(&value, width), role))
+        .collect()
+}
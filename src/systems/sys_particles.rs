@@ -0,0 +1,86 @@
+use std::f64::consts::TAU;
+
+use rand::{thread_rng, Rng};
+use thomas::{
+    Coords2d, GameCommand, GameCommandsArg, IntCoords2d, Query, QueryResultList, System,
+    SystemsGenerator, TerminalTransform, EVENT_AFTER_UPDATE, EVENT_UPDATE,
+};
+
+use crate::{make_particle, Particle, PendingParticleBurst, MAX_PARTICLES};
+
+pub struct ParticleSystemsGenerator {}
+impl SystemsGenerator for ParticleSystemsGenerator {
+    fn generate(&self) -> Vec<(&'static str, System)> {
+        vec![
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![Query::new().has::<Particle>().has::<TerminalTransform>()],
+                    move_particles,
+                ),
+            ),
+            (
+                EVENT_AFTER_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<PendingParticleBurst>(),
+                        Query::new().has::<Particle>(),
+                    ],
+                    spawn_particles,
+                ),
+            ),
+        ]
+    }
+}
+
+fn move_particles(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [particle_results, ..] = &results[..] {
+        for particle_result in particle_results {
+            let particle = particle_result.components().get::<Particle>();
+            let mut transform = particle_result.components().get_mut::<TerminalTransform>();
+
+            let age_seconds = particle.age_timer.elapsed_millis() as f64 / 1000.0;
+
+            transform.coords = particle.origin
+                + IntCoords2d::new(
+                    (particle.velocity.x() * age_seconds).round() as i64,
+                    (particle.velocity.y() * age_seconds).round() as i64,
+                );
+        }
+    }
+}
+
+fn spawn_particles(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [pending_burst_results, particle_results, ..] = &results[..] {
+        let mut available_particles = MAX_PARTICLES.saturating_sub(particle_results.len());
+
+        for pending_burst_result in pending_burst_results {
+            let pending_burst = pending_burst_result
+                .components()
+                .get::<PendingParticleBurst>();
+
+            let count = pending_burst.burst.count.min(available_particles);
+
+            for _ in 0..count {
+                let angle = thread_rng().gen_range(0.0..TAU);
+                let speed = thread_rng().gen_range(0.3..=1.0) * pending_burst.burst.speed;
+
+                commands
+                    .borrow_mut()
+                    .issue(GameCommand::AddEntity(make_particle(
+                        pending_burst.coords,
+                        // Terminal cells are about twice as tall as they are wide, so halve the vertical speed to keep
+                        // bursts looking round.
+                        Coords2d::new(angle.cos() * speed, angle.sin() * speed / 2.0),
+                        &pending_burst.burst,
+                    )));
+            }
+
+            available_particles -= count;
+
+            commands
+                .borrow_mut()
+                .issue(GameCommand::DestroyEntity(*pending_burst_result.entity()));
+        }
+    }
+}
@@ -1,56 +1,43 @@
 use std::{cell::Ref, collections::HashMap};
 
 use thomas::{
-    GameCommand, GameCommandsArg, Input, IntCoords2d, Keycode, Layer, Query, QueryResultList,
-    System, SystemsGenerator, TerminalCollider, TerminalRenderer, TerminalTransform, Timer,
-    EVENT_INIT, EVENT_UPDATE
+    GameCommand, GameCommandsArg, Input, IntCoords2d, Keycode, Query, QueryResultList, System,
+    SystemsGenerator, TerminalTransform, Timer, EVENT_AFTER_UPDATE, EVENT_INIT, EVENT_UPDATE,
 };
 
 use crate::{
-    make_bullet, Bullet, BulletType, Combat, Enemy, Player, PlayerMovement, Scorekeeper,
-    PLAYER_COLLISION_LAYER, PLAYER_DISPLAY_CHAR, PLAYER_STARTING_LIVES, SCREEN_WIDTH,
-    UI_Y_START_POSITION, PLAYER_COLOR,
+    make_bullet, make_player, make_summary_card, Bonuses, Boss, Bullet, BulletType, Bunker, Combat,
+    Enemy, GameEvent, GameEvents, GameMode, GamePhase, GameRng, GameState, Localization, Player,
+    PlayerMovement, Scorekeeper, Settings, Sprite, Stats, PLAYER_SHOOT_WAIT_MILLIS,
+    PLAYER_STARTING_LIVES, SCREEN_WIDTH, UI_Y_START_POSITION,
 };
 
 const MOVE_WAIT_TIME_MILLIS: u128 = 50;
-const SHOOT_WAIT_TIME_MILLIS: u128 = 100;
+const GAME_OVER_SUMMARY_WAIT_TIME: u128 = 5000;
+const RUN_COMPLETE_WAIT_TIME: u128 = 10000;
 
 type MovementToDirectionMap = HashMap<Keycode, IntCoords2d>;
 
-pub struct PlayerSystemsGenerator {}
+pub struct PlayerSystemsGenerator {
+    pub use_sprites: bool,
+}
 impl SystemsGenerator for PlayerSystemsGenerator {
     fn generate(&self) -> Vec<(&'static str, System)> {
+        let use_sprites = self.use_sprites;
+
         vec![
             (
                 EVENT_INIT,
-                System::new(vec![], |_, commands| {
-                    commands.borrow_mut().issue(GameCommand::AddEntity(vec![
-                        Box::new(Player {
-                            lives: PLAYER_STARTING_LIVES,
-                        }),
-                        Box::new(PlayerMovement {
-                            move_timer: Timer::start_new(),
-                        }),
-                        Box::new(Combat {
-                            shoot_timer: Timer::start_new(),
-                        }),
-                        Box::new(TerminalTransform {
-                            coords: IntCoords2d::new(
-                                SCREEN_WIDTH as i64 / 2,
-                                UI_Y_START_POSITION as i64,
-                            ),
-                        }),
-                        Box::new(TerminalRenderer {
-                            display: PLAYER_DISPLAY_CHAR,
-                            layer: Layer::base(),
-                            foreground_color: Some(PLAYER_COLOR),
-                            background_color: None
-                        }),
-                        Box::new(TerminalCollider {
-                            is_active: true,
-                            layer: PLAYER_COLLISION_LAYER,
-                        }),
-                    ]));
+                System::new(vec![], move |_, commands| {
+                    for player_entity in make_player(
+                        IntCoords2d::new(SCREEN_WIDTH as i64 / 2, UI_Y_START_POSITION as i64),
+                        PLAYER_STARTING_LIVES,
+                        use_sprites,
+                    ) {
+                        commands
+                            .borrow_mut()
+                            .issue(GameCommand::AddEntity(player_entity));
+                    }
 
                     commands
                         .borrow_mut()
@@ -58,7 +45,15 @@ impl SystemsGenerator for PlayerSystemsGenerator {
                             score: 0,
                             high_score: 10000,
                             level: 0,
-                        })]))
+                        })]));
+
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(GameState {
+                            phase: GamePhase::Menu,
+                            mode: GameMode::Classic,
+                            phase_timer: Timer::start_new(),
+                        })]));
                 }),
             ),
             (
@@ -69,6 +64,17 @@ impl SystemsGenerator for PlayerSystemsGenerator {
                             .has::<PlayerMovement>()
                             .has::<TerminalTransform>(),
                         Query::new().has::<Input>(),
+                        Query::new().has_where::<GameState>(|state| {
+                            !matches!(
+                                state.phase,
+                                GamePhase::Achievements
+                                    | GamePhase::Menu
+                                    | GamePhase::Editor
+                                    | GamePhase::Interlude
+                            )
+                        }),
+                        Query::new().has::<Player>().has::<Sprite>(),
+                        Query::new().has::<Settings>(),
                     ],
                     movement,
                 ),
@@ -83,6 +89,8 @@ impl SystemsGenerator for PlayerSystemsGenerator {
                             .has::<Combat>(),
                         Query::new()
                             .has_where::<Input>(|input| input.is_key_pressed(&Keycode::Space)),
+                        Query::new().has_where::<GameState>(|state| state.is_playing()),
+                        Query::new().has::<GameEvents>(),
                     ],
                     combat,
                 ),
@@ -93,10 +101,49 @@ impl SystemsGenerator for PlayerSystemsGenerator {
                     vec![
                         Query::new().has::<Player>(),
                         Query::new().has::<Scorekeeper>(),
+                        Query::new().has::<GameState>(),
+                        Query::new().has::<Stats>(),
+                        Query::new().has::<GameEvents>(),
+                        Query::new().has::<Localization>(),
+                    ],
+                    end_run,
+                ),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<Player>(),
+                        Query::new().has::<Scorekeeper>(),
+                        Query::new().has::<GameState>(),
+                        Query::new().has::<Stats>(),
+                        Query::new().has::<GameRng>(),
+                        Query::new().has::<Bonuses>(),
+                    ],
+                    reset_run,
+                ),
+            ),
+            (
+                EVENT_AFTER_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<GameEvents>(),
                         Query::new().has::<Enemy>(),
+                        Query::new().has::<Boss>(),
                         Query::new().has::<Bullet>(),
+                        Query::new().has::<Bunker>(),
+                    ],
+                    clear_board,
+                ),
+            ),
+            (
+                EVENT_AFTER_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<GameEvents>(),
+                        Query::new().has::<Player>(),
                     ],
-                    handle_player_death,
+                    lose_lives,
                 ),
             ),
         ]
@@ -104,16 +151,24 @@ impl SystemsGenerator for PlayerSystemsGenerator {
 }
 
 fn movement(results: Vec<QueryResultList>, _: GameCommandsArg) {
-    if let [player_results, input_results, ..] = &results[..] {
+    if let [player_results, input_results, unpaused_state_results, player_sprite_results, settings_results, ..] =
+        &results[..]
+    {
+        if unpaused_state_results.is_empty() {
+            return;
+        }
+
         let input = input_results.get_only::<Input>();
 
         let mut movement = player_results.get_only_mut::<PlayerMovement>();
         let mut transform = player_results.get_only_mut::<TerminalTransform>();
 
-        let movement_input_to_direction: MovementToDirectionMap = HashMap::from([
-            (Keycode::A, IntCoords2d::left()),
-            (Keycode::D, IntCoords2d::right()),
-        ]);
+        let movement_input_to_direction: MovementToDirectionMap = settings_results
+            .get_only::<Settings>()
+            .controls
+            .movement_keys()
+            .into_iter()
+            .collect();
 
         if movement.move_timer.elapsed_millis() >= MOVE_WAIT_TIME_MILLIS {
             if let Some(movement_direction) =
@@ -121,8 +176,16 @@ fn movement(results: Vec<QueryResultList>, _: GameCommandsArg) {
             {
                 transform.coords += *movement_direction;
 
+                let half_width = player_sprite_results
+                    .try_get_only::<Sprite>()
+                    .map(|sprite| sprite.width() / 2)
+                    .unwrap_or(0);
+
                 transform.coords = IntCoords2d::new(
-                    transform.coords.x().clamp(0, SCREEN_WIDTH as i64 - 1),
+                    transform
+                        .coords
+                        .x()
+                        .clamp(half_width, SCREEN_WIDTH as i64 - 1 - half_width),
                     transform.coords.y(),
                 );
 
@@ -153,51 +216,184 @@ fn get_pressed_movement_key<'a>(
 }
 
 fn combat(results: Vec<QueryResultList>, commands: GameCommandsArg) {
-    if let [player_query, input_with_shoot_button_pressed_results, ..] = &results[..] {
-        if input_with_shoot_button_pressed_results.len() > 0 {
+    if let [player_query, input_with_shoot_button_pressed_results, playing_state_results, game_events_results, ..] =
+        &results[..]
+    {
+        if !input_with_shoot_button_pressed_results.is_empty() && !playing_state_results.is_empty()
+        {
             for player_result in player_query {
                 let transform = player_result.components().get::<TerminalTransform>();
                 let mut combat = player_result.components().get_mut::<Combat>();
 
-                if combat.shoot_timer.elapsed_millis() >= SHOOT_WAIT_TIME_MILLIS {
+                if combat.shoot_timer.elapsed_millis() >= combat.shoot_wait_millis {
+                    let bullet_coords = transform.coords + IntCoords2d::down();
+
                     commands
                         .borrow_mut()
                         .issue(GameCommand::AddEntity(make_bullet(
-                            transform.coords + IntCoords2d::down(),
+                            bullet_coords,
                             IntCoords2d::down(),
                             BulletType::Player,
                         )));
 
+                    game_events_results
+                        .get_only_mut::<GameEvents>()
+                        .emit(GameEvent::ShotFired {
+                            coords: bullet_coords,
+                            bullet_type: BulletType::Player,
+                        });
+
                     combat.shoot_timer.restart();
+                    combat.shoot_wait_millis = PLAYER_SHOOT_WAIT_MILLIS;
                 }
             }
         }
     }
 }
 
-fn handle_player_death(results: Vec<QueryResultList>, commands: GameCommandsArg) {
-    if let [player_results, scorekeeper_results, enemies_results, bullets_results, ..] =
+fn end_run(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [player_results, scorekeeper_results, game_state_results, stats_results, game_events_results, localization_results, ..] =
         &results[..]
     {
-        let mut player = player_results.get_only_mut::<Player>();
-        let mut scorekeeper = scorekeeper_results.get_only_mut::<Scorekeeper>();
+        let mut game_state = game_state_results.get_only_mut::<GameState>();
+
+        if player_results.get_only::<Player>().lives > 0 || game_state.phase == GamePhase::GameOver
+        {
+            return;
+        }
 
-        if player.lives == 0 {
-            scorekeeper.score = 0;
-            scorekeeper.level = 0;
+        let scorekeeper = scorekeeper_results.get_only::<Scorekeeper>();
+        let stats = stats_results.get_only::<Stats>();
 
-            player.lives = PLAYER_STARTING_LIVES;
+        game_events_results
+            .get_only_mut::<GameEvents>()
+            .emit(GameEvent::GameOver {
+                score: scorekeeper.score,
+                level: scorekeeper.level,
+                time_millis: stats.run_time_millis(),
+            });
 
-            for enemy_result in enemies_results {
-                commands
-                    .borrow_mut()
-                    .issue(GameCommand::DestroyEntity(*enemy_result.entity()));
+        let localization = localization_results.get_only::<Localization>();
+
+        let title = match game_state.mode {
+            GameMode::Survival => localization.current().format(
+                "summary.survival_over",
+                &[
+                    ("seconds", &(stats.run_time_millis() / 1000)),
+                    ("kills", &stats.run.total_kills()),
+                ],
+            ),
+            GameMode::Classic
+            | GameMode::Campaign
+            | GameMode::TimeAttack
+            | GameMode::Daily
+            | GameMode::PlayTest => localization.current().format(
+                "summary.game_over",
+                &[("waves", &scorekeeper.level.saturating_sub(1))],
+            ),
+        };
+
+        for card_line in make_summary_card(
+            &title,
+            &stats.run,
+            stats.run_time_millis(),
+            None,
+            localization.current(),
+        ) {
+            commands
+                .borrow_mut()
+                .issue(GameCommand::AddEntity(card_line));
+        }
+
+        game_state.enter(GamePhase::GameOver);
+    }
+}
+
+/// Sweeps away what's left of the run once it's over.
+fn clear_board(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [game_events_results, enemy_results, boss_results, bullet_results, bunker_results, ..] =
+        &results[..]
+    {
+        if !game_events_results
+            .get_only::<GameEvents>()
+            .iter()
+            .any(|event| matches!(event, GameEvent::GameOver { .. }))
+        {
+            return;
+        }
+
+        for result in enemy_results
+            .iter()
+            .chain(boss_results.iter())
+            .chain(bullet_results.iter())
+            .chain(bunker_results.iter())
+        {
+            commands
+                .borrow_mut()
+                .issue(GameCommand::DestroyEntity(*result.entity()));
+        }
+    }
+}
+
+/// Gets everything ready for the next run once the summary of the last one has been shown for long enough.
+fn reset_run(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [player_results, scorekeeper_results, game_state_results, stats_results, game_rng_results, bonuses_results, ..] =
+        &results[..]
+    {
+        let mut game_state = game_state_results.get_only_mut::<GameState>();
+
+        let is_summary_over = match game_state.phase {
+            GamePhase::GameOver => {
+                game_state.phase_timer.elapsed_millis() >= GAME_OVER_SUMMARY_WAIT_TIME
             }
+            GamePhase::RunComplete => {
+                game_state.phase_timer.elapsed_millis() >= RUN_COMPLETE_WAIT_TIME
+            }
+            _ => false,
+        };
+
+        if !is_summary_over {
+            return;
+        }
+
+        let mut scorekeeper = scorekeeper_results.get_only_mut::<Scorekeeper>();
+        scorekeeper.score = 0;
+        scorekeeper.level = 0;
+
+        player_results.get_only_mut::<Player>().lives = PLAYER_STARTING_LIVES;
+
+        stats_results.get_only_mut::<Stats>().reset();
+        bonuses_results
+            .get_only_mut::<Bonuses>()
+            .extra_lives_awarded = 0;
+        game_rng_results.get_only_mut::<GameRng>().start_new_run();
+
+        // A play-test goes back to the editor, which puts the mode back once it's tidied up after the run.
+        let next_phase = match game_state.mode {
+            GameMode::PlayTest => GamePhase::Editor,
+            GameMode::Classic
+            | GameMode::Campaign
+            | GameMode::Survival
+            | GameMode::TimeAttack
+            | GameMode::Daily => GamePhase::Menu,
+        };
+
+        // Survival and time attack have nothing of their own to tidy up, so they're left straight away.
+        if matches!(game_state.mode, GameMode::Survival | GameMode::TimeAttack) {
+            game_state.mode = GameMode::Classic;
+        }
+
+        game_state.enter(next_phase);
+    }
+}
+
+fn lose_lives(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [game_events_results, player_results, ..] = &results[..] {
+        let mut player = player_results.get_only_mut::<Player>();
 
-            for bullet_result in bullets_results {
-                commands
-                    .borrow_mut()
-                    .issue(GameCommand::DestroyEntity(*bullet_result.entity()));
+        for event in game_events_results.get_only::<GameEvents>().iter() {
+            if let GameEvent::PlayerHit { .. } = event {
+                player.lives = player.lives.saturating_sub(1);
             }
         }
     }
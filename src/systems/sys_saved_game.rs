@@ -0,0 +1,362 @@
+use thomas::{
+    GameCommand, GameCommandsArg, Input, IntCoords2d, Keycode, Query, QueryResultList, System,
+    SystemsGenerator, TerminalCollider, TerminalTransform, EVENT_AFTER_UPDATE, EVENT_CLEANUP,
+    EVENT_INIT, EVENT_UPDATE,
+};
+
+use crate::{
+    delete_file, load_key_values, make_boss, make_bullet, make_bunker, make_enemy, save_key_values,
+    Banners, Bonuses, Boss, Bullet, BulletType, Bunker, Combat, Combo, Enemy, EnemyMovement,
+    FormationStream, GameMode, GamePhase, GameRng, GameState, Localization, PausableTimer, Player,
+    ResumableGame, SavedBoss, SavedBullet, SavedBunker, SavedEnemy, SavedGame, SavedPlayer,
+    SavedStream, Scorekeeper, Stats, ThemeRole, BANNER_DURATION_MILLIS,
+    PLAYER_BULLET_COLLISION_LAYER, SAVED_GAME_FILE_NAME,
+};
+
+/// Saves the run in progress when the game is quit, and restores it when the player chooses to continue.
+pub struct SavedGameSystemsGenerator {
+    pub use_sprites: bool,
+}
+impl SystemsGenerator for SavedGameSystemsGenerator {
+    fn generate(&self) -> Vec<(&'static str, System)> {
+        let use_sprites = self.use_sprites;
+
+        vec![
+            (
+                EVENT_INIT,
+                System::new(vec![], |_, commands| {
+                    let (saved, error) = match load_key_values(SAVED_GAME_FILE_NAME) {
+                        Ok(values) if !values.is_empty() => match SavedGame::from_saved(&values) {
+                            Ok(saved) => (Some(saved), None),
+                            Err(error) => (None, Some(error)),
+                        },
+                        _ => (None, None),
+                    };
+
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(ResumableGame {
+                            saved,
+                            error,
+                            is_resuming: false,
+                        })]));
+                }),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new()
+                            .has_where::<Input>(|input| input.is_key_down(&Keycode::Escape)),
+                        Query::new().has_where::<GameState>(|state| {
+                            matches!(
+                                state.phase,
+                                GamePhase::Playing | GamePhase::WaveSummary | GamePhase::Interlude
+                            ) && state.mode != GameMode::PlayTest
+                        }),
+                        Query::new().has::<Player>(),
+                    ],
+                    leave_run,
+                ),
+            ),
+            (
+                EVENT_AFTER_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<ResumableGame>(),
+                        Query::new().has::<GameState>(),
+                        Query::new().has::<Scorekeeper>(),
+                        Query::new()
+                            .has::<Player>()
+                            .has::<TerminalTransform>()
+                            .has::<Combat>(),
+                        Query::new().has::<GameRng>(),
+                        Query::new().has::<Stats>(),
+                        Query::new().has::<Bonuses>(),
+                        Query::new().has::<Banners>(),
+                        Query::new().has::<Localization>(),
+                        Query::new().has::<EnemyMovement>(),
+                        Query::new().has::<Combo>(),
+                        Query::new().has::<FormationStream>(),
+                    ],
+                    move |results, commands| resume_game(results, commands, use_sprites),
+                ),
+            ),
+            (
+                EVENT_CLEANUP,
+                System::new(
+                    vec![
+                        Query::new().has::<GameState>(),
+                        Query::new().has::<Scorekeeper>(),
+                        Query::new()
+                            .has::<Player>()
+                            .has::<TerminalTransform>()
+                            .has::<Combat>(),
+                        Query::new().has::<GameRng>(),
+                        Query::new().has::<Enemy>().has::<TerminalTransform>(),
+                        Query::new().has::<Boss>().has::<TerminalTransform>(),
+                        Query::new()
+                            .has::<Bullet>()
+                            .has::<TerminalTransform>()
+                            .has::<TerminalCollider>(),
+                        Query::new().has::<Stats>(),
+                        Query::new().has::<Bonuses>(),
+                        Query::new().has::<Bunker>().has::<TerminalTransform>(),
+                        Query::new().has::<EnemyMovement>(),
+                        Query::new().has::<Combo>(),
+                        Query::new().has::<FormationStream>(),
+                    ],
+                    save_game,
+                ),
+            ),
+        ]
+    }
+}
+
+/// Saves and quits a run that can be continued. Any other run ends as if the last life was lost, so a campaign
+/// picks up from its last checkpoint and a daily challenge keeps the result it got to.
+fn leave_run(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [escape_pressed_results, mid_run_state_results, player_results, ..] = &results[..] {
+        if escape_pressed_results.is_empty() || mid_run_state_results.is_empty() {
+            return;
+        }
+
+        if mid_run_state_results
+            .get_only::<GameState>()
+            .mode
+            .is_resumable()
+        {
+            commands.borrow_mut().issue(GameCommand::Quit);
+        } else {
+            player_results.get_only_mut::<Player>().lives = 0;
+        }
+    }
+}
+
+fn resume_game(results: Vec<QueryResultList>, commands: GameCommandsArg, use_sprites: bool) {
+    if let [resumable_game_results, game_state_results, scorekeeper_results, player_results, game_rng_results, stats_results, bonuses_results, banners_results, localization_results, enemy_movement_results, combo_results, formation_stream_results, ..] =
+        &results[..]
+    {
+        let mut resumable_game = resumable_game_results.get_only_mut::<ResumableGame>();
+
+        if !resumable_game.is_resuming {
+            return;
+        }
+
+        resumable_game.is_resuming = false;
+
+        let Some(saved) = resumable_game.saved.take() else {
+            return;
+        };
+
+        // Continuing uses the save up, so an old run can't be picked up again after this one ends.
+        let _ = delete_file(SAVED_GAME_FILE_NAME);
+
+        let mut scorekeeper = scorekeeper_results.get_only_mut::<Scorekeeper>();
+        scorekeeper.score = saved.score;
+        scorekeeper.level = saved.level;
+
+        player_results.get_only_mut::<Player>().lives = saved.player.lives;
+
+        let mut transform = player_results.get_only_mut::<TerminalTransform>();
+        transform.coords = IntCoords2d::new(saved.player.x, transform.coords.y());
+
+        let mut combat = player_results.get_only_mut::<Combat>();
+        combat.shoot_timer.restart();
+        combat.shoot_wait_millis = saved.player.shoot_wait_millis;
+
+        game_rng_results
+            .get_only_mut::<GameRng>()
+            .resume_run(saved.seed, saved.rng_position);
+
+        let mut stats = stats_results.get_only_mut::<Stats>();
+        stats.run = saved.run_stats;
+        stats.wave = saved.wave_stats;
+
+        bonuses_results
+            .get_only_mut::<Bonuses>()
+            .extra_lives_awarded = saved.extra_lives_awarded;
+
+        let mut combo = combo_results.get_only_mut::<Combo>();
+        combo.chain = saved.combo_chain;
+
+        if combo.chain > 0 {
+            combo.window_timer.restart();
+        }
+
+        if let Some(stream) = saved.stream {
+            let mut formation_stream = formation_stream_results.get_only_mut::<FormationStream>();
+            formation_stream.descend_timer.restart();
+            formation_stream.rows_spawned = stream.rows_spawned;
+            formation_stream.seconds_scored = stream.seconds_scored;
+        }
+
+        for enemy in saved.enemies {
+            for enemy_entity in make_enemy(enemy.coords, enemy.kind, use_sprites) {
+                commands
+                    .borrow_mut()
+                    .issue(GameCommand::AddEntity(enemy_entity));
+            }
+        }
+
+        if let Some(saved_boss) = saved.boss {
+            let mut boss = Boss::new(saved_boss.max_hp);
+            boss.hp = saved_boss.hp;
+            boss.direction = saved_boss.direction;
+
+            for boss_entity in make_boss(saved_boss.coords, boss) {
+                commands
+                    .borrow_mut()
+                    .issue(GameCommand::AddEntity(boss_entity));
+            }
+        }
+
+        for bullet in saved.bullets {
+            commands
+                .borrow_mut()
+                .issue(GameCommand::AddEntity(make_bullet(
+                    bullet.coords,
+                    bullet.direction,
+                    bullet.bullet_type,
+                )));
+        }
+
+        for bunker in saved.bunkers {
+            commands
+                .borrow_mut()
+                .issue(GameCommand::AddEntity(make_bunker(bunker.coords, bunker.hp)));
+        }
+
+        enemy_movement_results
+            .get_only_mut::<EnemyMovement>()
+            .direction = saved.march_direction;
+
+        let mut game_state = game_state_results.get_only_mut::<GameState>();
+        game_state.mode = saved.mode;
+
+        if saved.is_between_waves {
+            // The next wave follows once the summary's wait is up, as it would have.
+            stats.wave_timer.stop();
+            game_state.enter(GamePhase::WaveSummary);
+        } else {
+            stats.wave_timer = PausableTimer::start_from(saved.wave_time_millis);
+            game_state.enter(GamePhase::Playing);
+
+            let locale = localization_results.get_only::<Localization>();
+            let (key, role) = if saved.boss.is_some() {
+                ("banner.boss_wave", ThemeRole::Boss)
+            } else {
+                ("banner.wave", ThemeRole::Highlight)
+            };
+
+            banners_results.get_only_mut::<Banners>().show(
+                locale.current().format(key, &[("level", &saved.level)]),
+                role,
+                BANNER_DURATION_MILLIS,
+            );
+        }
+    }
+}
+
+fn save_game(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [game_state_results, scorekeeper_results, player_results, game_rng_results, enemy_results, boss_results, bullet_results, stats_results, bonuses_results, bunker_results, enemy_movement_results, combo_results, formation_stream_results, ..] =
+        &results[..]
+    {
+        let game_state = game_state_results.get_only::<GameState>();
+        let scorekeeper = scorekeeper_results.get_only::<Scorekeeper>();
+        let player = player_results.get_only::<Player>();
+
+        let is_mid_run = matches!(
+            game_state.phase,
+            GamePhase::Playing | GamePhase::WaveSummary | GamePhase::Achievements
+        ) && game_state.mode.is_resumable()
+            && scorekeeper.level > 0
+            && player.lives > 0;
+
+        if !is_mid_run {
+            return;
+        }
+
+        let combat = player_results.get_only::<Combat>();
+        let game_rng = game_rng_results.get_only::<GameRng>();
+        let stats = stats_results.get_only::<Stats>();
+
+        let saved = SavedGame {
+            mode: game_state.mode,
+            score: scorekeeper.score,
+            level: scorekeeper.level,
+            is_between_waves: game_state.phase == GamePhase::WaveSummary,
+            seed: game_rng.seed,
+            rng_position: game_rng.position(),
+            player: SavedPlayer {
+                x: player_results.get_only::<TerminalTransform>().coords.x(),
+                lives: player.lives,
+                shoot_wait_millis: combat
+                    .shoot_wait_millis
+                    .saturating_sub(combat.shoot_timer.elapsed_millis()),
+            },
+            enemies: enemy_results
+                .iter()
+                .map(|enemy_result| {
+                    let enemy = enemy_result.components().get::<Enemy>();
+
+                    // Divers are saved back in formation.
+                    SavedEnemy {
+                        kind: enemy.kind,
+                        coords: enemy.formation_coords(
+                            enemy_result.components().get::<TerminalTransform>().coords,
+                        ),
+                    }
+                })
+                .collect(),
+            boss: boss_results.iter().next().map(|boss_result| {
+                let boss = boss_result.components().get::<Boss>();
+
+                SavedBoss {
+                    coords: boss_result.components().get::<TerminalTransform>().coords,
+                    hp: boss.hp,
+                    max_hp: boss.max_hp,
+                    direction: boss.direction,
+                }
+            }),
+            bullets: bullet_results
+                .iter()
+                .map(|bullet_result| SavedBullet {
+                    bullet_type: if bullet_result.components().get::<TerminalCollider>().layer
+                        == PLAYER_BULLET_COLLISION_LAYER
+                    {
+                        BulletType::Player
+                    } else {
+                        BulletType::Enemy
+                    },
+                    coords: bullet_result.components().get::<TerminalTransform>().coords,
+                    direction: bullet_result.components().get::<Bullet>().direction,
+                })
+                .collect(),
+            bunkers: bunker_results
+                .iter()
+                .map(|bunker_result| SavedBunker {
+                    coords: bunker_result.components().get::<TerminalTransform>().coords,
+                    hp: bunker_result.components().get::<Bunker>().hp,
+                })
+                .collect(),
+            march_direction: enemy_movement_results.get_only::<EnemyMovement>().direction,
+            extra_lives_awarded: bonuses_results.get_only::<Bonuses>().extra_lives_awarded,
+            combo_chain: combo_results.get_only::<Combo>().chain,
+            wave_time_millis: stats.wave_timer.elapsed_millis(),
+            stream: (game_state.mode == GameMode::Survival).then(|| {
+                let formation_stream = formation_stream_results.get_only::<FormationStream>();
+
+                SavedStream {
+                    rows_spawned: formation_stream.rows_spawned,
+                    seconds_scored: formation_stream.seconds_scored,
+                }
+            }),
+            run_stats: stats.run.clone(),
+            wave_stats: stats.wave.clone(),
+        };
+
+        // Nothing more can be done about a failed save on the way out.
+        let _ = save_key_values(SAVED_GAME_FILE_NAME, &saved.to_saved());
+    }
+}
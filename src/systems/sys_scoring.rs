@@ -0,0 +1,51 @@
+use thomas::{
+    GameCommandsArg, Query, QueryResultList, System, SystemsGenerator, EVENT_AFTER_UPDATE,
+};
+
+use crate::{Combo, DestroyedBy, GameEvent, GameEvents, Scorekeeper};
+
+pub struct ScoringSystemsGenerator {}
+impl SystemsGenerator for ScoringSystemsGenerator {
+    fn generate(&self) -> Vec<(&'static str, System)> {
+        vec![(
+            EVENT_AFTER_UPDATE,
+            System::new(
+                vec![
+                    Query::new().has::<GameEvents>(),
+                    Query::new().has::<Scorekeeper>(),
+                    Query::new().has::<Combo>(),
+                ],
+                score_events,
+            ),
+        )]
+    }
+}
+
+fn score_events(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [game_events_results, scorekeeper_results, combo_results, ..] = &results[..] {
+        let mut scorekeeper = scorekeeper_results.get_only_mut::<Scorekeeper>();
+        let mut combo = combo_results.get_only_mut::<Combo>();
+
+        for event in game_events_results.get_only::<GameEvents>().iter() {
+            match event {
+                // Only shooting an invader down earns anything. One that crashes into the player costs them a life.
+                GameEvent::EnemyDestroyed {
+                    by: DestroyedBy::Collision,
+                    ..
+                } => {}
+                GameEvent::EnemyDestroyed { kind, .. } => {
+                    combo.register_hit();
+
+                    scorekeeper.add_points(kind.point_value() * combo.multiplier());
+                }
+                GameEvent::EnemyHit { .. } => {
+                    combo.register_hit();
+                }
+                GameEvent::PlayerHit { .. } => {
+                    combo.break_chain();
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use rand::{thread_rng, Rng};
+use thomas::{
+    GameCommand, GameCommandsArg, IntCoords2d, Layer, Query, QueryResultList, System,
+    SystemsGenerator, TerminalCamera, TerminalRenderer, TerminalTransform, EVENT_INIT,
+    EVENT_UPDATE,
+};
+
+use crate::{ScreenEffectSettings, ScreenEffects, ScreenFlash, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+pub struct ScreenEffectsSystemsGenerator {
+    pub settings: ScreenEffectSettings,
+}
+impl SystemsGenerator for ScreenEffectsSystemsGenerator {
+    fn generate(&self) -> Vec<(&'static str, System)> {
+        let settings = self.settings;
+
+        vec![
+            (
+                EVENT_INIT,
+                System::new(vec![], move |_, commands| {
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(ScreenEffects::new(
+                            settings,
+                        ))]));
+                }),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<ScreenEffects>(),
+                        Query::new()
+                            .has_where::<TerminalCamera>(|camera| camera.is_main)
+                            .has::<TerminalTransform>(),
+                    ],
+                    shake_camera,
+                ),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<ScreenEffects>(),
+                        Query::new().has::<ScreenFlash>(),
+                    ],
+                    flash_screen,
+                ),
+            ),
+        ]
+    }
+}
+
+fn shake_camera(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [screen_effects_results, main_camera_results, ..] = &results[..] {
+        let Some(mut camera_transform) =
+            main_camera_results.try_get_only_mut::<TerminalTransform>()
+        else {
+            return;
+        };
+
+        let mut screen_effects = screen_effects_results.get_only_mut::<ScreenEffects>();
+
+        camera_transform.coords = if screen_effects.is_shaking() {
+            let amplitude = screen_effects.shake_amplitude;
+
+            IntCoords2d::new(
+                thread_rng().gen_range(-amplitude..=amplitude),
+                // Cells are about twice as tall as they are wide, so the same amplitude vertically looks much bigger.
+                thread_rng().gen_range(-amplitude / 2..=amplitude / 2),
+            )
+        } else {
+            screen_effects.shake_amplitude = 0;
+
+            IntCoords2d::zero()
+        };
+    }
+}
+
+fn flash_screen(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [screen_effects_results, screen_flash_results, ..] = &results[..] {
+        let mut screen_effects = screen_effects_results.get_only_mut::<ScreenEffects>();
+        let pending_flash_color = screen_effects.pending_flash_color.take();
+
+        if pending_flash_color.is_some() || !screen_effects.is_flashing() {
+            for screen_flash_result in screen_flash_results {
+                commands
+                    .borrow_mut()
+                    .issue(GameCommand::DestroyEntity(*screen_flash_result.entity()));
+            }
+        }
+
+        if let Some(flash_color) = pending_flash_color {
+            for y in 0..SCREEN_HEIGHT {
+                for x in 0..SCREEN_WIDTH {
+                    commands.borrow_mut().issue(GameCommand::AddEntity(vec![
+                        Box::new(ScreenFlash {}),
+                        Box::new(TerminalTransform {
+                            coords: IntCoords2d::new(x as i64, y as i64),
+                        }),
+                        Box::new(TerminalRenderer {
+                            display: ' ',
+                            layer: Layer::furthest_background(),
+                            foreground_color: None,
+                            background_color: Some(flash_color),
+                        }),
+                    ]));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::{cell::RefCell, rc::Rc};
+
+use thomas::{
+    GameCommand, GameCommandsArg, Priority, Query, QueryResultList, System, SystemsGenerator,
+    EVENT_AFTER_UPDATE, EVENT_BEFORE_UPDATE, EVENT_CLEANUP, EVENT_INIT,
+};
+
+use crate::{
+    Config, GameEvent, GameEvents, GameRng, Levels, Player, Scorekeeper, Settings, Telemetry,
+    TelemetryEvent, TelemetryWriter, COMBO_WINDOW_MILLIS, ENEMY_POINT_VALUE, PLAYER_STARTING_LIVES,
+    SCREEN_HEIGHT, SCREEN_WIDTH,
+};
+
+/// Sets up the state that lives for the whole session, regardless of how many runs are played.
+pub struct SessionSystemsGenerator {
+    pub config: Config,
+    pub telemetry_writer: Rc<RefCell<Option<TelemetryWriter>>>,
+}
+impl SystemsGenerator for SessionSystemsGenerator {
+    fn generate(&self) -> Vec<(&'static str, System)> {
+        let seed = self.config.seed;
+        let telemetry_writer = Rc::clone(&self.telemetry_writer);
+
+        vec![
+            (
+                EVENT_INIT,
+                System::new(vec![], move |_, commands| {
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(GameRng::new(seed))]));
+
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(Telemetry {
+                            writer: telemetry_writer.borrow_mut().take(),
+                        })]));
+
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(GameEvents::default())]));
+                }),
+            ),
+            (
+                EVENT_BEFORE_UPDATE,
+                System::new_with_priority(
+                    Priority::highest(),
+                    vec![Query::new().has::<GameEvents>()],
+                    |results, _| {
+                        if let [game_events_results, ..] = &results[..] {
+                            game_events_results.get_only_mut::<GameEvents>().events.clear();
+                        }
+                    },
+                ),
+            ),
+            (
+                EVENT_AFTER_UPDATE,
+                // Runs after the other subscribers so it records the state they leave behind, like lives remaining.
+                System::new_with_priority(
+                    Priority::lower_than(&Priority::default()),
+                    vec![
+                        Query::new().has::<GameEvents>(),
+                        Query::new().has::<Telemetry>(),
+                        Query::new().has::<Scorekeeper>(),
+                        Query::new().has::<Player>(),
+                        Query::new().has::<Settings>(),
+                        Query::new().has::<Levels>(),
+                    ],
+                    record_telemetry,
+                ),
+            ),
+            (
+                EVENT_CLEANUP,
+                System::new(vec![Query::new().has::<Telemetry>()], |results, _| {
+                    if let [telemetry_results, ..] = &results[..] {
+                        // Dropping the writer flushes anything still queued.
+                        telemetry_results.get_only_mut::<Telemetry>().writer.take();
+                    }
+                }),
+            ),
+        ]
+    }
+}
+
+fn record_telemetry(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [game_events_results, telemetry_results, scorekeeper_results, player_results, settings_results, levels_results, ..] =
+        &results[..]
+    {
+        let telemetry = telemetry_results.get_only::<Telemetry>();
+        let level = scorekeeper_results.get_only::<Scorekeeper>().level;
+
+        for event in game_events_results.get_only::<GameEvents>().iter() {
+            match *event {
+                GameEvent::RunStarted { seed } => {
+                    let difficulty = settings_results.get_only::<Settings>().difficulty;
+
+                    telemetry.record(TelemetryEvent::RunStart {
+                        seed,
+                        config: vec![
+                            ("version", String::from(env!("CARGO_PKG_VERSION"))),
+                            ("screen_width", SCREEN_WIDTH.to_string()),
+                            ("screen_height", SCREEN_HEIGHT.to_string()),
+                            ("starting_lives", PLAYER_STARTING_LIVES.to_string()),
+                            ("enemy_point_value", ENEMY_POINT_VALUE.to_string()),
+                            ("difficulty", String::from(difficulty.name())),
+                            (
+                                "enemy_shoot_chance",
+                                difficulty.enemy_shoot_chance().to_string(),
+                            ),
+                            (
+                                "enemy_move_wait_time_ms",
+                                difficulty.enemy_move_wait_millis().to_string(),
+                            ),
+                            (
+                                "enemy_dive_wait_time_ms",
+                                difficulty.enemy_dive_wait_millis().to_string(),
+                            ),
+                            ("combo_window_ms", COMBO_WINDOW_MILLIS.to_string()),
+                            (
+                                "level",
+                                levels_results
+                                    .get_only::<Levels>()
+                                    .for_wave(level)
+                                    .name
+                                    .clone(),
+                            ),
+                        ],
+                    });
+                }
+                GameEvent::EnemyDestroyed { kind, coords, .. } => {
+                    telemetry.record(TelemetryEvent::EnemyDestroyed {
+                        kind,
+                        x: coords.x(),
+                        y: coords.y(),
+                        level,
+                    });
+                }
+                GameEvent::PlayerHit { coords } => {
+                    telemetry.record(TelemetryEvent::PlayerHit {
+                        x: coords.x(),
+                        y: coords.y(),
+                        lives_remaining: player_results.get_only::<Player>().lives,
+                        level,
+                    });
+                }
+                GameEvent::WaveCleared {
+                    level,
+                    time_millis,
+                    accuracy,
+                } => {
+                    telemetry.record(TelemetryEvent::LevelCleared {
+                        level,
+                        time_millis,
+                        accuracy,
+                    });
+                }
+                GameEvent::GameOver {
+                    score,
+                    level,
+                    time_millis,
+                } => {
+                    telemetry.record(TelemetryEvent::GameOver {
+                        score,
+                        level,
+                        time_millis,
+                    });
+                }
+                GameEvent::EnemyHit { .. }
+                | GameEvent::BunkerHit { .. }
+                | GameEvent::ShotFired { .. }
+                | GameEvent::WaveStarted { .. }
+                | GameEvent::FormationStepped
+                | GameEvent::CampaignCompleted { .. }
+                | GameEvent::TimeAttackCompleted { .. } => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use thomas::{
+    Entity, GameCommand, GameCommandsArg, IntCoords2d, Query, QueryResultList, System,
+    SystemsGenerator, TerminalTransform, EVENT_AFTER_UPDATE,
+};
+
+use crate::{Sprite, SpriteCell};
+
+pub struct SpriteSystemsGenerator {}
+impl SystemsGenerator for SpriteSystemsGenerator {
+    fn generate(&self) -> Vec<(&'static str, System)> {
+        vec![(
+            EVENT_AFTER_UPDATE,
+            System::new(
+                vec![
+                    Query::new().has::<Sprite>().has::<TerminalTransform>(),
+                    Query::new().has::<SpriteCell>().has::<TerminalTransform>(),
+                ],
+                follow_sprites,
+            ),
+        )]
+    }
+}
+
+/// Returns the entity whose sprite `entity` is a cell of, or `entity` itself if it isn't a sprite cell.
+pub fn get_sprite_owner(
+    entity: Entity,
+    sprite_results: &QueryResultList,
+    sprite_cell_results: &QueryResultList,
+) -> Entity {
+    sprite_cell_results
+        .iter()
+        .find(|sprite_cell_result| *sprite_cell_result.entity() == entity)
+        .and_then(|sprite_cell_result| {
+            let sprite_id = sprite_cell_result
+                .components()
+                .get::<SpriteCell>()
+                .sprite_id;
+
+            sprite_results
+                .iter()
+                .find(|sprite_result| sprite_result.components().get::<Sprite>().id == sprite_id)
+        })
+        .map(|sprite_result| *sprite_result.entity())
+        .unwrap_or(entity)
+}
+
+fn follow_sprites(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [sprite_results, sprite_cell_results, ..] = &results[..] {
+        let sprite_coords: HashMap<u64, IntCoords2d> = sprite_results
+            .iter()
+            .map(|sprite_result| {
+                (
+                    sprite_result.components().get::<Sprite>().id,
+                    sprite_result.components().get::<TerminalTransform>().coords,
+                )
+            })
+            .collect();
+
+        for sprite_cell_result in sprite_cell_results {
+            let sprite_cell = sprite_cell_result.components().get::<SpriteCell>();
+
+            if let Some(coords) = sprite_coords.get(&sprite_cell.sprite_id) {
+                sprite_cell_result
+                    .components()
+                    .get_mut::<TerminalTransform>()
+                    .coords = *coords + sprite_cell.offset;
+            } else {
+                commands
+                    .borrow_mut()
+                    .issue(GameCommand::DestroyEntity(*sprite_cell_result.entity()));
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use thomas::{
+    GameCommand, GameCommandsArg, Query, QueryResultList, System, SystemsGenerator,
+    EVENT_AFTER_UPDATE, EVENT_INIT, EVENT_UPDATE,
+};
+
+use crate::{
+    BulletType, DestroyedBy, GameEvent, GameEvents, GamePhase, GameState, PausableTimer, RunStats,
+    Stats, SummaryCard,
+};
+
+pub struct StatsSystemsGenerator {}
+impl SystemsGenerator for StatsSystemsGenerator {
+    fn generate(&self) -> Vec<(&'static str, System)> {
+        vec![
+            (
+                EVENT_INIT,
+                System::new(vec![], |_, commands| {
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(Stats {
+                            run: RunStats::default(),
+                            wave: RunStats::default(),
+                            wave_timer: PausableTimer::new(),
+                        })]));
+                }),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has_where::<GameState>(|state| {
+                            matches!(
+                                state.phase,
+                                GamePhase::Playing
+                                    | GamePhase::Menu
+                                    | GamePhase::Editor
+                                    | GamePhase::Interlude
+                            )
+                        }),
+                        Query::new().has::<SummaryCard>(),
+                    ],
+                    |results, commands| {
+                        if let [card_free_state_results, summary_card_results, ..] = &results[..] {
+                            if !card_free_state_results.is_empty() {
+                                for summary_card_result in summary_card_results {
+                                    commands.borrow_mut().issue(GameCommand::DestroyEntity(
+                                        *summary_card_result.entity(),
+                                    ));
+                                }
+                            }
+                        }
+                    },
+                ),
+            ),
+            (
+                EVENT_AFTER_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<GameEvents>(),
+                        Query::new().has::<Stats>(),
+                    ],
+                    record_stats,
+                ),
+            ),
+        ]
+    }
+}
+
+fn record_stats(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [game_events_results, stats_results, ..] = &results[..] {
+        let mut stats = stats_results.get_only_mut::<Stats>();
+
+        for event in game_events_results.get_only::<GameEvents>().iter() {
+            match event {
+                GameEvent::EnemyDestroyed { kind, by, .. } => {
+                    // Only a shot that lands counts as a hit, so crashes don't count towards accuracy.
+                    if *by == DestroyedBy::PlayerBullet {
+                        stats.record_hit();
+                    }
+
+                    stats.record_kill(*kind);
+                }
+                GameEvent::EnemyHit { .. } => stats.record_hit(),
+                GameEvent::PlayerHit { .. } => stats.record_life_lost(),
+                GameEvent::ShotFired {
+                    bullet_type: BulletType::Player,
+                    ..
+                } => stats.record_shot(),
+                _ => {}
+            }
+        }
+    }
+}
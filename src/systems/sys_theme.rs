@@ -0,0 +1,278 @@
+use thomas::{
+    GameCommand, GameCommandsArg, Input, Keycode, Priority, Query, QueryResultList, Rgb, System,
+    SystemsGenerator, TerminalRenderer, TerminalRendererState, Text, EVENT_AFTER_UPDATE,
+    EVENT_INIT, EVENT_UPDATE,
+};
+
+use crate::{
+    make_toast, Animation, Localization, TerminalAdapted, TerminalSupport, Theme, ThemeRole,
+    Themed, Themes, Toast,
+};
+
+pub struct ThemeSystemsGenerator {
+    /// The theme to start with. Classic when absent.
+    pub theme: Option<Theme>,
+    pub terminal_support: TerminalSupport,
+}
+impl SystemsGenerator for ThemeSystemsGenerator {
+    fn generate(&self) -> Vec<(&'static str, System)> {
+        let theme = self.theme.clone();
+        let terminal_support = self.terminal_support;
+
+        vec![
+            (
+                EVENT_INIT,
+                System::new(vec![], move |_, commands| {
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(Themes::new(
+                            theme.clone(),
+                            &terminal_support,
+                        ))]));
+                }),
+            ),
+            (
+                EVENT_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has_where::<Input>(|input| input.is_key_down(&Keycode::T)),
+                        Query::new().has::<Themes>(),
+                        Query::new().has::<Toast>(),
+                        Query::new().has::<Localization>(),
+                    ],
+                    switch_theme,
+                ),
+            ),
+            (
+                EVENT_AFTER_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<Themes>(),
+                        Query::new().has::<Themed>().has::<TerminalRenderer>(),
+                        Query::new().has::<Themed>().has::<Text>(),
+                        Query::new().has::<Themed>().has::<Animation>(),
+                        Query::new().has::<TerminalRendererState>(),
+                    ],
+                    restyle,
+                ),
+            ),
+            (
+                EVENT_AFTER_UPDATE,
+                // Runs after everything else has had its say about how things look this frame.
+                System::new_with_priority(
+                    Priority::lower_than(&Priority::default()),
+                    vec![
+                        Query::new()
+                            .has::<TerminalRenderer>()
+                            .has_no::<TerminalAdapted>(),
+                        Query::new()
+                            .has::<Text>()
+                            .has_no::<TerminalRenderer>()
+                            .has_no::<TerminalAdapted>(),
+                        Query::new().has::<Animation>(),
+                    ],
+                    move |results, commands| {
+                        adapt_to_terminal(results, commands, &terminal_support)
+                    },
+                ),
+            ),
+        ]
+    }
+}
+
+fn switch_theme(results: Vec<QueryResultList>, commands: GameCommandsArg) {
+    if let [t_pressed_results, themes_results, toast_results, localization_results, ..] =
+        &results[..]
+    {
+        if t_pressed_results.is_empty() {
+            return;
+        }
+
+        let mut themes = themes_results.get_only_mut::<Themes>();
+
+        themes.cycle();
+
+        commands
+            .borrow_mut()
+            .issue(GameCommand::AddEntity(make_toast(
+                localization_results
+                    .get_only::<Localization>()
+                    .current()
+                    .format("toast.theme", &[("name", &themes.current().name)]),
+                ThemeRole::Highlight,
+                toast_results.len(),
+            )));
+    }
+}
+
+fn restyle(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [themes_results, themed_renderer_results, themed_text_results, themed_animation_results, renderer_state_results, ..] =
+        &results[..]
+    {
+        let themes = themes_results.get_only::<Themes>();
+        let theme = themes.current();
+
+        if let Some(mut renderer_state) =
+            renderer_state_results.try_get_only_mut::<TerminalRendererState>()
+        {
+            // Empty cells are drawn in the default background, so changing it repaints them on the next render.
+            renderer_state.options.default_background_color = Some(theme.background);
+        }
+
+        let classic_theme = Theme::classic();
+
+        for themed_renderer_result in themed_renderer_results {
+            let mut themed = themed_renderer_result.components().get_mut::<Themed>();
+
+            if themed.applied_theme == Some(themes.current) {
+                continue;
+            }
+
+            let previous_theme = themed
+                .applied_theme
+                .map(|index| &themes.themes[index])
+                .unwrap_or(&classic_theme);
+            let mut renderer = themed_renderer_result
+                .components()
+                .get_mut::<TerminalRenderer>();
+
+            renderer.foreground_color = restyle_color(
+                renderer.foreground_color,
+                themed.role,
+                previous_theme,
+                theme,
+            );
+
+            if themed.is_glyph_themed {
+                renderer.display =
+                    restyle_glyph(renderer.display, themed.role, previous_theme, theme);
+            }
+
+            // Animated entities would otherwise be drawn in the old colours again on their next frame.
+            if let Some(themed_animation_result) =
+                themed_animation_results
+                    .iter()
+                    .find(|themed_animation_result| {
+                        themed_animation_result.entity() == themed_renderer_result.entity()
+                    })
+            {
+                let mut animation = themed_animation_result.components().get_mut::<Animation>();
+
+                for frame in animation.frames.iter_mut() {
+                    frame.color = restyle_color(frame.color, themed.role, previous_theme, theme);
+
+                    if themed.is_glyph_themed {
+                        frame.display =
+                            restyle_glyph(frame.display, themed.role, previous_theme, theme);
+                    }
+                }
+            }
+
+            themed.applied_theme = Some(themes.current);
+        }
+
+        for themed_text_result in themed_text_results {
+            let mut themed = themed_text_result.components().get_mut::<Themed>();
+
+            if themed.applied_theme == Some(themes.current) {
+                continue;
+            }
+
+            let mut text = themed_text_result.components().get_mut::<Text>();
+
+            text.foreground_color = Some(theme.color(themed.role));
+
+            if text.background_color.is_some() {
+                text.background_color = Some(theme.background);
+            }
+
+            themed.applied_theme = Some(themes.current);
+        }
+    }
+}
+
+/// Swaps the previous theme's colour for the role with the new one. Any other colour is left alone.
+fn restyle_color(
+    color: Option<Rgb>,
+    role: ThemeRole,
+    previous_theme: &Theme,
+    theme: &Theme,
+) -> Option<Rgb> {
+    if color == Some(previous_theme.color(role)) {
+        Some(theme.color(role))
+    } else {
+        color
+    }
+}
+
+fn restyle_glyph(display: char, role: ThemeRole, previous_theme: &Theme, theme: &Theme) -> char {
+    previous_theme
+        .glyphs(role)
+        .iter()
+        .position(|glyph| *glyph == display)
+        .and_then(|index| theme.glyphs(role).get(index))
+        .copied()
+        .unwrap_or(display)
+}
+
+/// Catches the colours and glyphs that don't come from the theme, like explosions and particles. Each entity is
+/// adapted once, when it first appears, along with every frame of its animation. Anything drawn from the theme
+/// after that is already adapted.
+fn adapt_to_terminal(
+    results: Vec<QueryResultList>,
+    commands: GameCommandsArg,
+    terminal_support: &TerminalSupport,
+) {
+    if *terminal_support == TerminalSupport::full() {
+        return;
+    }
+
+    if let [renderer_results, text_results, animation_results, ..] = &results[..] {
+        let adapt_color =
+            |color: Option<Rgb>| color.map(|color| terminal_support.adapt_color(color));
+
+        for renderer_result in renderer_results {
+            let mut renderer = renderer_result.components().get_mut::<TerminalRenderer>();
+
+            renderer.display = terminal_support.adapt_glyph(renderer.display);
+            renderer.foreground_color = adapt_color(renderer.foreground_color);
+            renderer.background_color = adapt_color(renderer.background_color);
+
+            if let Some(animation_result) = animation_results
+                .iter()
+                .find(|animation_result| animation_result.entity() == renderer_result.entity())
+            {
+                for frame in animation_result
+                    .components()
+                    .get_mut::<Animation>()
+                    .frames
+                    .iter_mut()
+                {
+                    frame.display = terminal_support.adapt_glyph(frame.display);
+                    frame.color = adapt_color(frame.color);
+                }
+            }
+
+            commands
+                .borrow_mut()
+                .issue(GameCommand::AddComponentsToEntity(
+                    *renderer_result.entity(),
+                    vec![Box::new(TerminalAdapted {})],
+                ));
+        }
+
+        for text_result in text_results {
+            let mut text = text_result.components().get_mut::<Text>();
+
+            text.foreground_color = adapt_color(text.foreground_color);
+            text.background_color = adapt_color(text.background_color);
+
+            commands
+                .borrow_mut()
+                .issue(GameCommand::AddComponentsToEntity(
+                    *text_result.entity(),
+                    vec![Box::new(TerminalAdapted {})],
+                ));
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use thomas::{
+    GameCommand, GameCommandsArg, Query, QueryResultList, System, SystemsGenerator,
+    EVENT_AFTER_UPDATE, EVENT_INIT,
+};
+
+use crate::{
+    load_key_values, save_key_values, Banners, GameEvent, GameEvents, GameMode, GameState,
+    Localization, ThemeRole, TimeAttack, BANNER_DURATION_MILLIS, TIME_ATTACK_SPLITS_FILE_NAME,
+};
+
+/// Takes the splits of a time attack run, and keeps the fastest run's splits between sessions to race against.
+pub struct TimeAttackSystemsGenerator {}
+impl SystemsGenerator for TimeAttackSystemsGenerator {
+    fn generate(&self) -> Vec<(&'static str, System)> {
+        vec![
+            (
+                EVENT_INIT,
+                System::new(vec![], |_, commands| {
+                    commands
+                        .borrow_mut()
+                        .issue(GameCommand::AddEntity(vec![Box::new(
+                            TimeAttack::from_saved(
+                                &load_key_values(TIME_ATTACK_SPLITS_FILE_NAME).unwrap_or_default(),
+                            ),
+                        )]));
+                }),
+            ),
+            (
+                EVENT_AFTER_UPDATE,
+                System::new(
+                    vec![
+                        Query::new().has::<GameEvents>(),
+                        Query::new().has::<TimeAttack>(),
+                        Query::new().has::<GameState>(),
+                        Query::new().has::<Banners>(),
+                        Query::new().has::<Localization>(),
+                    ],
+                    record_splits,
+                ),
+            ),
+        ]
+    }
+}
+
+fn record_splits(results: Vec<QueryResultList>, _: GameCommandsArg) {
+    if let [game_events_results, time_attack_results, game_state_results, banners_results, localization_results, ..] =
+        &results[..]
+    {
+        if game_state_results.get_only::<GameState>().mode != GameMode::TimeAttack {
+            return;
+        }
+
+        let mut time_attack = time_attack_results.get_only_mut::<TimeAttack>();
+
+        for event in game_events_results.get_only::<GameEvents>().iter() {
+            match *event {
+                GameEvent::WaveStarted { level: 1, .. } => time_attack.splits.clear(),
+                GameEvent::WaveCleared { time_millis, .. } => time_attack.record_split(time_millis),
+                GameEvent::TimeAttackCompleted { .. } if time_attack.is_personal_best() => {
+                    time_attack.best_splits = time_attack.splits.clone();
+
+                    // Not being able to save isn't worth interrupting the game over. The splits still count for
+                    // this session.
+                    let _ = save_key_values(TIME_ATTACK_SPLITS_FILE_NAME, &time_attack.to_saved());
+
+                    banners_results.get_only_mut::<Banners>().show(
+                        String::from(
+                            localization_results
+                                .get_only::<Localization>()
+                                .current()
+                                .text("banner.personal_best"),
+                        ),
+                        ThemeRole::Positive,
+                        BANNER_DURATION_MILLIS,
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use std::{
+    fs::OpenOptions,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::mpsc::{self, Sender},
+    thread::{self, JoinHandle},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::EnemyKind;
+
+pub enum TelemetryEvent {
+    RunStart {
+        seed: u64,
+        config: Vec<(&'static str, String)>,
+    },
+    EnemyDestroyed {
+        kind: EnemyKind,
+        x: i64,
+        y: i64,
+        level: u64,
+    },
+    PlayerHit {
+        x: i64,
+        y: i64,
+        lives_remaining: u8,
+        level: u64,
+    },
+    LevelCleared {
+        level: u64,
+        time_millis: u128,
+        accuracy: f64,
+    },
+    GameOver {
+        score: u64,
+        level: u64,
+        time_millis: u128,
+    },
+}
+impl TelemetryEvent {
+    fn name(&self) -> &'static str {
+        match self {
+            TelemetryEvent::RunStart { .. } => "run_start",
+            TelemetryEvent::EnemyDestroyed { .. } => "enemy_destroyed",
+            TelemetryEvent::PlayerHit { .. } => "player_hit",
+            TelemetryEvent::LevelCleared { .. } => "level_cleared",
+            TelemetryEvent::GameOver { .. } => "game_over",
+        }
+    }
+
+    fn fields_json(&self) -> String {
+        match self {
+            TelemetryEvent::RunStart { seed, config } => format!(
+                "\"seed\":{seed},\"config\":{{{}}}",
+                config
+                    .iter()
+                    .map(|(key, value)| format!(
+                        "\"{}\":\"{}\"",
+                        escape_json(key),
+                        escape_json(value)
+                    ))
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+            TelemetryEvent::EnemyDestroyed { kind, x, y, level } => format!(
+                "\"kind\":\"{}\",\"x\":{x},\"y\":{y},\"level\":{level}",
+                kind.name()
+            ),
+            TelemetryEvent::PlayerHit {
+                x,
+                y,
+                lives_remaining,
+                level,
+            } => format!("\"x\":{x},\"y\":{y},\"lives_remaining\":{lives_remaining},\"level\":{level}"),
+            TelemetryEvent::LevelCleared {
+                level,
+                time_millis,
+                accuracy,
+            } => format!("\"level\":{level},\"time_ms\":{time_millis},\"accuracy\":{accuracy:.3}"),
+            TelemetryEvent::GameOver {
+                score,
+                level,
+                time_millis,
+            } => format!("\"score\":{score},\"level\":{level},\"time_ms\":{time_millis}"),
+        }
+    }
+
+    /// Serializes the event as a single JSON object, stamped with when it happened.
+    pub fn to_json(&self, unix_millis: u128, session_millis: u128) -> String {
+        format!(
+            "{{\"event\":\"{}\",\"unix_ms\":{unix_millis},\"session_ms\":{session_millis},{}}}",
+            self.name(),
+            self.fields_json()
+        )
+    }
+}
+
+/// Appends telemetry events to a file as JSON Lines. Serialization and file IO happen on a background thread so
+/// recording an event from a system only costs a channel send.
+pub struct TelemetryWriter {
+    sender: Option<Sender<(u128, u128, TelemetryEvent)>>,
+    worker: Option<JoinHandle<()>>,
+    session_start: Instant,
+}
+impl TelemetryWriter {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let (sender, receiver) = mpsc::channel::<(u128, u128, TelemetryEvent)>();
+
+        let worker = thread::spawn(move || {
+            let mut writer = BufWriter::new(file);
+
+            while let Ok(first) = receiver.recv() {
+                let mut lines = vec![first];
+                lines.extend(receiver.try_iter());
+
+                for (unix_millis, session_millis, event) in lines {
+                    if writeln!(writer, "{}", event.to_json(unix_millis, session_millis)).is_err() {
+                        return;
+                    }
+                }
+
+                if writer.flush().is_err() {
+                    return;
+                }
+            }
+        });
+
+        Ok(Self {
+            sender: Some(sender),
+            worker: Some(worker),
+            session_start: Instant::now(),
+        })
+    }
+
+    pub fn record(&self, event: TelemetryEvent) {
+        if let Some(sender) = &self.sender {
+            let unix_millis = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_millis())
+                .unwrap_or(0);
+
+            // If the worker has died there's nowhere for the event to go, and telemetry should never take the game
+            // down with it.
+            let _ = sender.send((
+                unix_millis,
+                self.session_start.elapsed().as_millis(),
+                event,
+            ));
+        }
+    }
+}
+impl Drop for TelemetryWriter {
+    fn drop(&mut self) {
+        self.sender.take();
+
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn escape_json(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for character in value.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_quotes_and_backslashes() {
+        assert_eq!(escape_json(r#"say "hi""#), r#"say \"hi\""#);
+        assert_eq!(escape_json(r"C:\levels\one.txt"), r"C:\\levels\\one.txt");
+    }
+
+    #[test]
+    fn escapes_control_characters() {
+        assert_eq!(escape_json("a\nb\rc\td"), r"a\nb\rc\td");
+        assert_eq!(escape_json("\u{0}\u{1b}\u{1f}"), r"\u0000\u001b\u001f");
+    }
+
+    #[test]
+    fn leaves_non_ascii_alone() {
+        assert_eq!(escape_json("café 宇宙 👾"), "café 宇宙 👾");
+        assert_eq!(escape_json("\u{7f}"), "\u{7f}");
+    }
+
+    #[test]
+    fn serializes_events_as_one_object() {
+        let event = TelemetryEvent::GameOver {
+            score: 1200,
+            level: 4,
+            time_millis: 65_000,
+        };
+
+        assert_eq!(
+            event.to_json(1000, 250),
+            r#"{"event":"game_over","unix_ms":1000,"session_ms":250,"score":1200,"level":4,"time_ms":65000}"#
+        );
+    }
+
+    #[test]
+    fn escapes_config_values() {
+        let event = TelemetryEvent::RunStart {
+            seed: 7,
+            config: vec![("theme", String::from("my \"theme\"\n"))],
+        };
+
+        assert_eq!(
+            event.to_json(0, 0),
+            r#"{"event":"run_start","unix_ms":0,"session_ms":0,"seed":7,"config":{"theme":"my \"theme\"\n"}}"#
+        );
+    }
+}
@@ -0,0 +1,337 @@
+use std::env;
+
+use thomas::Rgb;
+
+/// How many colours the terminal can show.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorDepth {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+impl ColorDepth {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "truecolor" | "24bit" => Some(Self::TrueColor),
+            "256" => Some(Self::Ansi256),
+            "16" => Some(Self::Ansi16),
+            _ => None,
+        }
+    }
+}
+
+/// The colour xterm and most other terminals use for each of the 16 basic colours.
+const ANSI_16_PALETTE: [Rgb; 16] = [
+    Rgb(0, 0, 0),
+    Rgb(205, 0, 0),
+    Rgb(0, 205, 0),
+    Rgb(205, 205, 0),
+    Rgb(0, 0, 238),
+    Rgb(205, 0, 205),
+    Rgb(0, 205, 205),
+    Rgb(229, 229, 229),
+    Rgb(127, 127, 127),
+    Rgb(255, 0, 0),
+    Rgb(0, 255, 0),
+    Rgb(255, 255, 0),
+    Rgb(92, 92, 255),
+    Rgb(255, 0, 255),
+    Rgb(0, 255, 255),
+    Rgb(255, 255, 255),
+];
+
+/// The levels each channel can take in the 6x6x6 colour cube of a 256-colour terminal.
+const ANSI_256_CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// What the terminal can display, so colours and glyphs can be swapped for ones it can show properly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TerminalSupport {
+    pub color_depth: ColorDepth,
+    pub is_unicode: bool,
+}
+impl TerminalSupport {
+    /// A terminal that can show anything.
+    pub fn full() -> Self {
+        Self {
+            color_depth: ColorDepth::TrueColor,
+            is_unicode: true,
+        }
+    }
+
+    /// Works out what the terminal supports from `COLORTERM`, `TERM` and the locale.
+    pub fn detect() -> Self {
+        Self::from_env(|name| env::var(name).ok())
+    }
+
+    /// Works out what the terminal supports from the environment variables `get_var` looks up. Empty variables
+    /// count as unset.
+    pub fn from_env(get_var: impl Fn(&str) -> Option<String>) -> Self {
+        let get_var = |name: &str| get_var(name).filter(|value| !value.is_empty());
+        let term = get_var("TERM").map(|term| term.to_lowercase());
+
+        let color_depth = if matches!(
+            get_var("COLORTERM").as_deref(),
+            Some("truecolor") | Some("24bit")
+        ) || get_var("WT_SESSION").is_some()
+        {
+            ColorDepth::TrueColor
+        } else {
+            match term.as_deref() {
+                // Without a TERM we're most likely in a Windows console, which handles 24-bit colour.
+                None => ColorDepth::TrueColor,
+                Some(term) if term.contains("truecolor") || term.contains("direct") => {
+                    ColorDepth::TrueColor
+                }
+                Some(term)
+                    if term.contains("256")
+                        || ["xterm", "screen", "tmux"]
+                            .iter()
+                            .any(|prefix| term.starts_with(prefix)) =>
+                {
+                    ColorDepth::Ansi256
+                }
+                Some(_) => ColorDepth::Ansi16,
+            }
+        };
+
+        let is_unicode = match ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .find_map(|name| get_var(name))
+        {
+            Some(locale) => {
+                let locale = locale.to_lowercase();
+
+                locale.contains("utf-8") || locale.contains("utf8")
+            }
+            None => term.is_none(),
+        };
+
+        Self {
+            color_depth,
+            is_unicode,
+        }
+    }
+
+    /// The closest colour in the terminal's palette. Thomas's renderer always writes 24-bit colour codes, so this
+    /// only changes which colour is asked for, not how it's sent.
+    pub fn adapt_color(&self, color: Rgb) -> Rgb {
+        match self.color_depth {
+            ColorDepth::TrueColor => color,
+            ColorDepth::Ansi256 => {
+                let cube_color = Rgb(
+                    nearest_cube_level(color.r()),
+                    nearest_cube_level(color.g()),
+                    nearest_cube_level(color.b()),
+                );
+
+                let average = (color.r() as u16 + color.g() as u16 + color.b() as u16) / 3;
+                let grey_level = (8 + 10 * ((average.saturating_sub(3) / 10).min(23))) as u8;
+                let grey = Rgb(grey_level, grey_level, grey_level);
+
+                if color_distance(color, grey) < color_distance(color, cube_color) {
+                    grey
+                } else {
+                    cube_color
+                }
+            }
+            ColorDepth::Ansi16 => *ANSI_16_PALETTE
+                .iter()
+                .min_by_key(|palette_color| color_distance(color, **palette_color))
+                .unwrap(),
+        }
+    }
+
+    /// An ASCII stand-in for glyphs the terminal may not be able to draw.
+    pub fn adapt_glyph(&self, glyph: char) -> char {
+        if self.is_unicode || glyph.is_ascii() {
+            return glyph;
+        }
+
+        match glyph {
+            '•' | '●' => '!',
+            '·' => '.',
+            '█' | '▓' | '▒' | '░' => '#',
+            '─' | '━' => '-',
+            '│' | '┃' => '|',
+            _ => '*',
+        }
+    }
+}
+
+fn nearest_cube_level(channel: u8) -> u8 {
+    *ANSI_256_CUBE_LEVELS
+        .iter()
+        .min_by_key(|level| (**level as i16 - channel as i16).abs())
+        .unwrap()
+}
+
+/// Squared distance between two colours, weighted towards green because the eye is most sensitive to it.
+fn color_distance(a: Rgb, b: Rgb) -> u32 {
+    let channel_distance = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+
+    2 * channel_distance(a.r(), b.r())
+        + 4 * channel_distance(a.g(), b.g())
+        + 3 * channel_distance(a.b(), b.b())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn detect_from(vars: &[(&str, &str)]) -> TerminalSupport {
+        let vars: HashMap<&str, &str> = vars.iter().copied().collect();
+
+        TerminalSupport::from_env(|name| vars.get(name).map(|value| String::from(*value)))
+    }
+
+    fn with_depth(color_depth: ColorDepth) -> TerminalSupport {
+        TerminalSupport {
+            color_depth,
+            ..TerminalSupport::full()
+        }
+    }
+
+    fn ascii_only() -> TerminalSupport {
+        TerminalSupport {
+            is_unicode: false,
+            ..TerminalSupport::full()
+        }
+    }
+
+    #[test]
+    fn colorterm_means_truecolor() {
+        let support = detect_from(&[("COLORTERM", "truecolor"), ("TERM", "xterm-256color")]);
+
+        assert_eq!(support.color_depth, ColorDepth::TrueColor);
+        assert_eq!(
+            detect_from(&[("COLORTERM", "24bit"), ("TERM", "linux")]).color_depth,
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            detect_from(&[("WT_SESSION", "1"), ("TERM", "linux")]).color_depth,
+            ColorDepth::TrueColor
+        );
+    }
+
+    #[test]
+    fn term_decides_the_colour_depth_without_colorterm() {
+        for (term, color_depth) in [
+            ("xterm-direct", ColorDepth::TrueColor),
+            ("xterm-256color", ColorDepth::Ansi256),
+            ("XTERM", ColorDepth::Ansi256),
+            ("screen", ColorDepth::Ansi256),
+            ("tmux-256color", ColorDepth::Ansi256),
+            ("linux", ColorDepth::Ansi16),
+            ("vt100", ColorDepth::Ansi16),
+        ] {
+            assert_eq!(
+                detect_from(&[("TERM", term)]).color_depth,
+                color_depth,
+                "{term}"
+            );
+        }
+    }
+
+    #[test]
+    fn empty_variables_count_as_unset() {
+        let support = detect_from(&[("COLORTERM", ""), ("TERM", "linux"), ("LC_ALL", "")]);
+
+        assert_eq!(support.color_depth, ColorDepth::Ansi16);
+        assert!(!support.is_unicode);
+    }
+
+    #[test]
+    fn no_term_is_treated_as_a_windows_console() {
+        assert_eq!(detect_from(&[]), TerminalSupport::full());
+    }
+
+    #[test]
+    fn a_utf8_locale_means_unicode() {
+        for locale in ["en_GB.UTF-8", "es_ES.utf8", "C.UTF-8"] {
+            assert!(
+                detect_from(&[("TERM", "linux"), ("LANG", locale)]).is_unicode,
+                "{locale}"
+            );
+        }
+
+        assert!(!detect_from(&[("TERM", "linux"), ("LANG", "C")]).is_unicode);
+        assert!(!detect_from(&[("TERM", "xterm")]).is_unicode);
+    }
+
+    #[test]
+    fn lc_all_takes_precedence_over_lang() {
+        let support = detect_from(&[("TERM", "xterm"), ("LC_ALL", "C"), ("LANG", "en_US.UTF-8")]);
+
+        assert!(!support.is_unicode);
+        assert!(
+            detect_from(&[
+                ("TERM", "xterm"),
+                ("LC_CTYPE", "en_US.UTF-8"),
+                ("LANG", "C")
+            ])
+            .is_unicode
+        );
+    }
+
+    #[test]
+    fn truecolor_keeps_colours_as_they_are() {
+        let color = Rgb(196, 63, 96);
+
+        assert_eq!(TerminalSupport::full().adapt_color(color), color);
+    }
+
+    #[test]
+    fn ansi_256_rounds_to_the_colour_cube() {
+        let support = with_depth(ColorDepth::Ansi256);
+
+        assert_eq!(support.adapt_color(Rgb(196, 63, 96)), Rgb(215, 95, 95));
+        assert_eq!(support.adapt_color(Rgb(255, 0, 0)), Rgb(255, 0, 0));
+    }
+
+    #[test]
+    fn ansi_256_rounds_greys_to_the_grey_ramp() {
+        let support = with_depth(ColorDepth::Ansi256);
+
+        assert_eq!(support.adapt_color(Rgb(128, 128, 128)), Rgb(128, 128, 128));
+        assert_eq!(support.adapt_color(Rgb(20, 20, 20)), Rgb(18, 18, 18));
+    }
+
+    #[test]
+    fn ansi_16_picks_from_the_basic_palette() {
+        let support = with_depth(ColorDepth::Ansi16);
+
+        assert_eq!(support.adapt_color(Rgb(250, 10, 10)), Rgb(255, 0, 0));
+        assert_eq!(support.adapt_color(Rgb(200, 200, 200)), Rgb(229, 229, 229));
+        assert_eq!(support.adapt_color(Rgb(20, 12, 0)), Rgb(0, 0, 0));
+
+        for color in [Rgb(196, 63, 96), Rgb(255, 176, 0), Rgb(86, 180, 233)] {
+            assert!(ANSI_16_PALETTE.contains(&support.adapt_color(color)));
+        }
+    }
+
+    #[test]
+    fn unicode_terminals_keep_every_glyph() {
+        for glyph in ['•', '█', '★', 'A'] {
+            assert_eq!(TerminalSupport::full().adapt_glyph(glyph), glyph);
+        }
+    }
+
+    #[test]
+    fn ascii_terminals_get_stand_ins() {
+        let support = ascii_only();
+
+        for (glyph, stand_in) in [
+            ('•', '!'),
+            ('·', '.'),
+            ('▓', '#'),
+            ('─', '-'),
+            ('┃', '|'),
+            ('★', '*'),
+            ('@', '@'),
+        ] {
+            assert_eq!(support.adapt_glyph(glyph), stand_in, "{glyph}");
+        }
+    }
+}
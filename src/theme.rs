@@ -0,0 +1,424 @@
+use std::{collections::HashMap, fs, path::Path, slice};
+
+use thomas::Rgb;
+
+use crate::{
+    parse_key_values, TerminalSupport, BOSS_COLOR, BOSS_WEAK_SPOT_COLOR, BUNKER_COLOR,
+    ENEMY_ALT_DISPLAY_CHAR, ENEMY_BULLET_DISPLAY_CHAR, ENEMY_COLOR, ENEMY_DISPLAY_CHAR,
+    PLAYER_BULLET_DISPLAY_CHAR, PLAYER_COLOR, PLAYER_DISPLAY_CHAR,
+};
+
+/// What a themed entity is, which decides the colour and glyph the theme gives it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThemeRole {
+    Player,
+    PlayerBullet,
+    Enemy,
+    EnemyBullet,
+    Boss,
+    BossWeakSpot,
+    Bunker,
+    Star,
+    /// Ordinary HUD and menu text.
+    Text,
+    /// Titles, the combo counter and unlocked achievements.
+    Highlight,
+    /// Good news, like bonuses and extra lives.
+    Positive,
+    /// Bad news, like falling behind the best time attack splits.
+    Negative,
+    /// Things that aren't available yet, like locked achievements.
+    Muted,
+}
+
+/// Every colour and glyph the game draws with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub background: Rgb,
+    pub player: Rgb,
+    pub enemy: Rgb,
+    pub boss: Rgb,
+    pub boss_weak_spot: Rgb,
+    pub bunker: Rgb,
+    pub star: Rgb,
+    pub text: Rgb,
+    pub highlight: Rgb,
+    pub positive: Rgb,
+    pub negative: Rgb,
+    pub muted: Rgb,
+    pub player_glyph: char,
+    /// The invader glyph, then the glyph it swaps to on every other formation step.
+    pub enemy_glyphs: [char; 2],
+    pub player_bullet_glyph: char,
+    pub enemy_bullet_glyph: char,
+    pub star_glyph: char,
+}
+impl Theme {
+    /// The original look of the game. The prefabs draw everything in these colours and glyphs until the theme
+    /// system restyles them.
+    pub fn classic() -> Self {
+        Self {
+            name: String::from("Classic"),
+            background: Rgb::black(),
+            player: PLAYER_COLOR,
+            enemy: ENEMY_COLOR,
+            boss: BOSS_COLOR,
+            boss_weak_spot: BOSS_WEAK_SPOT_COLOR,
+            bunker: BUNKER_COLOR,
+            star: Rgb::white(),
+            text: Rgb::white(),
+            highlight: Rgb::yellow(),
+            positive: Rgb::green(),
+            negative: Rgb::red(),
+            muted: Rgb(128, 128, 128),
+            player_glyph: PLAYER_DISPLAY_CHAR,
+            enemy_glyphs: [ENEMY_DISPLAY_CHAR, ENEMY_ALT_DISPLAY_CHAR],
+            player_bullet_glyph: PLAYER_BULLET_DISPLAY_CHAR,
+            enemy_bullet_glyph: ENEMY_BULLET_DISPLAY_CHAR,
+            star_glyph: '*',
+        }
+    }
+
+    pub fn amber_crt() -> Self {
+        Self {
+            name: String::from("Amber CRT"),
+            background: Rgb(20, 12, 0),
+            player: Rgb(255, 176, 0),
+            enemy: Rgb(255, 140, 0),
+            boss: Rgb(230, 120, 0),
+            boss_weak_spot: Rgb(255, 240, 180),
+            bunker: Rgb(200, 110, 0),
+            star: Rgb(120, 80, 0),
+            text: Rgb(255, 176, 0),
+            highlight: Rgb(255, 220, 120),
+            positive: Rgb(255, 200, 60),
+            negative: Rgb(170, 70, 0),
+            muted: Rgb(140, 95, 0),
+            ..Self::classic()
+        }
+    }
+
+    pub fn green_phosphor() -> Self {
+        Self {
+            name: String::from("Green Phosphor"),
+            background: Rgb(0, 16, 0),
+            player: Rgb(51, 255, 51),
+            enemy: Rgb(0, 200, 60),
+            boss: Rgb(0, 170, 0),
+            boss_weak_spot: Rgb(220, 255, 220),
+            bunker: Rgb(0, 150, 0),
+            star: Rgb(0, 100, 0),
+            text: Rgb(51, 255, 51),
+            highlight: Rgb(180, 255, 180),
+            positive: Rgb(120, 255, 120),
+            negative: Rgb(200, 255, 0),
+            muted: Rgb(0, 120, 0),
+            ..Self::classic()
+        }
+    }
+
+    /// Pure, saturated colours on black, with bullets that are easier to pick out than dots.
+    pub fn high_contrast() -> Self {
+        Self {
+            name: String::from("High Contrast"),
+            background: Rgb::black(),
+            player: Rgb::white(),
+            enemy: Rgb(255, 255, 0),
+            boss: Rgb(255, 255, 0),
+            boss_weak_spot: Rgb(255, 0, 255),
+            bunker: Rgb(0, 255, 0),
+            star: Rgb(128, 128, 128),
+            text: Rgb::white(),
+            highlight: Rgb(255, 255, 0),
+            positive: Rgb(0, 255, 255),
+            negative: Rgb(255, 0, 0),
+            muted: Rgb(160, 160, 160),
+            player_bullet_glyph: '|',
+            enemy_bullet_glyph: 'v',
+            ..Self::classic()
+        }
+    }
+
+    /// Built from the Okabe-Ito palette, so nothing relies on telling red from green.
+    pub fn deuteranopia_safe() -> Self {
+        Self {
+            name: String::from("Deuteranopia-safe"),
+            background: Rgb::black(),
+            player: Rgb(86, 180, 233),
+            enemy: Rgb(230, 159, 0),
+            boss: Rgb(213, 94, 0),
+            boss_weak_spot: Rgb(240, 228, 66),
+            bunker: Rgb(0, 158, 115),
+            star: Rgb(110, 110, 110),
+            text: Rgb::white(),
+            highlight: Rgb(240, 228, 66),
+            positive: Rgb(86, 180, 233),
+            negative: Rgb(213, 94, 0),
+            muted: Rgb(128, 128, 128),
+            ..Self::classic()
+        }
+    }
+
+    /// Every theme that ships with the game. Classic is always first.
+    pub fn built_ins() -> Vec<Self> {
+        vec![
+            Self::classic(),
+            Self::amber_crt(),
+            Self::green_phosphor(),
+            Self::high_contrast(),
+            Self::deuteranopia_safe(),
+        ]
+    }
+
+    /// Finds a built-in theme by name, ignoring case and treating spaces as dashes, so `amber-crt` finds Amber CRT.
+    pub fn find_built_in(name: &str) -> Option<Self> {
+        Self::built_ins()
+            .into_iter()
+            .find(|theme| theme.slug() == name.to_lowercase().replace(' ', "-"))
+    }
+
+    /// Loads a theme from a `key=value` file. Keys are the field names of `Theme`. Anything left out is taken from
+    /// Classic, and the name defaults to the file name.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read theme file {}: {e}", path.display()))?;
+
+        let entries = parse_key_values(&contents);
+        let mut theme = Self::from_key_values(&entries)
+            .map_err(|e| format!("Invalid theme file {}: {e}", path.display()))?;
+
+        if !entries.contains_key("name") {
+            if let Some(file_stem) = path.file_stem() {
+                theme.name = file_stem.to_string_lossy().into_owned();
+            }
+        }
+
+        Ok(theme)
+    }
+
+    pub fn from_key_values(entries: &HashMap<String, String>) -> Result<Self, String> {
+        let mut theme = Self::classic();
+
+        for (key, value) in entries {
+            match key.as_str() {
+                "name" => theme.name = value.clone(),
+                "background" => theme.background = parse_color(key, value)?,
+                "player" => theme.player = parse_color(key, value)?,
+                "enemy" => theme.enemy = parse_color(key, value)?,
+                "boss" => theme.boss = parse_color(key, value)?,
+                "boss_weak_spot" => theme.boss_weak_spot = parse_color(key, value)?,
+                "bunker" => theme.bunker = parse_color(key, value)?,
+                "star" => theme.star = parse_color(key, value)?,
+                "text" => theme.text = parse_color(key, value)?,
+                "highlight" => theme.highlight = parse_color(key, value)?,
+                "positive" => theme.positive = parse_color(key, value)?,
+                "negative" => theme.negative = parse_color(key, value)?,
+                "muted" => theme.muted = parse_color(key, value)?,
+                "player_glyph" => theme.player_glyph = parse_glyph(key, value)?,
+                "enemy_glyphs" => {
+                    theme.enemy_glyphs = match value.chars().collect::<Vec<char>>()[..] {
+                        [glyph] => [glyph, glyph],
+                        [glyph, alt_glyph] => [glyph, alt_glyph],
+                        _ => {
+                            return Err(format!(
+                                "{key} expects one or two characters, got '{value}'."
+                            ))
+                        }
+                    }
+                }
+                "player_bullet_glyph" => theme.player_bullet_glyph = parse_glyph(key, value)?,
+                "enemy_bullet_glyph" => theme.enemy_bullet_glyph = parse_glyph(key, value)?,
+                "star_glyph" => theme.star_glyph = parse_glyph(key, value)?,
+                _ => return Err(format!("Unknown key '{key}'.")),
+            }
+        }
+
+        Ok(theme)
+    }
+
+    /// This theme with every colour and glyph swapped for the closest one the terminal can show.
+    pub fn adapted_to(&self, terminal_support: &TerminalSupport) -> Self {
+        let color = |color: Rgb| terminal_support.adapt_color(color);
+        let glyph = |glyph: char| terminal_support.adapt_glyph(glyph);
+
+        Self {
+            name: self.name.clone(),
+            background: color(self.background),
+            player: color(self.player),
+            enemy: color(self.enemy),
+            boss: color(self.boss),
+            boss_weak_spot: color(self.boss_weak_spot),
+            bunker: color(self.bunker),
+            star: color(self.star),
+            text: color(self.text),
+            highlight: color(self.highlight),
+            positive: color(self.positive),
+            negative: color(self.negative),
+            muted: color(self.muted),
+            player_glyph: glyph(self.player_glyph),
+            enemy_glyphs: self.enemy_glyphs.map(glyph),
+            player_bullet_glyph: glyph(self.player_bullet_glyph),
+            enemy_bullet_glyph: glyph(self.enemy_bullet_glyph),
+            star_glyph: glyph(self.star_glyph),
+        }
+    }
+
+    pub fn slug(&self) -> String {
+        self.name.to_lowercase().replace(' ', "-")
+    }
+
+    pub fn color(&self, role: ThemeRole) -> Rgb {
+        match role {
+            ThemeRole::Player | ThemeRole::PlayerBullet => self.player,
+            ThemeRole::Enemy | ThemeRole::EnemyBullet => self.enemy,
+            ThemeRole::Boss => self.boss,
+            ThemeRole::BossWeakSpot => self.boss_weak_spot,
+            ThemeRole::Bunker => self.bunker,
+            ThemeRole::Star => self.star,
+            ThemeRole::Text => self.text,
+            ThemeRole::Highlight => self.highlight,
+            ThemeRole::Positive => self.positive,
+            ThemeRole::Negative => self.negative,
+            ThemeRole::Muted => self.muted,
+        }
+    }
+
+    /// The glyphs an entity with this role can show. Roles drawn with sprite art or text have none.
+    pub fn glyphs(&self, role: ThemeRole) -> &[char] {
+        match role {
+            ThemeRole::Player => slice::from_ref(&self.player_glyph),
+            ThemeRole::PlayerBullet => slice::from_ref(&self.player_bullet_glyph),
+            ThemeRole::Enemy => &self.enemy_glyphs,
+            ThemeRole::EnemyBullet => slice::from_ref(&self.enemy_bullet_glyph),
+            ThemeRole::Star => slice::from_ref(&self.star_glyph),
+            _ => &[],
+        }
+    }
+}
+
+/// Parses a colour written as `#rrggbb`.
+fn parse_color(key: &str, value: &str) -> Result<Rgb, String> {
+    let error = || format!("{key} expects a colour like #ff8800, got '{value}'.");
+
+    let hex = value
+        .strip_prefix('#')
+        .filter(|hex| hex.len() == 6 && hex.is_ascii())
+        .ok_or_else(error)?;
+    let channel =
+        |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).map_err(|_| error());
+
+    Ok(Rgb(channel(0)?, channel(2)?, channel(4)?))
+}
+
+fn parse_glyph(key: &str, value: &str) -> Result<char, String> {
+    match value.chars().collect::<Vec<char>>()[..] {
+        [glyph] => Ok(glyph),
+        _ => Err(format!("{key} expects a single character, got '{value}'.")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_file(contents: &str) -> Result<Theme, String> {
+        Theme::from_key_values(&parse_key_values(contents))
+    }
+
+    #[test]
+    fn parses_hex_colours() {
+        assert_eq!(parse_color("player", "#ff8800"), Ok(Rgb(255, 136, 0)));
+        assert_eq!(parse_color("player", "#00AbCd"), Ok(Rgb(0, 171, 205)));
+    }
+
+    #[test]
+    fn rejects_bad_hex_colours() {
+        for value in ["ff8800", "#ff880", "#ff88000", "#gg8800", "#ff88é", ""] {
+            assert_eq!(
+                parse_color("player", value),
+                Err(format!(
+                    "player expects a colour like #ff8800, got '{value}'."
+                )),
+            );
+        }
+    }
+
+    #[test]
+    fn reads_a_valid_file() {
+        let theme = from_file(
+            "name=Midnight\n\
+             background=#000010\n\
+             player=#00ff00\n\
+             enemy_glyphs=Mm\n\
+             star_glyph=.\n",
+        )
+        .unwrap();
+
+        assert_eq!(theme.name, "Midnight");
+        assert_eq!(theme.background, Rgb(0, 0, 16));
+        assert_eq!(theme.player, Rgb(0, 255, 0));
+        assert_eq!(theme.enemy_glyphs, ['M', 'm']);
+        assert_eq!(theme.star_glyph, '.');
+    }
+
+    #[test]
+    fn missing_keys_fall_back_to_classic() {
+        let theme = from_file("player=#00ff00\n").unwrap();
+        let classic = Theme::classic();
+
+        assert_eq!(theme.name, classic.name);
+        assert_eq!(theme.enemy, classic.enemy);
+        assert_eq!(theme.player_glyph, classic.player_glyph);
+        assert_eq!(
+            Theme {
+                player: classic.player,
+                ..theme
+            },
+            classic
+        );
+    }
+
+    #[test]
+    fn an_empty_file_is_classic() {
+        assert_eq!(from_file(""), Ok(Theme::classic()));
+    }
+
+    #[test]
+    fn a_bad_value_is_reported_with_its_key() {
+        assert_eq!(
+            from_file("enemy=#12345z\n"),
+            Err(String::from(
+                "enemy expects a colour like #ff8800, got '#12345z'."
+            )),
+        );
+        assert_eq!(
+            from_file("player_glyph=AB\n"),
+            Err(String::from(
+                "player_glyph expects a single character, got 'AB'."
+            )),
+        );
+        assert_eq!(
+            from_file("enemy_glyphs=XYZ\n"),
+            Err(String::from(
+                "enemy_glyphs expects one or two characters, got 'XYZ'."
+            )),
+        );
+    }
+
+    #[test]
+    fn an_unknown_key_is_an_error() {
+        assert_eq!(
+            from_file("player=#00ff00\nlaser=#ff0000\n"),
+            Err(String::from("Unknown key 'laser'.")),
+        );
+    }
+
+    #[test]
+    fn a_single_enemy_glyph_is_used_for_both_frames() {
+        assert_eq!(
+            from_file("enemy_glyphs=W\n").unwrap().enemy_glyphs,
+            ['W', 'W']
+        );
+    }
+}
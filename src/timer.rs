@@ -0,0 +1,79 @@
+use thomas::Timer;
+
+/// A `Timer` that can be paused and picked up again where it left off, for clocks that shouldn't run while play is
+/// paused.
+pub struct PausableTimer {
+    timer: Timer,
+    /// The time counted before the latest pause.
+    banked_millis: u128,
+    is_paused: bool,
+}
+impl PausableTimer {
+    /// A timer that isn't started.
+    pub fn new() -> Self {
+        Self {
+            timer: Timer::new(),
+            banked_millis: 0,
+            is_paused: false,
+        }
+    }
+
+    pub fn start_new() -> Self {
+        Self {
+            timer: Timer::start_new(),
+            banked_millis: 0,
+            is_paused: false,
+        }
+    }
+
+    /// A running timer that's already counted `elapsed_millis`, for a clock picked back up from a save.
+    pub fn start_from(elapsed_millis: u128) -> Self {
+        Self {
+            timer: Timer::start_new(),
+            banked_millis: elapsed_millis,
+            is_paused: false,
+        }
+    }
+
+    pub fn restart(&mut self) {
+        self.banked_millis = 0;
+        self.is_paused = false;
+        self.timer.restart();
+    }
+
+    pub fn stop(&mut self) {
+        self.banked_millis = 0;
+        self.is_paused = false;
+        self.timer.stop();
+    }
+
+    /// Holds the time counted so far. Has no effect on a timer that isn't running.
+    pub fn pause(&mut self) {
+        if self.timer.is_running() {
+            self.banked_millis += self.timer.elapsed_millis();
+            self.is_paused = true;
+            self.timer.stop();
+        }
+    }
+
+    /// Carries on counting from where the timer was paused. Has no effect on a timer that isn't paused.
+    pub fn resume(&mut self) {
+        if self.is_paused {
+            self.is_paused = false;
+            self.timer.restart();
+        }
+    }
+
+    pub fn elapsed_millis(&self) -> u128 {
+        self.banked_millis + self.timer.elapsed_millis()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.timer.is_running()
+    }
+}
+impl Default for PausableTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}